@@ -0,0 +1,62 @@
+//! Fetching the same host over and over with one shared client, the way the
+//! server does, against a fresh client for every fetch.
+//!
+//! `cargo bench -p ascii_artinator_api`
+
+#![allow(clippy::needless_return)]
+
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+const FETCHES: u32 = 200;
+
+// answers every request on every connection with a tiny body, keeping the
+// connections open like a real image host would
+fn serve() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            std::thread::spawn(move || {
+                let mut buf = [0; 4096];
+                while let Ok(n) = stream.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                    let resp = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                    if stream.write_all(resp.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    return format!("http://{}/", addr);
+}
+
+fn time(name: &str, mut fetch: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..FETCHES {
+        fetch();
+    }
+    let per_fetch = start.elapsed() / FETCHES;
+    println!("{:>14}: {:?} per fetch", name, per_fetch);
+    return per_fetch;
+}
+
+fn main() {
+    let url = serve();
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let get = |client: &reqwest::Client| {
+        runtime.block_on(async {
+            client.get(&url).send().await.unwrap().bytes().await.unwrap();
+        });
+    };
+    let shared = reqwest::Client::new();
+    let reused = time("shared client", || get(&shared));
+    let fresh = time("fresh clients", || get(&reqwest::Client::new()));
+    println!("{:>14}: {:.1}x faster", "sharing", fresh.as_secs_f64() / reused.as_secs_f64());
+}
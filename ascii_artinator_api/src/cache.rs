@@ -0,0 +1,64 @@
+//! The results of recent conversions, so the same link with the same options
+//! isn't fetched and converted all over again.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::Converted;
+
+/// As long as the responses tell everyone downstream they may cache them.
+const MAX_AGE: Duration = Duration::from_secs(3600);
+
+pub struct ResultCache {
+    capacity: usize,
+    entries: HashMap<String, (Instant, Converted)>,
+    // keys oldest first, for evicting
+    order: VecDeque<String>,
+}
+
+impl ResultCache {
+    /// A capacity of 0 keeps nothing at all.
+    pub fn new(capacity: usize) -> Self {
+        return Self { capacity, entries: HashMap::new(), order: VecDeque::new() };
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<Converted> {
+        let (stored, converted) = self.entries.get(key)?;
+        if stored.elapsed() < MAX_AGE {
+            return Some(converted.clone());
+        }
+        self.entries.remove(key);
+        self.order.retain(|k| k != key);
+        return None;
+    }
+
+    pub fn insert(&mut self, key: String, converted: Converted) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), (Instant::now(), converted)).is_none() {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Roughly how much memory the entries take, counting only the keys and
+    /// bodies since they're most of it.
+    pub fn approx_bytes(&self) -> usize {
+        return self.entries.iter().map(|(key, (_, converted))| key.len() + converted.body.len()).sum();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
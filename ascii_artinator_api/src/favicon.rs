@@ -0,0 +1,87 @@
+//! Finding the favicon of a website, for `favicon=true`.
+
+use ascii_artinator_core::ConvertError;
+
+/// Gets the value of an attribute from the inside of a tag, like
+/// `rel="icon" href=/x.png`, quoted or not.
+pub fn attr_value(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(name) {
+        let start = from + found;
+        from = start + name.len();
+        // has to be a whole attribute name, not the end of another one
+        let before = lower[..start].chars().next_back();
+        if before.is_some_and(|c| !c.is_ascii_whitespace()) {
+            continue;
+        }
+        let rest = tag[from..].trim_start();
+        let Some(rest) = rest.strip_prefix('=') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or(""),
+            _ => rest.split(|c: char| c.is_ascii_whitespace() || c == '>').next().unwrap_or(""),
+        };
+        return Some(value.to_owned());
+    }
+    return None;
+}
+
+/// The insides of every `<name ...>` tag in the page, in page order.
+pub fn tags<'a>(html: &'a str, name: &str) -> Vec<&'a str> {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let mut tags = Vec::new();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(&open) {
+        let start = from + found + open.len();
+        let end = lower[start..].find('>').map(|end| start + end).unwrap_or(lower.len());
+        from = end;
+        // `<img` isn't `<imgur-thing`
+        if lower[start..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-') {
+            continue;
+        }
+        tags.push(&html[start..end]);
+    }
+    return tags;
+}
+
+/// The hrefs of every `<link>` with "icon" in its rel, in page order.
+fn icon_links(html: &str) -> Vec<String> {
+    return tags(html, "link").into_iter()
+        .filter(|tag| {
+            return attr_value(tag, "rel")
+                .is_some_and(|rel| rel.to_ascii_lowercase().split_whitespace().any(|r| r.contains("icon")));
+        })
+        .filter_map(|tag| attr_value(tag, "href"))
+        .collect();
+}
+
+/// Finds the favicon for a site: the `<link rel="icon">`s in its page first,
+/// then plain old `/favicon.ico`. The page and every candidate go through
+/// [`crate::fetch_link`], with its caps and timeouts, and the first one that
+/// looks like an image comes back with its URL, already fetched.
+pub async fn resolve(site_url: &str) -> Result<(String, crate::Fetched), ConvertError> {
+    let page = crate::fetch_link(site_url).await?;
+    // relative links are relative to wherever the redirects ended up
+    let base = page.url.clone();
+    let html = String::from_utf8_lossy(&page.bytes).into_owned();
+    // its permit goes back before the candidates take theirs
+    drop(page);
+    let mut candidates: Vec<reqwest::Url> = icon_links(&html).iter()
+        .filter_map(|href| base.join(href).ok())
+        .collect();
+    if let Ok(ico) = base.join("/favicon.ico") {
+        candidates.push(ico);
+    }
+    for candidate in candidates {
+        // anything that comes back and isn't a page could be an image
+        match crate::fetch_link(candidate.as_str()).await {
+            Ok(fetched) if !fetched.is_html => return Ok((candidate.to_string(), fetched)),
+            _ => continue,
+        }
+    }
+    return Err(ConvertError::NoFavicon);
+}
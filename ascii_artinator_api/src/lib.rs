@@ -0,0 +1,1102 @@
+//! The HTTP side of things: the routes, fetching images for them and the
+//! instance's settings, read from `AA_*` variables the first time they're
+//! needed. The binary just binds a port and [`run`]s this.
+
+#![allow(clippy::needless_return)]
+
+use actix_web::{Responder, delete, get, post};
+use ascii_artinator_core::{ConvertError, ConvertOptions, Format, Frames};
+use rand::Rng;
+use std::collections::HashMap;
+
+mod cache;
+mod favicon;
+mod page;
+
+/// The variable `var` parsed, or `default` if it isn't set; panics on one
+/// that doesn't parse, so bad settings fail loudly.
+pub fn env_or<T: std::str::FromStr>(var: &str, default: T) -> T {
+    return match std::env::var(var) {
+        Ok(val) => val.parse().unwrap_or_else(|_| panic!("bad value for {}: {}", var, val)),
+        Err(_) => default,
+    };
+}
+
+fn default_max_conversions() -> usize {
+    return std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+}
+
+lazy_static::lazy_static! {
+    // bounds how many images are being decoded and converted at once, so a
+    // burst of requests can't eat all the memory
+    static ref CONVERSION_PERMITS: tokio::sync::Semaphore = tokio::sync::Semaphore::new(
+        env_or("AA_MAX_CONVERSIONS", default_max_conversions())
+    );
+    static ref CONVERSION_WAIT: std::time::Duration = std::time::Duration::from_millis(
+        env_or("AA_CONVERSION_WAIT_MS", 10000)
+    );
+    // how many of a batch's images are fetched at once, across all batches,
+    // so a big one can't take every conversion permit from single images
+    static ref BATCH_PERMITS: tokio::sync::Semaphore = tokio::sync::Semaphore::new(
+        env_or("AA_MAX_BATCH_FETCHES", 2)
+    );
+    // how long connecting to an image's host may take, and then how long it
+    // may go without sending anything, headers or body; split so big images
+    // on slow links are fine but dead hosts fail fast
+    static ref CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(
+        env_or("AA_CONNECT_TIMEOUT_MS", 5000)
+    );
+    static ref READ_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(
+        env_or("AA_READ_TIMEOUT_MS", 30000)
+    );
+    // the one client everything outbound goes through, images and favicon
+    // lookups alike, so connections and TLS sessions to the same hosts get
+    // reused; cloning it is cheap, it's an Arc inside
+    static ref FETCH_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .connect_timeout(*CONNECT_TIMEOUT)
+        .user_agent(concat!("ascii_artinator/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .expect("can't build the http client");
+    static ref MAX_PIXELS: u64 = env_or("AA_MAX_PIXELS", ascii_artinator_core::DEFAULT_MAX_PIXELS);
+    // most bytes an image's body may have, whether its length is announced
+    // up front or it's chunked and only known once it's all in
+    static ref MAX_BYTES: usize = env_or("AA_MAX_BYTES", 32 * 1024 * 1024);
+    // a font for render.png over the built-in one, which has to have the
+    // braille block; one that can't be read fails at boot
+    static ref RENDER_FONT: Option<Vec<u8>> = std::env::var("AA_RENDER_FONT").ok().map(|path| {
+        return std::fs::read(&path).unwrap_or_else(|err| panic!("can't read AA_RENDER_FONT {}: {}", path, err));
+    });
+    static ref DEFAULT_OPTIONS: ConvertOptions = load_default_options();
+    // in production, error bodies only say what kind of thing went wrong, and
+    // the details go to the log
+    static ref PRODUCTION: bool = env_or("AA_PRODUCTION", false);
+    // every route lives under this, e.g. "/api/v1" for "/api/v1/braille"
+    static ref PATH_PREFIX: String = load_path_prefix();
+    // whether JPEGs that got cut off are converted as far as they go, marked
+    // with X-Partial-Decode, instead of being turned down
+    static ref PARTIAL_DECODE: bool = env_or("AA_PARTIAL_DECODE", true);
+    // conversions currently running, so identical requests arriving at the
+    // same time wait on the one already going instead of redoing it
+    static ref IN_FLIGHT: std::sync::Mutex<HashMap<String, SharedConversion>> = Default::default();
+    // finished conversions, keyed like IN_FLIGHT; 0 entries turns it off
+    static ref RESULT_CACHE: std::sync::Mutex<cache::ResultCache> = std::sync::Mutex::new(
+        cache::ResultCache::new(env_or("AA_CACHE_ENTRIES", 256))
+    );
+    // the key the admin routes want in X-Api-Key; without one set, there are
+    // no admin routes
+    static ref ADMIN_KEY: Option<String> = std::env::var("AA_ADMIN_KEY").ok().filter(|key| !key.is_empty());
+}
+
+fn load_path_prefix() -> String {
+    let prefix = env_or("AA_PATH_PREFIX", String::new());
+    let prefix = prefix.trim_end_matches('/');
+    if !prefix.is_empty() && !prefix.starts_with('/') {
+        panic!("bad value for AA_PATH_PREFIX: {} (has to start with a /)", prefix);
+    }
+    return prefix.to_owned();
+}
+
+// a TOML file of option = value, for the instance's defaults
+fn read_defaults_file(path: &str) -> HashMap<String, String> {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("can't read AA_DEFAULTS_FILE {}: {}", path, err));
+    let table: toml::Table = text.parse()
+        .unwrap_or_else(|err| panic!("bad AA_DEFAULTS_FILE {}: {}", path, err));
+    return table.into_iter().map(|(key, val)| {
+        let val = match val {
+            toml::Value::String(s) => s,
+            toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => val.to_string(),
+            _ => panic!("bad value in AA_DEFAULTS_FILE for {}: {}", key, val),
+        };
+        return (key, val);
+    }).collect();
+}
+
+// the instance's default options: AA_DEFAULTS_FILE first, then AA_DEFAULTS
+// (like a query string, "mode=edges&border=true") over it, then whatever
+// each request sets over both
+fn load_default_options() -> ConvertOptions {
+    let mut defaults = match std::env::var("AA_DEFAULTS_FILE") {
+        Ok(path) => read_defaults_file(&path),
+        Err(_) => HashMap::new(),
+    };
+    if let Ok(query) = std::env::var("AA_DEFAULTS") {
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, val) = pair.split_once('=').unwrap_or((pair, "true"));
+            defaults.insert(key.to_owned(), val.to_owned());
+        }
+    }
+    return ascii_artinator_core::parse_options(&defaults, &ConvertOptions::default())
+        .unwrap_or_else(|err| panic!("bad default options: {}", err));
+}
+
+fn render_png(ascii: &str, opts: &ConvertOptions) -> Result<Vec<u8>, ConvertError> {
+    let font = RENDER_FONT.as_deref().unwrap_or(ascii_artinator_core::render::DEFAULT_FONT);
+    let fg = ascii_artinator_core::render::parse_hex_color(&opts.render_fg).ok_or(ConvertError::Render)?;
+    let bg = ascii_artinator_core::render::parse_hex_color(&opts.render_bg).ok_or(ConvertError::Render)?;
+    return ascii_artinator_core::render::render_png(ascii, font, opts.font_size, fg, bg);
+}
+
+// the raw body of an image, what it might be, and the permit to work on it,
+// which has to be held until done
+struct Fetched {
+    bytes: actix_web::web::Bytes,
+    mime_format: Option<image::ImageFormat>,
+    ext_format: Option<image::ImageFormat>,
+    // where it ended up after redirects, and whether it's a page instead
+    url: reqwest::Url,
+    is_html: bool,
+    permit: tokio::sync::SemaphorePermit<'static>,
+}
+
+// a link to an image, or to a page with one on it; only the one page is
+// looked into, a page linking to another page is as far as it goes
+async fn fetch_bytes(img_url: &str) -> Result<Fetched, ConvertError> {
+    let fetched = fetch_link(img_url).await?;
+    if !fetched.is_html {
+        return Ok(fetched);
+    }
+    let found = page::image_link(&String::from_utf8_lossy(&fetched.bytes))
+        .and_then(|link| fetched.url.join(&link).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .ok_or(ConvertError::NoPageImage)?;
+    println!("{}: {} is a page, fetching {} off it", chrono::Utc::now(), img_url, found);
+    // its permit goes back before the image takes one
+    drop(fetched);
+    let image = fetch_link(found.as_str()).await?;
+    if image.is_html {
+        return Err(ConvertError::NoPageImage);
+    }
+    return Ok(image);
+}
+
+// one link, no looking into pages; capped at MAX_BYTES, timed by
+// READ_TIMEOUT, and only http or https
+async fn fetch_link(img_url: &str) -> Result<Fetched, ConvertError> {
+    // links only; the server never gets to read anything off its own disk
+    let is_link = reqwest::Url::parse(img_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+    if !is_link {
+        return Err(ConvertError::BadOption("only http and https links work here".to_owned()));
+    }
+    let sent = tokio::time::timeout(*READ_TIMEOUT, FETCH_CLIENT.get(img_url).send()).await;
+    let mut resp = match sent {
+        Ok(Ok(resp)) => resp,
+        Ok(Err(err)) => {
+            eprintln!("{}: fetch failed for {}: {}", chrono::Utc::now(), img_url, err);
+            if err.is_connect() && err.is_timeout() {
+                return Err(ConvertError::ConnectTimeout);
+            }
+            return Err(ConvertError::Fetch);
+        },
+        Err(_) => {
+            eprintln!("{}: no response in time from {}", chrono::Utc::now(), img_url);
+            return Err(ConvertError::ReadTimeout);
+        },
+    };
+    // an error page is no image, whatever's drawn on it
+    if !resp.status().is_success() {
+        eprintln!("{}: {} answered {}", chrono::Utc::now(), img_url, resp.status());
+        return Err(ConvertError::Fetch);
+    }
+    // the extension of the last path segment, as a hint if the bytes are
+    // ambiguous (the final URL, after any redirects)
+    let ext_format = resp.url().path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|name| name.rsplit_once('.'))
+        .and_then(|(_, ext)| image::ImageFormat::from_extension(ext));
+    let content_type = resp.headers().get("content-type")
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_owned);
+    let mime_format = content_type.as_deref().and_then(image::ImageFormat::from_mime_type);
+    let url = resp.url().clone();
+    if resp.content_length().is_some_and(|len| len > *MAX_BYTES as u64) {
+        eprintln!("{}: {} says it's over {} bytes", chrono::Utc::now(), img_url, *MAX_BYTES);
+        return Err(ConvertError::TooLarge);
+    }
+    let permit = match tokio::time::timeout(*CONVERSION_WAIT, CONVERSION_PERMITS.acquire()).await {
+        Ok(Ok(permit)) => permit,
+        _ => return Err(ConvertError::Busy),
+    };
+    // chunk by chunk, so the read timeout is for each wait and not the whole
+    // transfer
+    let mut bytes = Vec::new();
+    loop {
+        match tokio::time::timeout(*READ_TIMEOUT, resp.chunk()).await {
+            // a missing or lying Content-Length only comes out here, so the
+            // cap is checked as it goes, before the rest is read
+            Ok(Ok(Some(chunk))) if bytes.len() + chunk.len() > *MAX_BYTES => {
+                eprintln!("{}: {} went over {} bytes", chrono::Utc::now(), img_url, *MAX_BYTES);
+                return Err(ConvertError::TooLarge);
+            },
+            Ok(Ok(Some(chunk))) => bytes.extend_from_slice(&chunk),
+            Ok(Ok(None)) => break,
+            Ok(Err(err)) => {
+                eprintln!("{}: reading body failed for {}: {}", chrono::Utc::now(), img_url, err);
+                return Err(ConvertError::Fetch);
+            },
+            Err(_) => {
+                eprintln!("{}: body stalled for {}", chrono::Utc::now(), img_url);
+                return Err(ConvertError::ReadTimeout);
+            },
+        }
+    }
+    let is_html = page::is_html(content_type.as_deref(), &bytes);
+    return Ok(Fetched { bytes: bytes.into(), mime_format, ext_format, url, is_html, permit });
+}
+
+// `fut`, given up on once the deadline's past, if there is one
+async fn until_deadline<T>(
+    deadline: Option<std::time::Instant>,
+    fut: impl std::future::Future<Output = Result<T, ConvertError>>
+) -> Result<T, ConvertError> {
+    return match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), fut).await
+            .unwrap_or(Err(ConvertError::DeadlineExceeded)),
+        None => fut.await,
+    };
+}
+
+// a fetched and decoded image, and the permit to work on it
+struct FetchedImage {
+    img: image::DynamicImage,
+    // with frames=all, the ones after `img`
+    more: Vec<image::DynamicImage>,
+    // only part of it made it, see PARTIAL_DECODE
+    partial: bool,
+    permit: tokio::sync::SemaphorePermit<'static>,
+}
+
+// `prefetched` is what got fetched from `img_url` already, if it was
+async fn fetch_image(
+    img_url: &str,
+    opts: &ConvertOptions,
+    prefetched: Option<Fetched>
+) -> Result<FetchedImage, ConvertError> {
+    let fetched = match prefetched {
+        Some(fetched) => fetched,
+        None => until_deadline(opts.deadline, fetch_bytes(img_url)).await?,
+    };
+    let decoded = if opts.frames == Frames::All {
+        ascii_artinator_core::frames::decode_all_with_hints(
+            &fetched.bytes, fetched.mime_format, fetched.ext_format, *MAX_PIXELS, opts
+        )
+    } else {
+        ascii_artinator_core::decode_with_hints(
+            &fetched.bytes, fetched.mime_format, fetched.ext_format, *MAX_PIXELS, opts
+        ).map(|img| vec![img])
+    };
+    let (mut imgs, partial) = match decoded {
+        Ok(imgs) => (imgs, false),
+        Err(ConvertError::Decode) if *PARTIAL_DECODE => {
+            let img = ascii_artinator_core::decode_truncated_jpeg(&fetched.bytes, *MAX_PIXELS, opts)?;
+            println!("{}: partial decode of {}", chrono::Utc::now(), img_url);
+            (vec![img], true)
+        },
+        Err(err) => return Err(err),
+    };
+    // decoding a big one can take a while of its own
+    opts.check_deadline()?;
+    let img = imgs.remove(0);
+    return Ok(FetchedImage { img, more: imgs, partial, permit: fetched.permit });
+}
+
+// a finished conversion, with the aHash of the source for X-Image-Hash and
+// its Otsu threshold for X-Suggested-Threshold
+#[derive(Clone)]
+struct Converted {
+    body: Vec<u8>,
+    image_hash: u64,
+    suggested_threshold: u8,
+    // the one target_density picked, for X-Density-Threshold
+    density_threshold: Option<u8>,
+    partial: bool,
+}
+
+async fn make_braille(
+    img_url: &str,
+    opts: &ConvertOptions,
+    prefetched: Option<Fetched>
+) -> Result<Converted, ConvertError> {
+    let FetchedImage { img, more, partial, permit: _permit } = fetch_image(img_url, opts, prefetched).await?;
+    // a thumbnail is plenty for the hash and a histogram, and it's quick to
+    // get even from a huge image, where the deadline may already have gone
+    let thumb = img.thumbnail(256, 256);
+    opts.check_deadline()?;
+    let image_hash = ascii_artinator_core::hash::average_hash(&thumb);
+    let suggested_threshold = ascii_artinator_core::preprocess::otsu_threshold(&ascii_artinator_core::to_gray(&thumb));
+    let (mut ascii, applied) = ascii_artinator_core::convert_with_applied(img, opts)?;
+    // the rest of frames=all, one under the other
+    for frame in more {
+        ascii.push('\n');
+        ascii.push_str(&ascii_artinator_core::convert(frame, opts)?);
+    }
+    let density_threshold = opts.target_density.map(|_| applied.stencil_threshold);
+    // only plain text is meant for chat, so only it has chat's length limit
+    if opts.format == Some(Format::Text) && ascii.chars().count() > 500 {
+        return Err(ConvertError::TooTall)
+    }
+    let body = if opts.format == Some(Format::RenderPng) {
+        render_png(&ascii, opts)?
+    } else {
+        ascii_artinator_core::encode(ascii, opts.encoding).into_bytes()
+    };
+    return Ok(Converted { body, image_hash, suggested_threshold, density_threshold, partial });
+}
+
+type SharedConversion = futures::future::Shared<futures::future::BoxFuture<'static, Result<Converted, ConvertError>>>;
+
+// make_braille, but answered from the cache if it was done recently, or
+// joining an identical conversion if there's one going; `prefetched` only
+// gets used if it comes to converting
+async fn make_braille_shared(
+    img_url: String,
+    opts: ConvertOptions,
+    prefetched: Option<Fetched>
+) -> Result<Converted, ConvertError> {
+    use futures::FutureExt;
+    let key = format!("{}\n{:?}", img_url, ConvertOptions { deadline: None, ..opts.clone() });
+    if let Some(converted) = RESULT_CACHE.lock().unwrap().get(&key) {
+        return Ok(converted);
+    }
+    // a deadline is this request's own, so it can't wait on someone else's
+    // conversion or cut theirs short
+    if opts.deadline.is_some() {
+        let result = make_braille(&img_url, &opts, prefetched).await;
+        if let Ok(converted) = &result {
+            RESULT_CACHE.lock().unwrap().insert(key, converted.clone());
+        }
+        return result;
+    }
+    let conversion = {
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+        match in_flight.get(&key) {
+            Some(conversion) => conversion.clone(),
+            None => {
+                let done_key = key.clone();
+                let conversion = async move {
+                    let result = make_braille(&img_url, &opts, prefetched).await;
+                    // cached before it stops being in flight, so there's no
+                    // gap for the same request to start over in
+                    if let Ok(converted) = &result {
+                        RESULT_CACHE.lock().unwrap().insert(done_key.clone(), converted.clone());
+                    }
+                    IN_FLIGHT.lock().unwrap().remove(&done_key);
+                    return result;
+                }.boxed().shared();
+                in_flight.insert(key, conversion.clone());
+                conversion
+            },
+        }
+    };
+    return conversion.await;
+}
+
+fn error_status(err: &ConvertError) -> actix_web::http::StatusCode {
+    return match err {
+        ConvertError::TooLarge => actix_web::http::StatusCode::PAYLOAD_TOO_LARGE,
+        ConvertError::BadOption(_) => actix_web::http::StatusCode::BAD_REQUEST,
+        ConvertError::DeadlineExceeded => actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
+        _ => actix_web::http::StatusCode::OK,
+    };
+}
+
+// quality of a media type in an Accept header, 0 if it isn't listed
+fn accept_quality(accept: &str, media_type: &str) -> f32 {
+    for entry in accept.split(',') {
+        let mut parts = entry.split(';').map(|part| part.trim());
+        if parts.next() == Some(media_type) {
+            return parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+        }
+    }
+    return 0.0;
+}
+
+// browsers get html, everyone else (curl, bots) keeps getting plain text; only
+// an explicit preference for text/html over text/plain counts
+fn format_from_accept(http_req: &actix_web::HttpRequest) -> Format {
+    let accept = http_req.headers().get("accept").and_then(|h| h.to_str().ok()).unwrap_or("");
+    if accept_quality(accept, "text/html") > accept_quality(accept, "text/plain") {
+        return Format::Html;
+    }
+    return Format::Text;
+}
+
+// X-Request-Deadline-Ms, how long the client is willing to wait, counted
+// from now
+fn deadline_from(http_req: &actix_web::HttpRequest) -> Result<Option<std::time::Instant>, ConvertError> {
+    let Some(header) = http_req.headers().get("x-request-deadline-ms") else {
+        return Ok(None);
+    };
+    return match header.to_str().ok().and_then(|ms| ms.trim().parse::<u64>().ok()) {
+        Some(ms) => Ok(Some(std::time::Instant::now() + std::time::Duration::from_millis(ms))),
+        None => Err(ConvertError::BadOption("x-request-deadline-ms has to be a number of milliseconds".to_owned())),
+    };
+}
+
+fn make_etag(body: &[u8]) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    body.hash(&mut hasher);
+    return format!("\"{:016x}\"", hasher.finish());
+}
+
+fn etag_matches(http_req: &actix_web::HttpRequest, etag: &str) -> bool {
+    return match http_req.headers().get("if-none-match").and_then(|h| h.to_str().ok()) {
+        Some(tags) => tags.split(',').any(|tag| {
+            let tag = tag.trim();
+            return tag == "*" || tag.trim_start_matches("W/") == etag;
+        }),
+        None => false,
+    };
+}
+
+// what production mode says instead of the full message; bad options are
+// about the request itself, and their messages say which one was bad
+fn generic_message(err: &ConvertError) -> &'static str {
+    return match err.category() {
+        "fetch" => "couldn't fetch the image",
+        "busy" => "too many images at once, try again later",
+        "image" => "couldn't convert that image",
+        "unsupported" => "that isn't supported here",
+        "size" => "that's too big",
+        _ => "something went wrong on our end",
+    };
+}
+
+// the message as it goes out to the client
+fn client_message(err: &ConvertError) -> String {
+    if *PRODUCTION && !matches!(err, ConvertError::BadOption(_)) {
+        return generic_message(err).to_owned();
+    }
+    return err.to_string();
+}
+
+// errors are often transient (timeouts, busy server), never cache them
+fn error_response(err: ConvertError) -> actix_web::HttpResponse {
+    let mut resp = actix_web::HttpResponse::build(error_status(&err));
+    resp.insert_header(("cache-control", "no-store"))
+        .insert_header(("x-error-category", err.category()));
+    // lets bots tell "nothing to draw" apart from real failures
+    if err == ConvertError::FlatImage {
+        resp.insert_header(("x-flat-image", "true"));
+    }
+    if *PRODUCTION {
+        eprintln!("{}: error: {}", chrono::Utc::now(), err);
+    }
+    return resp.content_type("text/plain; charset=utf-8").body(client_message(&err));
+}
+
+// `favicon`, which isn't a conversion option: it changes what gets fetched
+fn take_favicon(query: &mut HashMap<String, String>) -> Result<bool, ConvertError> {
+    return match query.remove("favicon").as_deref() {
+        None | Some("false") => Ok(false),
+        Some("true") => Ok(true),
+        Some(val) => Err(ConvertError::BadOption(format!("bad value for favicon: {}", val))),
+    };
+}
+
+// `img_url` itself, or with `favicon=true` the icon found for it, which is
+// already fetched so it's not fetched again
+async fn favicon_or(
+    img_url: String,
+    use_favicon: bool,
+    deadline: Option<std::time::Instant>
+) -> Result<(String, Option<Fetched>), ConvertError> {
+    if !use_favicon {
+        return Ok((img_url, None));
+    }
+    let (icon_url, icon) = until_deadline(deadline, favicon::resolve(&img_url)).await?;
+    return Ok((icon_url, Some(icon)));
+}
+
+#[get("/braille")]
+async fn braille(
+    http_req: actix_web::HttpRequest,
+    query: actix_web::web::Query<HashMap<String, String>>
+) -> actix_web::HttpResponse {
+    let deadline = match deadline_from(&http_req) {
+        Ok(deadline) => deadline,
+        Err(err) => return error_response(err),
+    };
+    let mut query = query.into_inner();
+    let img_url = match query.remove("img_url") {
+        Some(img_url) => img_url,
+        None => return error_response(ConvertError::BadOption("img_url is missing".to_owned())),
+    };
+    println!("{}: {}", chrono::Utc::now(), img_url);
+    let use_favicon = match take_favicon(&mut query) {
+        Ok(use_favicon) => use_favicon,
+        Err(err) => return error_response(err),
+    };
+    let mut opts = match ascii_artinator_core::parse_options(&query, &DEFAULT_OPTIONS) {
+        Ok(opts) => opts,
+        Err(err) => return error_response(err),
+    };
+    let format = *opts.format.get_or_insert_with(|| format_from_accept(&http_req));
+    // only the client knows if NO_COLOR is set or if it's printing to a
+    // terminal, so it has to say in headers
+    let hint = |name: &str| http_req.headers().get(name).and_then(|h| h.to_str().ok()).unwrap_or("");
+    opts.resolve_color(!hint("x-no-color").is_empty(), hint("x-terminal") == "true");
+    let content_type = match format {
+        Format::Text => "text/plain; charset=utf-8",
+        Format::Html => "text/html; charset=utf-8",
+        Format::RenderPng => "image/png",
+        Format::Rust | Format::C => "text/plain; charset=utf-8",
+        Format::Jsonl => "application/x-ndjson; charset=utf-8",
+        Format::Multi => "application/json",
+    };
+    let (img_url, prefetched) = match favicon_or(img_url, use_favicon, deadline).await {
+        Ok(found) => found,
+        Err(err) => return error_response(err),
+    };
+    opts.source = Some(img_url.clone());
+    opts.deadline = deadline;
+    return match make_braille_shared(img_url, opts, prefetched).await {
+        Ok(Converted { body, image_hash, suggested_threshold, density_threshold, partial }) => {
+            let etag = make_etag(&body);
+            let not_modified = etag_matches(&http_req, &etag);
+            let mut resp = if not_modified {
+                actix_web::HttpResponse::NotModified()
+            } else {
+                actix_web::HttpResponse::Ok()
+            };
+            resp.insert_header(("etag", etag.as_str()))
+                .insert_header(("cache-control", "public, max-age=3600"))
+                .insert_header(("vary", "accept, x-no-color, x-terminal"))
+                .insert_header(("x-image-hash", format!("{:016x}", image_hash)))
+                .insert_header(("x-suggested-threshold", suggested_threshold.to_string()));
+            if partial {
+                resp.insert_header(("x-partial-decode", "true"));
+            }
+            if let Some(threshold) = density_threshold {
+                resp.insert_header(("x-density-threshold", threshold.to_string()));
+            }
+            if not_modified {
+                resp.finish()
+            } else {
+                resp.content_type(content_type).body(body)
+            }
+        },
+        Err(err) => error_response(err),
+    };
+}
+
+// /braille as a JSON object, with the chat text and what would otherwise be
+// in the headers, for clients that would rather parse one thing
+#[get("/braille.json")]
+async fn braille_json(
+    http_req: actix_web::HttpRequest,
+    query: actix_web::web::Query<HashMap<String, String>>
+) -> actix_web::HttpResponse {
+    let deadline = match deadline_from(&http_req) {
+        Ok(deadline) => deadline,
+        Err(err) => return error_response(err),
+    };
+    let mut query = query.into_inner();
+    let img_url = match query.remove("img_url") {
+        Some(img_url) => img_url,
+        None => return error_response(ConvertError::BadOption("img_url is missing".to_owned())),
+    };
+    println!("{}: json: {}", chrono::Utc::now(), img_url);
+    let use_favicon = match take_favicon(&mut query) {
+        Ok(use_favicon) => use_favicon,
+        Err(err) => return error_response(err),
+    };
+    let mut opts = match ascii_artinator_core::parse_options(&query, &DEFAULT_OPTIONS) {
+        Ok(opts) => opts,
+        Err(err) => return error_response(err),
+    };
+    // the text goes in a JSON string, so only text
+    match opts.format {
+        None | Some(Format::Text) => opts.format = Some(Format::Text),
+        Some(_) => return error_response(ConvertError::BadOption("braille.json only comes as text".to_owned())),
+    }
+    let (img_url, prefetched) = match favicon_or(img_url, use_favicon, deadline).await {
+        Ok(found) => found,
+        Err(err) => return error_response(err),
+    };
+    opts.source = Some(img_url.clone());
+    opts.deadline = deadline;
+    return match make_braille_shared(img_url.clone(), opts, prefetched).await {
+        Ok(Converted { body, image_hash, suggested_threshold, density_threshold, partial }) => {
+            actix_web::HttpResponse::Ok()
+                .insert_header(("cache-control", "public, max-age=3600"))
+                .json(serde_json::json!({
+                    "img_url": img_url,
+                    "braille": String::from_utf8_lossy(&body),
+                    "image_hash": format!("{:016x}", image_hash),
+                    "suggested_threshold": suggested_threshold,
+                    "density_threshold": density_threshold,
+                    "partial": partial,
+                }))
+        },
+        Err(err) => error_response(err),
+    };
+}
+
+// most frames /frames gives back at once
+const MAX_FRAME_PAGE: usize = 50;
+
+// pulls a count out of the query, with a default and an upper bound
+fn take_count(
+    query: &mut HashMap<String, String>,
+    key: &str,
+    default: usize,
+    max: usize
+) -> Result<usize, ConvertError> {
+    return match query.remove(key).map(|val| val.parse::<usize>()) {
+        None => Ok(default),
+        Some(Ok(n)) if n <= max => Ok(n),
+        Some(_) => Err(ConvertError::BadOption(format!("{} has to be between 0 and {}", key, max))),
+    };
+}
+
+// a page of the frames of an animated GIF or the pages of a TIFF, one
+// converted frame per line, with the total in X-Total-Frames; anything else
+// counts as a single frame
+#[get("/frames")]
+async fn frames(
+    http_req: actix_web::HttpRequest,
+    query: actix_web::web::Query<HashMap<String, String>>
+) -> actix_web::HttpResponse {
+    let deadline = match deadline_from(&http_req) {
+        Ok(deadline) => deadline,
+        Err(err) => return error_response(err),
+    };
+    let mut query = query.into_inner();
+    let img_url = match query.remove("img_url") {
+        Some(img_url) => img_url,
+        None => return error_response(ConvertError::BadOption("img_url is missing".to_owned())),
+    };
+    let offset = match take_count(&mut query, "offset", 0, ascii_artinator_core::frames::MAX_FRAMES) {
+        Ok(offset) => offset,
+        Err(err) => return error_response(err),
+    };
+    let limit = match take_count(&mut query, "limit", 10, MAX_FRAME_PAGE) {
+        Ok(limit) => limit,
+        Err(err) => return error_response(err),
+    };
+    let mut opts = match ascii_artinator_core::parse_options(&query, &DEFAULT_OPTIONS) {
+        Ok(opts) => opts,
+        Err(err) => return error_response(err),
+    };
+    // one line per frame only works for chat text
+    match opts.format {
+        None | Some(Format::Text) => opts.format = Some(Format::Text),
+        Some(_) => return error_response(ConvertError::BadOption("frames only come as text".to_owned())),
+    }
+    opts.deadline = deadline;
+    println!("{}: frames: {}", chrono::Utc::now(), img_url);
+    let fetched = until_deadline(deadline, fetch_bytes(&img_url)).await;
+    let fetched = match fetched {
+        Ok(fetched) => fetched,
+        Err(err) => return error_response(err),
+    };
+    // decoding every frame is the slow part, so it's a checkpoint first
+    if let Err(err) = opts.check_deadline() {
+        return error_response(err);
+    }
+    let candidates = ascii_artinator_core::format_candidates(
+        &fetched.bytes, fetched.mime_format, fetched.ext_format
+    );
+    let decoded = match candidates.first() {
+        Some(image::ImageFormat::Gif) => {
+            ascii_artinator_core::frames::gif_frames(&fetched.bytes, offset, limit, *MAX_PIXELS, deadline)
+        },
+        Some(image::ImageFormat::Tiff) => {
+            ascii_artinator_core::frames::tiff_pages(&fetched.bytes, offset, limit, *MAX_PIXELS, deadline)
+        },
+        _ => ascii_artinator_core::decode_with_hints(
+            &fetched.bytes, fetched.mime_format, fetched.ext_format, *MAX_PIXELS, &opts
+        ).map(|img| (vec![img].into_iter().skip(offset).take(limit).collect(), 1)),
+    };
+    let (imgs, total) = match decoded {
+        Ok(decoded) => decoded,
+        Err(err) => return error_response(err),
+    };
+    // every frame written straight into the one body; each one checks the
+    // deadline on its way through
+    let mut body = String::new();
+    for img in imgs {
+        if let Err(err) = ascii_artinator_core::write_output(&mut body, img, &opts, None) {
+            return error_response(err);
+        }
+        body.push('\n');
+    }
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("x-total-frames", total.to_string()))
+        .insert_header(("cache-control", "public, max-age=3600"))
+        .content_type("text/plain; charset=utf-8")
+        .body(body);
+}
+
+// the dominant colors of an image as a JSON array of hex colors, for picking
+// a matching theme
+#[get("/palette")]
+async fn palette(
+    http_req: actix_web::HttpRequest,
+    query: actix_web::web::Query<HashMap<String, String>>
+) -> actix_web::HttpResponse {
+    let deadline = match deadline_from(&http_req) {
+        Ok(deadline) => deadline,
+        Err(err) => return error_response(err),
+    };
+    let mut query = query.into_inner();
+    let img_url = match query.remove("img_url") {
+        Some(img_url) => img_url,
+        None => return error_response(ConvertError::BadOption("img_url is missing".to_owned())),
+    };
+    let n = match query.remove("n").map(|n| n.parse::<usize>()) {
+        None => 5,
+        Some(Ok(n)) if (1..=ascii_artinator_core::palette::MAX_COLORS).contains(&n) => n,
+        Some(_) => return error_response(ConvertError::BadOption(format!(
+            "n has to be between 1 and {}", ascii_artinator_core::palette::MAX_COLORS
+        ))),
+    };
+    if let Some(key) = query.keys().next() {
+        return error_response(ConvertError::BadOption(format!("unknown option: {}", key)));
+    }
+    println!("{}: palette: {}", chrono::Utc::now(), img_url);
+    let opts = ConvertOptions { deadline, ..DEFAULT_OPTIONS.clone() };
+    let FetchedImage { img, permit: _permit, .. } = match fetch_image(&img_url, &opts, None).await {
+        Ok(fetched) => fetched,
+        Err(err) => return error_response(err),
+    };
+    let colors: Vec<String> = ascii_artinator_core::palette::dominant_colors(&img, n).iter()
+        .map(|[r, g, b]| format!("#{:02x}{:02x}{:02x}", r, g, b))
+        .collect();
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("cache-control", "public, max-age=3600"))
+        .content_type("application/json")
+        .body(serde_json::to_string(&colors).unwrap());
+}
+
+// which braille chars a conversion comes out as and how full of dots it is,
+// for tuning options that leave it too sparse or too dense
+#[get("/braille/stats")]
+async fn stats(
+    http_req: actix_web::HttpRequest,
+    query: actix_web::web::Query<HashMap<String, String>>
+) -> actix_web::HttpResponse {
+    let deadline = match deadline_from(&http_req) {
+        Ok(deadline) => deadline,
+        Err(err) => return error_response(err),
+    };
+    let mut query = query.into_inner();
+    let img_url = match query.remove("img_url") {
+        Some(img_url) => img_url,
+        None => return error_response(ConvertError::BadOption("img_url is missing".to_owned())),
+    };
+    let mut opts = match ascii_artinator_core::parse_options(&query, &DEFAULT_OPTIONS) {
+        Ok(opts) => opts,
+        Err(err) => return error_response(err),
+    };
+    if opts.charset != ascii_artinator_core::Charset::Braille {
+        return error_response(ConvertError::BadOption("stats are only for braille".to_owned()));
+    }
+    // blanks have to read back as blank, which the dot ones wouldn't
+    opts.blank_mode = ascii_artinator_core::BlankMode::Braille;
+    opts.deadline = deadline;
+    println!("{}: stats: {}", chrono::Utc::now(), img_url);
+    let FetchedImage { img, permit: _permit, .. } = match fetch_image(&img_url, &opts, None).await {
+        Ok(fetched) => fetched,
+        Err(err) => return error_response(err),
+    };
+    let rows = match ascii_artinator_core::convert_to_rows(img, &opts, None) {
+        Ok(rows) => rows,
+        Err(err) => return error_response(err),
+    };
+    let grid = ascii_artinator_core::braille::BrailleGrid::from_rows(&rows);
+    let stats = grid.stats();
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("cache-control", "public, max-age=3600"))
+        .json(serde_json::json!({
+            "cols": grid.cols(),
+            "rows": grid.rows(),
+            "fill": stats.fill,
+            "counts": stats.counts,
+        }));
+}
+
+// how big /braille would come out for an image and options, from the size in
+// the image's header, without decoding or converting it
+#[get("/braille/estimate")]
+async fn estimate(
+    http_req: actix_web::HttpRequest,
+    query: actix_web::web::Query<HashMap<String, String>>
+) -> actix_web::HttpResponse {
+    let deadline = match deadline_from(&http_req) {
+        Ok(deadline) => deadline,
+        Err(err) => return error_response(err),
+    };
+    let mut query = query.into_inner();
+    let img_url = match query.remove("img_url") {
+        Some(img_url) => img_url,
+        None => return error_response(ConvertError::BadOption("img_url is missing".to_owned())),
+    };
+    let mut opts = match ascii_artinator_core::parse_options(&query, &DEFAULT_OPTIONS) {
+        Ok(opts) => opts,
+        Err(err) => return error_response(err),
+    };
+    opts.source = Some(img_url.clone());
+    opts.deadline = deadline;
+    println!("{}: estimate: {}", chrono::Utc::now(), img_url);
+    let fetched = match until_deadline(deadline, fetch_bytes(&img_url)).await {
+        Ok(fetched) => fetched,
+        Err(err) => return error_response(err),
+    };
+    let dimensions = ascii_artinator_core::estimate::image_dimensions(
+        &fetched.bytes, fetched.mime_format, fetched.ext_format
+    );
+    let (width, height) = match dimensions {
+        Ok(dimensions) => dimensions,
+        Err(err) => return error_response(err),
+    };
+    let estimate = match ascii_artinator_core::estimate::estimate_size(width, height, &opts) {
+        Ok(estimate) => estimate,
+        Err(err) => return error_response(err),
+    };
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("cache-control", "public, max-age=3600"))
+        .json(serde_json::json!({
+            "width": width,
+            "height": height,
+            "cols": estimate.cols,
+            "rows": estimate.rows,
+            "bytes": estimate.bytes,
+        }));
+}
+
+/// Most links one batch may have.
+const MAX_BATCH: usize = 16;
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchRequest {
+    img_urls: Vec<String>,
+}
+
+// several links converted with the same options from the query, as a JSON
+// array with each one's braille or error, in order; one failing doesn't
+// take the others down with it. Each image's hash comes along, and one that
+// looks like an earlier one in the batch says which with `duplicate_of`
+#[post("/braille/batch")]
+async fn braille_batch(
+    query: actix_web::web::Query<HashMap<String, String>>,
+    body: actix_web::web::Bytes
+) -> actix_web::HttpResponse {
+    let req: BatchRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(err) => return error_response(ConvertError::BadOption(format!("that's not the JSON I wanted: {}", err))),
+    };
+    if req.img_urls.len() > MAX_BATCH {
+        return error_response(ConvertError::BadOption(format!("at most {} links per batch", MAX_BATCH)));
+    }
+    let mut opts = match ascii_artinator_core::parse_options(&query.into_inner(), &DEFAULT_OPTIONS) {
+        Ok(opts) => opts,
+        Err(err) => return error_response(err),
+    };
+    // the results go in JSON strings, so only text
+    match opts.format {
+        None | Some(Format::Text) => opts.format = Some(Format::Text),
+        Some(_) => return error_response(ConvertError::BadOption("batches only come as text".to_owned())),
+    }
+    println!("{}: batch of {}", chrono::Utc::now(), req.img_urls.len());
+    let results = futures::future::join_all(req.img_urls.into_iter().map(|img_url| {
+        let mut opts = opts.clone();
+        return async move {
+            let _permit = BATCH_PERMITS.acquire().await.expect("the batch semaphore is never closed");
+            opts.source = Some(img_url.clone());
+            return match make_braille_shared(img_url.clone(), opts, None).await {
+                Ok(converted) => (serde_json::json!({
+                    "img_url": img_url,
+                    "braille": String::from_utf8_lossy(&converted.body),
+                    "image_hash": format!("{:016x}", converted.image_hash),
+                }), Some(converted.image_hash)),
+                Err(err) => (serde_json::json!({
+                    "img_url": img_url,
+                    "error": client_message(&err),
+                }), None),
+            };
+        };
+    })).await;
+    let hashes: Vec<Option<u64>> = results.iter().map(|(_, hash)| *hash).collect();
+    let results: Vec<serde_json::Value> = results.into_iter().enumerate().map(|(i, (mut result, hash))| {
+        let earlier = hash.and_then(|hash| hashes[..i].iter().position(|other| {
+            return other.is_some_and(|other| ascii_artinator_core::hash::is_near_duplicate(hash, other));
+        }));
+        if let Some(earlier) = earlier {
+            result["duplicate_of"] = earlier.into();
+        }
+        return result;
+    }).collect();
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("cache-control", "no-store"))
+        .json(results);
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextRequest {
+    text: String,
+    #[serde(default)]
+    font: ascii_artinator_core::TextFont,
+    #[serde(default)]
+    blank_mode: ascii_artinator_core::BlankMode,
+}
+
+// text drawn straight to braille in big letters, no image involved
+#[post("/braille/text")]
+async fn braille_text(body: actix_web::web::Bytes) -> actix_web::HttpResponse {
+    let req: TextRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(err) => return error_response(ConvertError::BadOption(format!("that's not the JSON I wanted: {}", err))),
+    };
+    println!("{}: text: {:?}", chrono::Utc::now(), req.text);
+    return match ascii_artinator_core::text_to_braille(&req.text, req.font, req.blank_mode) {
+        Ok(rows) => actix_web::HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(ascii_artinator_core::rows::join_rows(&rows)),
+        Err(err) => error_response(err),
+    };
+}
+
+fn load_words() -> Vec<String> {
+    std::fs::read_to_string("10000-english-no-swears.txt")
+        .unwrap()
+        .lines()
+        .map(|c| c.to_owned())
+        .collect()
+}
+
+lazy_static::lazy_static! {
+    static ref WORD_LIST: Vec<String> = load_words();
+}
+
+fn generate_zoazo() -> String{
+    let mut rng = rand::thread_rng();
+    let zoazo_short = rng.gen_bool(0.5);
+    let zoazo_len = if zoazo_short { 1 } else { rng.gen_range(2..=5) };
+    let mut zoazo_emote;
+    // one in 1000 chance of it being TTS TROLLED
+    if rng.gen_bool(0.001) {
+        zoazo_emote = String::from("hili zoazo");
+    } else {
+        zoazo_emote = String::from("zoazo");
+    }
+    for _ in 0..zoazo_len {
+        let mut rand_word = WORD_LIST[rng.gen_range(0..WORD_LIST.len())].clone();
+        rand_word = rand_word
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i == 0 { return c.to_ascii_uppercase() } else { return c })
+            .collect::<String>();
+        zoazo_emote.push_str(&rand_word);
+    }
+    return zoazo_emote;
+}
+
+// what to answer a request for an admin route without the admin key, or
+// nothing if it has it
+fn admin_rejection(http_req: &actix_web::HttpRequest) -> Option<actix_web::HttpResponse> {
+    let Some(admin_key) = ADMIN_KEY.as_ref() else {
+        return Some(actix_web::HttpResponse::NotFound().finish());
+    };
+    let given = http_req.headers().get("x-api-key").and_then(|h| h.to_str().ok());
+    if given != Some(admin_key.as_str()) {
+        return Some(actix_web::HttpResponse::Unauthorized().body("wrong key, nice try"));
+    }
+    return None;
+}
+
+#[get("/cache")]
+async fn cache_info(http_req: actix_web::HttpRequest) -> actix_web::HttpResponse {
+    if let Some(resp) = admin_rejection(&http_req) {
+        return resp;
+    }
+    let cache = RESULT_CACHE.lock().unwrap();
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("cache-control", "no-store"))
+        .json(serde_json::json!({ "entries": cache.len(), "bytes": cache.approx_bytes() }));
+}
+
+#[delete("/cache")]
+async fn cache_clear(http_req: actix_web::HttpRequest) -> actix_web::HttpResponse {
+    if let Some(resp) = admin_rejection(&http_req) {
+        return resp;
+    }
+    let mut cache = RESULT_CACHE.lock().unwrap();
+    let before = cache.len();
+    cache.clear();
+    println!("{}: cache cleared, {} entries", chrono::Utc::now(), before);
+    return actix_web::HttpResponse::Ok()
+        .insert_header(("cache-control", "no-store"))
+        .json(serde_json::json!({ "before": before, "after": cache.len() }));
+}
+
+#[get("/zoazo")]
+async fn zoazo() -> impl Responder {
+    let zoazo_emote = generate_zoazo();
+    println!("{}: zoazoEmote: {}", chrono::Utc::now(), zoazo_emote);
+    return zoazo_emote;
+}
+
+#[get("/health")]
+async fn health() -> actix_web::HttpResponse {
+    return actix_web::HttpResponse::Ok().insert_header(("cache-control", "no-store")).body("ok");
+}
+
+/// Every route, under `AA_PATH_PREFIX`, for `App::new().configure(routes)`;
+/// the settings they need are read as they're first used.
+pub fn routes(cfg: &mut actix_web::web::ServiceConfig) {
+    cfg.service(
+        actix_web::web::scope(&PATH_PREFIX)
+            .service(health)
+            .service(braille)
+            .service(braille_json)
+            .service(braille_text)
+            .service(braille_batch)
+            .service(estimate)
+            .service(stats)
+            .service(palette)
+            .service(frames)
+            .service(cache_info)
+            .service(cache_clear)
+            .service(zoazo)
+    );
+}
+
+/// Reads every setting at once, so bad values fail at boot rather than on
+/// whichever request first needs them.
+pub fn load_settings() {
+    lazy_static::initialize(&CONVERSION_PERMITS);
+    lazy_static::initialize(&CONVERSION_WAIT);
+    lazy_static::initialize(&BATCH_PERMITS);
+    lazy_static::initialize(&CONNECT_TIMEOUT);
+    lazy_static::initialize(&READ_TIMEOUT);
+    lazy_static::initialize(&FETCH_CLIENT);
+    println!(
+        "{}: fetching with a {:?} connect timeout and a {:?} read timeout",
+        chrono::Utc::now(), *CONNECT_TIMEOUT, *READ_TIMEOUT
+    );
+    lazy_static::initialize(&MAX_PIXELS);
+    lazy_static::initialize(&MAX_BYTES);
+    lazy_static::initialize(&DEFAULT_OPTIONS);
+    lazy_static::initialize(&PRODUCTION);
+    lazy_static::initialize(&PATH_PREFIX);
+    lazy_static::initialize(&PARTIAL_DECODE);
+    lazy_static::initialize(&RESULT_CACHE);
+    lazy_static::initialize(&ADMIN_KEY);
+    #[cfg(feature = "render")]
+    lazy_static::initialize(&RENDER_FONT);
+}
+
+/// The server on `listener`, with the settings loaded; await it to serve
+/// until it's stopped, through its handle.
+pub fn run(listener: std::net::TcpListener) -> std::io::Result<actix_web::dev::Server> {
+    load_settings();
+    return Ok(actix_web::HttpServer::new(|| actix_web::App::new().configure(routes)).listen(listener)?.run());
+}
@@ -0,0 +1,30 @@
+//! Finding the image on a webpage, for when the link pasted is the page the
+//! image is on and not the image itself.
+
+use crate::favicon::{attr_value, tags};
+
+/// Whether what came back is a page, by its content type or, for hosts that
+/// don't send one, by how it starts.
+pub fn is_html(content_type: Option<&str>, bytes: &[u8]) -> bool {
+    if content_type.is_some_and(|ct| ct.starts_with("text/html")) {
+        return true;
+    }
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(64)]).trim_start().to_ascii_lowercase();
+    return start.starts_with("<!doctype html") || start.starts_with("<html");
+}
+
+/// The page's image: its `og:image`, which is the one it wants shown for
+/// it, or else the first `<img>` in it.
+pub fn image_link(html: &str) -> Option<String> {
+    let og_image = tags(html, "meta").into_iter().find_map(|tag| {
+        let property = attr_value(tag, "property").or_else(|| attr_value(tag, "name"))?;
+        if !property.eq_ignore_ascii_case("og:image") {
+            return None;
+        }
+        return attr_value(tag, "content").filter(|content| !content.is_empty());
+    });
+    return og_image.or_else(|| {
+        return tags(html, "img").into_iter()
+            .find_map(|tag| attr_value(tag, "src").filter(|src| !src.is_empty()));
+    });
+}
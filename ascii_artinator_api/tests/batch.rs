@@ -0,0 +1,142 @@
+//! A batch never fetches more of its images at once than AA_MAX_BATCH_FETCHES
+//! allows, the links that fail don't take the others down, and images that
+//! look alike are pointed out.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::{Api, Served};
+
+const LIMIT: usize = 2;
+
+fn start_api() -> Api {
+    return Api::start(&[
+        ("AA_MAX_BATCH_FETCHES", &LIMIT.to_string()),
+        // plenty, so only the batch limit holds anything back
+        ("AA_MAX_CONVERSIONS", "16"),
+    ]);
+}
+
+// serves the circle slowly on every path but /missing.png, counting how
+// many requests it's in the middle of at once; returns the base link and
+// the most it ever saw
+fn serve_counting() -> (String, Arc<AtomicUsize>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let png = common::circle_png();
+    let active = Arc::new(AtomicUsize::new(0));
+    let most = Arc::new(AtomicUsize::new(0));
+    let seen = most.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let (active, most, png) = (active.clone(), most.clone(), png.clone());
+            std::thread::spawn(move || {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                let mut buf = [0; 4096];
+                let n = stream.read(&mut buf).unwrap();
+                let missing = String::from_utf8_lossy(&buf[..n]).starts_with("GET /missing.png");
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                let body = if missing { b"nope".to_vec() } else { png };
+                let head = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    if missing { "404 Not Found" } else { "200 OK" },
+                    if missing { "text/plain" } else { "image/png" },
+                    body.len()
+                );
+                active.fetch_sub(1, Ordering::SeqCst);
+                let _ = stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(&body));
+            });
+        }
+    });
+    return (format!("http://{}", addr), seen);
+}
+
+#[test]
+fn never_over_the_limit() {
+    let api = start_api();
+    let (base, most) = serve_counting();
+    // different queries, so none of them come out of the cache
+    let img_urls: Vec<String> = (0..6).map(|i| format!("{}/circle.png?n={}", base, i)).collect();
+    let url = api.url("/braille/batch?cols=8&rows=2");
+    let resp = reqwest::blocking::Client::new()
+        .post(url)
+        .body(serde_json::json!({ "img_urls": img_urls }).to_string())
+        .send()
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let results: Vec<serde_json::Value> = serde_json::from_str(&resp.text().unwrap()).unwrap();
+    assert_eq!(results.len(), 6);
+    assert!(results.iter().all(|result| result["braille"].is_string()), "{:?}", results);
+    let most = most.load(Ordering::SeqCst);
+    assert!(most <= LIMIT, "{} fetches at once", most);
+    assert!(most > 0);
+}
+
+#[test]
+fn partial_failures_keep_the_rest() {
+    let api = start_api();
+    let (base, _) = serve_counting();
+    let img_urls = [format!("{}/circle.png", base), format!("{}/missing.png", base)];
+    let url = api.url("/braille/batch");
+    let resp = reqwest::blocking::Client::new()
+        .post(url)
+        .body(serde_json::json!({ "img_urls": img_urls }).to_string())
+        .send()
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let results: Vec<serde_json::Value> = serde_json::from_str(&resp.text().unwrap()).unwrap();
+    assert_eq!(results[0]["img_url"], img_urls[0].as_str());
+    assert!(results[0]["braille"].is_string());
+    assert_eq!(results[1]["img_url"], img_urls[1].as_str());
+    assert!(results[1]["error"].is_string());
+    assert!(results[1].get("braille").is_none());
+}
+
+#[test]
+fn too_many_links_fail() {
+    let api = start_api();
+    let img_urls: Vec<String> = (0..17).map(|i| format!("http://127.0.0.1:1/{}.png", i)).collect();
+    let url = api.url("/braille/batch");
+    let resp = reqwest::blocking::Client::new()
+        .post(url)
+        .body(serde_json::json!({ "img_urls": img_urls }).to_string())
+        .send()
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[test]
+fn near_duplicates_point_at_the_first() {
+    let api = start_api();
+    let png = |img: image::DynamicImage| {
+        let mut bytes = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png).unwrap();
+        return bytes;
+    };
+    let circle = image::load_from_memory(&common::circle_png()).unwrap();
+    let grad = image::open(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../ascii_artinator_core/tests/golden/grad16.png")).unwrap();
+    // the circle, a different image, and the circle with its edges cropped off
+    let bodies = [png(circle.clone()), png(grad), png(circle.crop_imm(6, 6, 188, 188))];
+    let base = common::serve(move |request| {
+        let i: usize = request.split(['/', '.']).nth(1).unwrap().parse().unwrap();
+        return Served::png(bodies[i].clone());
+    });
+    let img_urls: Vec<String> = (0..3).map(|i| format!("{}/{}.png", base, i)).collect();
+    let resp = reqwest::blocking::Client::new()
+        .post(api.url("/braille/batch?cols=8&rows=2"))
+        .body(serde_json::json!({ "img_urls": img_urls }).to_string())
+        .send()
+        .unwrap();
+    let results: Vec<serde_json::Value> = serde_json::from_str(&resp.text().unwrap()).unwrap();
+    assert!(results.iter().all(|result| result["image_hash"].as_str().is_some_and(|hash| hash.len() == 16)), "{:?}", results);
+    assert!(results[0].get("duplicate_of").is_none());
+    assert!(results[1].get("duplicate_of").is_none());
+    assert_eq!(results[2]["duplicate_of"], 0);
+}
@@ -0,0 +1,69 @@
+//! The byte cap has to hold for chunked bodies too, which never say how big
+//! they are up front.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::io::{Read, Write};
+
+use common::Api;
+
+// the server with a cap of 1000 bytes
+fn start_api() -> Api {
+    return Api::start(&[("AA_MAX_BYTES", "1000")]);
+}
+
+// answers every request with a chunked body of `chunks` chunks of 100 bytes,
+// no Content-Length; returns the link and how many chunks got written
+fn serve_chunked(chunks: usize) -> (String, std::sync::mpsc::Receiver<usize>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sent_tx, sent_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 4096];
+        let _ = stream.read(&mut buf).unwrap();
+        let head = "HTTP/1.1 200 OK\r\ncontent-type: image/png\r\ntransfer-encoding: chunked\r\n\r\n";
+        stream.write_all(head.as_bytes()).unwrap();
+        let mut sent = 0;
+        for _ in 0..chunks {
+            let chunk = format!("64\r\n{}\r\n", "x".repeat(100));
+            if stream.write_all(chunk.as_bytes()).and_then(|_| stream.flush()).is_err() {
+                break;
+            }
+            sent += 1;
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let _ = stream.write_all(b"0\r\n\r\n");
+        let _ = sent_tx.send(sent);
+    });
+    return (format!("http://{}/img.png", addr), sent_rx);
+}
+
+fn fetch_through(api: &Api, img_url: &str) -> (u16, String) {
+    let resp = api.get(&format!("/braille?img_url={}", img_url), &[]);
+    return (resp.status().as_u16(), resp.text().unwrap());
+}
+
+#[test]
+fn chunked_over_the_cap_is_too_large() {
+    let api = start_api();
+    let (img_url, sent) = serve_chunked(2000);
+    let (status, body) = fetch_through(&api, &img_url);
+    assert_eq!(status, 413);
+    assert_eq!(body, ascii_artinator_core::ConvertError::TooLarge.to_string());
+    // it gave up mid-stream rather than reading all of it first
+    let sent = sent.recv_timeout(std::time::Duration::from_secs(30)).unwrap();
+    assert!(sent < 2000, "the whole body was read first");
+}
+
+#[test]
+fn chunked_under_the_cap_goes_through() {
+    let api = start_api();
+    let (img_url, _) = serve_chunked(5);
+    // 500 bytes of x isn't an image, but it got past the cap to find out
+    let (status, body) = fetch_through(&api, &img_url);
+    assert_ne!(status, 413);
+    assert_eq!(body, ascii_artinator_core::ConvertError::Decode.to_string());
+}
@@ -0,0 +1,125 @@
+//! What the API's tests share: the real server, run in this process on a
+//! port of its own, and a stand-in image host for it to fetch from.
+
+// every test file gets all of this, whether it uses all of it or not
+#![allow(dead_code, clippy::needless_return)]
+
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// The server, the same one the binary runs, serving from a thread of its
+/// own; stopped when this is dropped.
+pub struct Api {
+    handle: actix_web::dev::ServerHandle,
+    pub port: u16,
+}
+
+impl Drop for Api {
+    fn drop(&mut self) {
+        futures::executor::block_on(self.handle.stop(false));
+    }
+}
+
+// the `AA_*` settings this process started the api with
+static SETTINGS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+impl Api {
+    /// Starts the server with these `AA_*` settings on top of the defaults.
+    /// They're read once per process, and each test file is a process of its
+    /// own, so every test in a file has to ask for the same ones.
+    pub fn start(env: &[(&str, &str)]) -> Api {
+        let env: Vec<(String, String)> = env.iter().map(|(var, val)| (var.to_string(), val.to_string())).collect();
+        let settings = SETTINGS.get_or_init(|| {
+            for (var, val) in &env {
+                std::env::set_var(var, val);
+            }
+            return env.clone();
+        });
+        assert_eq!(*settings, env, "a test file can only start the api with one set of settings");
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (send_handle, handle) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            actix_web::rt::System::new().block_on(async move {
+                let server = ascii_artinator_api::run(listener).unwrap();
+                send_handle.send(server.handle()).unwrap();
+                return server.await;
+            })
+        });
+        // it's listening from the bind on, so it's up once there's a handle
+        return Api { handle: handle.recv().unwrap(), port };
+    }
+
+    /// A link to `path_and_query` on the server.
+    pub fn url(&self, path_and_query: &str) -> String {
+        return format!("http://127.0.0.1:{}{}", self.port, path_and_query);
+    }
+
+    /// GETs `path_and_query`, with these headers.
+    pub fn get(&self, path_and_query: &str, headers: &[(&str, &str)]) -> reqwest::blocking::Response {
+        let mut req = reqwest::blocking::Client::new().get(self.url(path_and_query)).timeout(Duration::from_secs(120));
+        for (name, val) in headers {
+            req = req.header(*name, *val);
+        }
+        return req.send().unwrap();
+    }
+}
+
+/// The bundled image most tests convert.
+pub fn circle_png() -> Vec<u8> {
+    return std::fs::read(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../ascii_artinator_core/tests/golden/circle.png")
+    ).unwrap();
+}
+
+/// A host serving the circle as a JPEG, only the first half of it.
+pub fn half_jpeg_host() -> String {
+    let mut jpeg = Vec::new();
+    ascii_artinator_core::image::load_from_memory(&circle_png()).unwrap()
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), ascii_artinator_core::image::ImageOutputFormat::Jpeg(90))
+        .unwrap();
+    jpeg.truncate(jpeg.len() / 2);
+    return serve(move |_| Served { status: "200 OK", content_type: "image/jpeg", body: jpeg.clone() });
+}
+
+/// What the stand-in host answers with.
+#[derive(Clone)]
+pub struct Served {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Served {
+    pub fn png(body: Vec<u8>) -> Served {
+        return Served { status: "200 OK", content_type: "image/png", body };
+    }
+}
+
+/// A host answering every request, each on a thread of its own, with
+/// whatever `answer` says for the request line (`GET /x.png HTTP/1.1`);
+/// gives back its base link, with no slash at the end.
+pub fn serve(answer: impl Fn(&str) -> Served + Send + Sync + 'static) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let answer = std::sync::Arc::new(answer);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let answer = answer.clone();
+            std::thread::spawn(move || {
+                let mut buf = [0; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let served = answer(request.lines().next().unwrap_or(""));
+                let head = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    served.status, served.content_type, served.body.len()
+                );
+                let _ = stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(&served.body));
+            });
+        }
+    });
+    return format!("http://{}", addr);
+}
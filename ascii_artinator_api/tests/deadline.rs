@@ -0,0 +1,96 @@
+//! An `X-Request-Deadline-Ms` the conversion can't make has to come back as
+//! a 503, and soon, not once it's done anyway; on every endpoint that
+//! fetches, not only `/braille`.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use common::{Api, Served};
+
+// a big PNG of noise, which takes a while to decode and resize
+fn big_png() -> Vec<u8> {
+    let mut seed: u32 = 1;
+    let img = image::GrayImage::from_fn(2500, 2500, |_, _| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        return image::Luma([(seed >> 24) as u8]);
+    });
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageLuma8(img).write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png).unwrap();
+    return bytes;
+}
+
+// the status and how long it took, asking for html so chat's length limit
+// doesn't get in the way
+fn convert(api: &Api, img_url: &str, deadline: Option<&str>) -> (u16, Duration) {
+    let path = format!("/braille?img_url={}&format=html", img_url);
+    let headers: Vec<(&str, &str)> = deadline.map(|deadline| ("x-request-deadline-ms", deadline)).into_iter().collect();
+    let started = Instant::now();
+    let resp = api.get(&path, &headers);
+    return (resp.status().as_u16(), started.elapsed());
+}
+
+#[test]
+fn a_tiny_deadline_gives_up_promptly() {
+    let api = Api::start(&[]);
+    let big = big_png();
+    let img_url = format!("{}/big.png", common::serve(move |_| Served::png(big.clone())));
+    let (status, took) = convert(&api, &img_url, Some("1"));
+    assert_eq!(status, 503);
+    assert!(took < Duration::from_secs(2), "took {:?}", took);
+    // the same conversion does go through with time to do it
+    let (status, _) = convert(&api, &img_url, Some("600000"));
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn a_deadline_past_the_fetch_still_cuts_the_conversion_short() {
+    let api = Api::start(&[]);
+    // one color all over: a quick fetch, but a lot of pixels to get through
+    let mut solid = Vec::new();
+    image::DynamicImage::ImageLuma8(image::GrayImage::from_pixel(4000, 4000, image::Luma([90])))
+        .write_to(&mut Cursor::new(&mut solid), image::ImageFormat::Png)
+        .unwrap();
+    let fetched = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let served = fetched.clone();
+    let img_url = format!("{}/solid.png", common::serve(move |_| {
+        served.store(true, std::sync::atomic::Ordering::SeqCst);
+        return Served::png(solid.clone());
+    }));
+    let (status, took) = convert(&api, &img_url, Some("300"));
+    assert!(fetched.load(std::sync::atomic::Ordering::SeqCst));
+    assert_eq!(status, 503);
+    // well short of what going all the way takes
+    let (status, all_the_way) = convert(&api, &img_url, None);
+    assert_eq!(status, 200);
+    assert!(took * 2 < all_the_way, "took {:?} of {:?}", took, all_the_way);
+}
+
+#[test]
+fn a_bad_deadline_is_a_bad_request() {
+    let api = Api::start(&[]);
+    // turned down before anything gets fetched
+    let (status, _) = convert(&api, "http://127.0.0.1:1/big.png", Some("soon"));
+    assert_eq!(status, 400);
+}
+
+#[test]
+fn the_other_endpoints_keep_it_too() {
+    let api = Api::start(&[]);
+    // an image that takes far longer to come than anyone's waiting
+    let host = common::serve(|_| {
+        std::thread::sleep(Duration::from_secs(5));
+        return Served::png(common::circle_png());
+    });
+    for path in ["/braille/estimate", "/braille/stats", "/palette"] {
+        let started = Instant::now();
+        let resp = api.get(&format!("{}?img_url={}/slow.png", path, host), &[("x-request-deadline-ms", "200")]);
+        assert_eq!(resp.status().as_u16(), 503, "{}", path);
+        assert!(started.elapsed() < Duration::from_secs(3), "{} took {:?}", path, started.elapsed());
+        let bad = api.get(&format!("{}?img_url={}/slow.png", path, host), &[("x-request-deadline-ms", "soon")]);
+        assert_eq!(bad.status().as_u16(), 400, "{}", path);
+    }
+}
@@ -0,0 +1,137 @@
+//! The endpoints over real HTTP, from the query string through the fetch and
+//! the conversion to the response, against a stand-in image host.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::{Api, Served};
+
+// the circle at /circle.png, text that isn't an image at /notes.txt and a
+// 404 anywhere else
+fn host() -> String {
+    return common::serve(|request| {
+        if request.starts_with("GET /circle.png") {
+            return Served::png(common::circle_png());
+        }
+        if request.starts_with("GET /notes.txt") {
+            return Served { status: "200 OK", content_type: "text/plain", body: b"just some notes".to_vec() };
+        }
+        return Served { status: "404 Not Found", content_type: "text/plain", body: b"nope".to_vec() };
+    });
+}
+
+fn header(resp: &reqwest::blocking::Response, name: &str) -> String {
+    return resp.headers().get(name).map(|h| h.to_str().unwrap().to_owned()).unwrap_or_default();
+}
+
+#[test]
+fn braille_converts_a_link() {
+    let (api, host) = (Api::start(&[]), host());
+    let resp = api.get(&format!("/braille?img_url={}/circle.png&cols=20&rows=5", host), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(header(&resp, "content-type"), "text/plain; charset=utf-8");
+    assert!(!header(&resp, "etag").is_empty());
+    let body = resp.text().unwrap();
+    // chat text: a space before each of the rows
+    assert_eq!(body.split(' ').skip(1).count(), 5);
+    assert!(body.split(' ').skip(1).all(|row| row.chars().count() == 20));
+}
+
+#[test]
+fn format_comes_from_the_query_or_accept() {
+    let (api, host) = (Api::start(&[]), host());
+    let img_url = format!("{}/circle.png", host);
+    let html = api.get(&format!("/braille?img_url={}&cols=20&rows=5", img_url), &[("accept", "text/html")]);
+    assert_eq!(header(&html, "content-type"), "text/html; charset=utf-8");
+    assert!(html.text().unwrap().contains("<pre"));
+    let jsonl = api.get(&format!("/braille?img_url={}&cols=20&rows=5&format=jsonl", img_url), &[]);
+    assert_eq!(header(&jsonl, "content-type"), "application/x-ndjson; charset=utf-8");
+    for line in jsonl.text().unwrap().lines() {
+        let parsed: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(parsed["cells"].is_string());
+    }
+}
+
+#[test]
+fn braille_json_has_the_text_and_the_headers() {
+    let (api, host) = (Api::start(&[]), host());
+    let query = format!("?img_url={}/circle.png&cols=20&rows=5", host);
+    let plain = api.get(&format!("/braille{}", query), &[]);
+    let (hash, threshold) = (header(&plain, "x-image-hash"), header(&plain, "x-suggested-threshold"));
+    let text = plain.text().unwrap();
+    let resp = api.get(&format!("/braille.json{}", query), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(header(&resp, "content-type"), "application/json");
+    let json: serde_json::Value = serde_json::from_str(&resp.text().unwrap()).unwrap();
+    assert_eq!(json["img_url"], format!("{}/circle.png", host));
+    assert_eq!(json["braille"], text);
+    assert_eq!(json["image_hash"], hash);
+    assert_eq!(json["suggested_threshold"].to_string(), threshold);
+    assert_eq!(json["density_threshold"], serde_json::Value::Null);
+    assert_eq!(json["partial"], false);
+}
+
+#[test]
+fn same_body_is_not_modified() {
+    let (api, host) = (Api::start(&[]), host());
+    let path = format!("/braille?img_url={}/circle.png&cols=20&rows=5", host);
+    let etag = header(&api.get(&path, &[]), "etag");
+    let again = api.get(&path, &[("if-none-match", &etag)]);
+    assert_eq!(again.status().as_u16(), 304);
+}
+
+#[test]
+fn stats_and_estimate_answer_in_json() {
+    let (api, host) = (Api::start(&[]), host());
+    let img_url = format!("{}/circle.png", host);
+    let estimate: serde_json::Value = serde_json::from_str(
+        &api.get(&format!("/braille/estimate?img_url={}", img_url), &[]).text().unwrap()
+    ).unwrap();
+    assert_eq!(estimate["width"], 200);
+    assert_eq!(estimate["height"], 200);
+    let stats: serde_json::Value = serde_json::from_str(
+        &api.get(&format!("/braille/stats?img_url={}", img_url), &[]).text().unwrap()
+    ).unwrap();
+    // the estimate is the most it could be, and that's what the circle is
+    assert_eq!(stats["cols"], estimate["cols"]);
+    assert_eq!(stats["rows"], estimate["rows"]);
+    assert!(stats["fill"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn bad_requests_are_400() {
+    let (api, host) = (Api::start(&[]), host());
+    for path in [
+        "/braille".to_owned(),
+        format!("/braille?img_url={}/circle.png&mode=nope", host),
+        format!("/braille?img_url={}/circle.png&nope=1", host),
+        "/braille?img_url=file:///etc/passwd".to_owned(),
+        "/braille.json".to_owned(),
+        format!("/braille.json?img_url={}/circle.png&format=html", host),
+        format!("/braille.json?img_url={}/circle.png&favicon=maybe", host),
+    ] {
+        let resp = api.get(&path, &[]);
+        assert_eq!(resp.status().as_u16(), 400, "{}", path);
+        assert_eq!(header(&resp, "x-error-category"), "options", "{}", path);
+        assert_eq!(header(&resp, "cache-control"), "no-store", "{}", path);
+    }
+}
+
+#[test]
+fn failed_images_say_why() {
+    let (api, host) = (Api::start(&[]), host());
+    let resp = api.get(&format!("/braille?img_url={}/notes.txt", host), &[]);
+    assert_eq!(header(&resp, "x-error-category"), "image");
+    assert_eq!(resp.text().unwrap(), ascii_artinator_core::ConvertError::NotAnImage.to_string());
+    // nothing listening there
+    let resp = api.get("/braille?img_url=http://127.0.0.1:1/circle.png", &[]);
+    assert_eq!(header(&resp, "x-error-category"), "fetch");
+}
+
+#[test]
+fn unknown_paths_are_404() {
+    let api = Api::start(&[]);
+    assert_eq!(api.get("/nope", &[]).status().as_u16(), 404);
+    assert_eq!(api.get("/zoazo", &[]).status().as_u16(), 200);
+}
@@ -0,0 +1,79 @@
+//! `favicon=true` finds a site's icon from its page, and the deadline holds
+//! while it looks; the icon it finds is converted without being fetched
+//! again. The caps on the fetch are in `favicon_caps.rs`.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::{Api, Served};
+
+fn page(body: String) -> Served {
+    return Served { status: "200 OK", content_type: "text/html", body: body.into_bytes() };
+}
+
+// a site with its icon at /icon.png, and one whose page takes forever
+fn host() -> String {
+    return common::serve(|request| {
+        let path = request.split(' ').nth(1).unwrap_or("");
+        return match path {
+            "/icon.png" => Served::png(common::circle_png()),
+            "/site/" => page("<html><head><link rel=\"shortcut icon\" href=\"/icon.png\"></head></html>".to_owned()),
+            "/slow/" => {
+                std::thread::sleep(Duration::from_secs(10));
+                page("<html></html>".to_owned())
+            },
+            _ => Served { status: "404 Not Found", content_type: "text/plain", body: b"nope".to_vec() },
+        };
+    });
+}
+
+fn favicon(api: &Api, site: &str, headers: &[(&str, &str)]) -> reqwest::blocking::Response {
+    return api.get(&format!("/braille?img_url={}&favicon=true&cols=20&rows=5", site), headers);
+}
+
+#[test]
+fn finds_the_linked_icon() {
+    let (api, host) = (Api::start(&[]), host());
+    let direct = api.get(&format!("/braille?img_url={}/icon.png&cols=20&rows=5", host), &[]).text().unwrap();
+    let resp = favicon(&api, &format!("{}/site/", host), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.text().unwrap(), direct);
+}
+
+#[test]
+fn slow_page_runs_out_the_deadline() {
+    let (api, host) = (Api::start(&[]), host());
+    let started = Instant::now();
+    let resp = favicon(&api, &format!("{}/slow/", host), &[("x-request-deadline-ms", "500")]);
+    assert_eq!(resp.status().as_u16(), 503);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn the_icon_is_fetched_once() {
+    let api = Api::start(&[]);
+    let icon_fetches = Arc::new(AtomicUsize::new(0));
+    let counted = icon_fetches.clone();
+    let host = common::serve(move |request| {
+        return match request.split(' ').nth(1).unwrap_or("") {
+            "/icon.png" => {
+                counted.fetch_add(1, Ordering::SeqCst);
+                Served::png(common::circle_png())
+            },
+            _ => page("<html><link rel=icon href=/icon.png></html>".to_owned()),
+        };
+    });
+    let resp = favicon(&api, &format!("{}/", host), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    // found, and then converted from what finding it fetched
+    assert_eq!(icon_fetches.load(Ordering::SeqCst), 1);
+    // and with a deadline, which skips sharing the conversion
+    let resp = api.get(&format!("/braille?img_url={}/&favicon=true&cols=12&rows=3", host), &[("x-request-deadline-ms", "20000")]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(icon_fetches.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,56 @@
+//! `favicon=true` goes through the same fetch as any image, so a site page
+//! that's too big or too slow can't get around the caps.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ascii_artinator_core::ConvertError;
+use common::{Api, Served};
+
+fn start_api() -> Api {
+    return Api::start(&[("AA_MAX_BYTES", "10000"), ("AA_READ_TIMEOUT_MS", "300")]);
+}
+
+fn page(body: String) -> Served {
+    return Served { status: "200 OK", content_type: "text/html", body: body.into_bytes() };
+}
+
+// a site whose page is huge, and one whose page takes forever
+fn host() -> String {
+    return common::serve(|request| {
+        let path = request.split(' ').nth(1).unwrap_or("");
+        return match path {
+            "/icon.png" => Served::png(common::circle_png()),
+            "/big/" => page(format!("<html><head>{}<link rel=icon href=/icon.png></head></html>", " ".repeat(100_000))),
+            "/slow/" => {
+                std::thread::sleep(Duration::from_secs(10));
+                page("<html></html>".to_owned())
+            },
+            _ => Served { status: "404 Not Found", content_type: "text/plain", body: b"nope".to_vec() },
+        };
+    });
+}
+
+fn favicon(api: &Api, site: &str) -> reqwest::blocking::Response {
+    return api.get(&format!("/braille?img_url={}&favicon=true&cols=20&rows=5", site), &[]);
+}
+
+#[test]
+fn huge_page_is_too_large() {
+    let (api, host) = (start_api(), host());
+    let resp = favicon(&api, &format!("{}/big/", host));
+    assert_eq!(resp.status().as_u16(), 413);
+    assert_eq!(resp.text().unwrap(), ConvertError::TooLarge.to_string());
+}
+
+#[test]
+fn slow_page_times_out() {
+    let (api, host) = (start_api(), host());
+    let started = Instant::now();
+    let resp = favicon(&api, &format!("{}/slow/", host));
+    assert_eq!(resp.text().unwrap(), ConvertError::ReadTimeout.to_string());
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
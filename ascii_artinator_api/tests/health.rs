@@ -0,0 +1,23 @@
+//! `/health` says the server is up, without fetching or converting anything.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+#[actix_web::test]
+async fn health_is_ok() {
+    use actix_web::test::{call_service, init_service, read_body, TestRequest};
+    // just the routes, no server and no port
+    let app = init_service(actix_web::App::new().configure(ascii_artinator_api::routes)).await;
+    let resp = call_service(&app, TestRequest::get().uri("/health").to_request()).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers().get("cache-control").unwrap(), "no-store");
+    assert_eq!(read_body(resp).await, "ok");
+}
+
+#[test]
+fn health_over_http() {
+    let resp = common::Api::start(&[]).get("/health", &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.text().unwrap(), "ok");
+}
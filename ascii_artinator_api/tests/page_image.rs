@@ -0,0 +1,81 @@
+//! Links to a page instead of an image, which get the page's image instead.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::{Api, Served};
+
+fn page(body: &str) -> Served {
+    return Served { status: "200 OK", content_type: "text/html; charset=utf-8", body: body.as_bytes().to_vec() };
+}
+
+// the circle at /circle.png, and pages pointing at it, or at nothing, or at
+// each other
+fn host() -> String {
+    return common::serve(|request| {
+        let path = request.split(' ').nth(1).unwrap_or("");
+        return match path {
+            "/circle.png" => Served::png(common::circle_png()),
+            "/og.html" => page(concat!(
+                "<!DOCTYPE html><html><head><title>a circle</title>",
+                "<meta property=\"og:image\" content=\"/circle.png\">",
+                "</head><body><img src=\"/nope.png\"></body></html>"
+            )),
+            "/img.html" => page("<html><body><p>look:</p><img alt='a circle' src='circle.png'></body></html>"),
+            "/empty.html" => page("<html><body>no pictures here</body></html>"),
+            // a dead link's error page, with the site's logo on it
+            "/gone.html" => Served { status: "404 Not Found", ..page("<html><body><img src=/circle.png></body></html>") },
+            "/deeper.html" => page("<html><head><meta property=og:image content=/og.html></head></html>"),
+            // a host that doesn't say what it sent
+            "/untyped" => Served {
+                status: "200 OK",
+                content_type: "application/octet-stream",
+                body: b"<!doctype html><img src=/circle.png>".to_vec(),
+            },
+            _ => Served { status: "404 Not Found", content_type: "text/plain", body: b"nope".to_vec() },
+        };
+    });
+}
+
+fn braille(api: &Api, url: &str) -> reqwest::blocking::Response {
+    return api.get(&format!("/braille?img_url={}&cols=20&rows=5", url), &[]);
+}
+
+#[test]
+fn og_image_is_used_for_a_page() {
+    let (api, host) = (Api::start(&[]), host());
+    let direct = braille(&api, &format!("{}/circle.png", host)).text().unwrap();
+    let resp = braille(&api, &format!("{}/og.html", host));
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(resp.headers().get("x-error-category").is_none());
+    // og:image beats the <img> further down, which is a 404
+    assert_eq!(resp.text().unwrap(), direct);
+}
+
+#[test]
+fn first_img_is_used_without_og_image() {
+    let (api, host) = (Api::start(&[]), host());
+    let direct = braille(&api, &format!("{}/circle.png", host)).text().unwrap();
+    assert_eq!(braille(&api, &format!("{}/img.html", host)).text().unwrap(), direct);
+    assert_eq!(braille(&api, &format!("{}/untyped", host)).text().unwrap(), direct);
+}
+
+#[test]
+fn pages_without_an_image_say_so() {
+    let (api, host) = (Api::start(&[]), host());
+    let no_image = ascii_artinator_core::ConvertError::NoPageImage.to_string();
+    let resp = braille(&api, &format!("{}/empty.html", host));
+    assert_eq!(resp.headers()["x-error-category"], "fetch");
+    assert_eq!(resp.text().unwrap(), no_image);
+    // only one page deep, never a page off a page
+    assert_eq!(braille(&api, &format!("{}/deeper.html", host)).text().unwrap(), no_image);
+}
+
+#[test]
+fn error_pages_are_not_looked_into() {
+    let (api, host) = (Api::start(&[]), host());
+    let resp = braille(&api, &format!("{}/gone.html", host));
+    assert_eq!(resp.headers()["x-error-category"], "fetch");
+    assert_eq!(resp.text().unwrap(), ascii_artinator_core::ConvertError::Fetch.to_string());
+}
@@ -0,0 +1,16 @@
+//! A JPEG cut in half converts, as far as it goes, with `X-Partial-Decode`.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::Api;
+
+#[test]
+fn half_a_jpeg_is_marked_partial() {
+    let host = common::half_jpeg_host();
+    let resp = Api::start(&[]).get(&format!("/braille?img_url={}/half.jpg", host), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["x-partial-decode"], "true");
+    assert!(resp.headers().get("x-error-category").is_none());
+}
@@ -0,0 +1,18 @@
+//! With `AA_PARTIAL_DECODE` off, a JPEG cut in half is just a bad image.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::Api;
+
+#[test]
+fn half_a_jpeg_is_an_error_without_it() {
+    let host = common::half_jpeg_host();
+    let api = Api::start(&[("AA_PARTIAL_DECODE", "false")]);
+    let resp = api.get(&format!("/braille?img_url={}/half.jpg", host), &[]);
+    assert_eq!(resp.headers()["x-error-category"], "image");
+    assert!(resp.headers().get("x-partial-decode").is_none());
+    let body = resp.text().unwrap();
+    assert_eq!(body, "failed to read image INSANECAT");
+}
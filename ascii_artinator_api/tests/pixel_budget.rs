@@ -0,0 +1,34 @@
+//! `AA_MAX_PIXELS` turns away images by the size their header claims, with
+//! a 413.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::{Api, Served};
+
+// the circle at /circle.png, 200x200, and a 10x10 square anywhere else
+fn host() -> String {
+    return common::serve(|request| {
+        if request.starts_with("GET /circle.png") {
+            return Served::png(common::circle_png());
+        }
+        let mut square = Vec::new();
+        image::DynamicImage::ImageLuma8(image::GrayImage::new(10, 10))
+            .write_to(&mut std::io::Cursor::new(&mut square), image::ImageFormat::Png)
+            .unwrap();
+        return Served::png(square);
+    });
+}
+
+#[test]
+fn over_budget_is_413() {
+    let host = host();
+    let api = Api::start(&[("AA_MAX_PIXELS", "100")]);
+    let resp = api.get(&format!("/braille?img_url={}/circle.png", host), &[]);
+    assert_eq!(resp.status().as_u16(), 413);
+    assert_eq!(resp.headers()["x-error-category"], "size");
+    // one just within the budget is fine
+    let resp = api.get(&format!("/braille?img_url={}/square.png", host), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+}
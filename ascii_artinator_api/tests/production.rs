@@ -0,0 +1,27 @@
+//! `AA_PRODUCTION` swaps error messages for generic ones, except for bad
+//! options, whose messages are about the request and safe to give back.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::Api;
+
+#[test]
+fn bad_options_keep_their_message() {
+    let api = Api::start(&[("AA_PRODUCTION", "true")]);
+    let resp = api.get("/braille?img_url=http://127.0.0.1:1/x.png&cols=abc", &[]);
+    assert_eq!(resp.status().as_u16(), 400);
+    let body = resp.text().unwrap();
+    assert!(body.contains("cols"), "{}", body);
+    assert_ne!(body, "bad options");
+}
+
+#[test]
+fn other_errors_are_generic() {
+    let api = Api::start(&[("AA_PRODUCTION", "true")]);
+    // nothing listening there
+    let resp = api.get("/braille?img_url=http://127.0.0.1:1/x.png", &[]);
+    assert_eq!(resp.headers()["x-error-category"], "fetch");
+    assert_eq!(resp.text().unwrap(), "couldn't fetch the image");
+}
@@ -0,0 +1,19 @@
+//! `format=render.png` works out of the box, with the built-in font, when
+//! `AA_RENDER_FONT` doesn't say otherwise.
+
+#![cfg(feature = "render")]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::{Api, Served};
+
+#[test]
+fn renders_without_a_font_set() {
+    let host = common::serve(|_| Served::png(common::circle_png()));
+    let api = Api::start(&[]);
+    let resp = api.get(&format!("/braille?img_url={}/circle.png&cols=20&rows=5&format=render.png", host), &[]);
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "image/png");
+    assert!(ascii_artinator_core::image::load_from_memory(&resp.bytes().unwrap()).is_ok());
+}
@@ -0,0 +1,103 @@
+//! Converting from the command line, with the same options as the server's
+//! query string:
+//!
+//! `ascii_artinator_cli <link or path> [option=value ...]`
+//!
+//! e.g. `ascii_artinator_cli cat.png mode=edges --terminal kitty`
+
+#![allow(clippy::needless_return)]
+
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+
+use ascii_artinator_core::image::ImageFormat;
+use ascii_artinator_core::{Color, ConvertError, ConvertOptions, Frames};
+
+// the raw body of an image, and what the link or path says it might be
+struct Input {
+    bytes: Vec<u8>,
+    mime_format: Option<ImageFormat>,
+    ext_format: Option<ImageFormat>,
+}
+
+fn ext_format(name: &str) -> Option<ImageFormat> {
+    return name.rsplit_once('.').and_then(|(_, ext)| ImageFormat::from_extension(ext));
+}
+
+// links get fetched; anything else, plain paths and file:// links, is read
+// straight off the disk
+fn read_input(arg: &str) -> Result<Input, ConvertError> {
+    if arg.starts_with("http://") || arg.starts_with("https://") {
+        let resp = reqwest::blocking::get(arg).map_err(|err| {
+            eprintln!("fetch failed for {}: {}", arg, err);
+            return ConvertError::Fetch;
+        })?;
+        let ext_format = resp.url().path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(ext_format);
+        let mime_format = resp.headers().get("content-type")
+            .and_then(|h| h.to_str().ok())
+            .and_then(ImageFormat::from_mime_type);
+        let bytes = resp.bytes().map_err(|_| ConvertError::Fetch)?.to_vec();
+        return Ok(Input { bytes, mime_format, ext_format });
+    }
+    let path = arg.strip_prefix("file://").unwrap_or(arg);
+    let bytes = std::fs::read(path).map_err(|err| {
+        eprintln!("can't read {}: {}", path, err);
+        return ConvertError::Fetch;
+    })?;
+    return Ok(Input { bytes, mime_format: None, ext_format: ext_format(path) });
+}
+
+// options go like the query string, option=value, and a bare option means
+// option=true; flag style works too, --option=value or --option value
+fn parse_args(args: &[String]) -> Result<ConvertOptions, ConvertError> {
+    let mut query = HashMap::new();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        let is_flag = arg.starts_with("--");
+        let arg = arg.trim_start_matches("--");
+        let (key, val) = match arg.split_once('=') {
+            Some((key, val)) => (key, val.to_owned()),
+            None if is_flag => match args.next_if(|next| !next.starts_with("--") && !next.contains('=')) {
+                Some(val) => (arg, val.to_owned()),
+                None => (arg, "true".to_owned()),
+            },
+            None => (arg, "true".to_owned()),
+        };
+        query.insert(key.to_owned(), val);
+    }
+    return ascii_artinator_core::parse_options(&query, &ConvertOptions::default());
+}
+
+fn run(input: &str, args: &[String]) -> Result<(), ConvertError> {
+    let mut opts = parse_args(args)?;
+    // here it can be known, so colors are only for a terminal unless asked
+    opts.color.get_or_insert(Color::Auto);
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|val| !val.is_empty());
+    opts.resolve_color(no_color, std::io::stdout().is_terminal());
+    opts.source = Some(input.to_owned());
+    let input = read_input(input)?;
+    let max_pixels = ascii_artinator_core::DEFAULT_MAX_PIXELS;
+    let imgs = if opts.frames == Frames::All {
+        ascii_artinator_core::frames::decode_all_with_hints(&input.bytes, input.mime_format, input.ext_format, max_pixels, &opts)?
+    } else {
+        vec![ascii_artinator_core::decode_with_hints(&input.bytes, input.mime_format, input.ext_format, max_pixels, &opts)?]
+    };
+    // written out as it's formatted, never built up whole first
+    let mut out = ascii_artinator_core::output::IoSink::new(std::io::BufWriter::new(std::io::stdout().lock()));
+    ascii_artinator_core::write_frames(&mut out, imgs, &opts)?;
+    return writeln!(out.inner).and_then(|_| out.inner.flush()).map_err(|_| ConvertError::Write);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((input, rest)) = args.split_first() else {
+        eprintln!("usage: ascii_artinator_cli <link or path> [option=value ...]");
+        std::process::exit(2);
+    };
+    if let Err(err) = run(input, rest) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
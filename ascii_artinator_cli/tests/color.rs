@@ -0,0 +1,31 @@
+//! The CLI only colors when printing to a terminal, unless told to.
+
+#![allow(clippy::needless_return)]
+
+use std::process::Command;
+
+fn convert(extra: &[&str], no_color: bool) -> String {
+    let png = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../ascii_artinator_core/tests/golden/redblue.png");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ascii_artinator_cli"));
+    cmd.arg(png).arg("mode=rgbsplit").args(extra).env_remove("NO_COLOR");
+    if no_color {
+        cmd.env("NO_COLOR", "1");
+    }
+    let out = cmd.output().unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    return String::from_utf8(out.stdout).unwrap();
+}
+
+#[test]
+fn piped_output_has_no_color() {
+    assert!(!convert(&[], false).contains('\x1b'));
+}
+
+#[test]
+fn always_colors_and_resets() {
+    for no_color in [false, true] {
+        let out = convert(&["color=always"], no_color);
+        assert!(out.contains('\x1b'));
+        assert!(out.trim_end_matches('\n').ends_with("\x1b[0m"));
+    }
+}
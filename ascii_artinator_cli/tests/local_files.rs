@@ -0,0 +1,62 @@
+//! A local file has to come out the same whether it's given as a path, as a
+//! file:// link, or served over HTTP.
+
+#![allow(clippy::needless_return)]
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+fn bundled_png() -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../ascii_artinator_core/tests/golden/circle.png")
+        .canonicalize()
+        .unwrap();
+}
+
+// answers a single request with the file, just enough HTTP for reqwest
+fn serve_once(body: Vec<u8>) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 4096];
+        let _ = stream.read(&mut buf).unwrap();
+        let head = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: image/png\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(&body).unwrap();
+    });
+    return format!("http://{}/circle.png", addr);
+}
+
+fn convert(input: &str) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_ascii_artinator_cli"))
+        .args([input, "mode=edges"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    return String::from_utf8(out.stdout).unwrap();
+}
+
+#[test]
+fn path_file_link_and_http_match() {
+    let path = bundled_png();
+    let from_path = convert(path.to_str().unwrap());
+    let from_file_link = convert(&format!("file://{}", path.display()));
+    let from_http = convert(&serve_once(std::fs::read(&path).unwrap()));
+    assert!(!from_path.trim().is_empty());
+    assert_eq!(from_path, from_file_link);
+    assert_eq!(from_path, from_http);
+}
+
+#[test]
+fn missing_file_fails() {
+    let out = Command::new(env!("CARGO_BIN_EXE_ascii_artinator_cli"))
+        .arg("/nonexistent/nope.png")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+}
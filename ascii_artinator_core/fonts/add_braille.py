@@ -0,0 +1,250 @@
+#!/usr/bin/env python3
+"""Builds ArtinatorMono.ttf, the font render.png draws with when none is set:
+DejaVu Sans Mono, which has no braille, with the 256 braille patterns drawn
+in on its own cell and renamed, as its license asks of modified versions.
+
+    python3 add_braille.py /usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf ArtinatorMono.ttf
+
+Only the standard library; the dots are circles on a grid that tiles evenly
+across cells and lines, so neighbouring chars line up into one dot matrix.
+"""
+
+import math
+import struct
+import sys
+
+FAMILY = "Artinator Mono"
+POSTSCRIPT = "ArtinatorMono"
+OLD_NAMES = [("DejaVu Sans Mono", FAMILY), ("DejaVuSansMono", POSTSCRIPT)]
+# tables with something per glyph that the new glyphs would throw off, or a
+# signature the changes break; none of them is needed to draw anything
+DROPPED = {b"hdmx", b"LTSH", b"VDMX", b"DSIG"}
+
+
+def read_tables(data):
+    num_tables = struct.unpack(">H", data[4:6])[0]
+    tables = {}
+    for i in range(num_tables):
+        tag, _, offset, length = struct.unpack(">4sIII", data[12 + i * 16:28 + i * 16])
+        tables[tag] = data[offset:offset + length]
+    return tables
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def write_font(tables):
+    tags = sorted(tables)
+    num_tables = len(tags)
+    entry_selector = int(math.log2(num_tables))
+    search_range = 16 << entry_selector
+    header = struct.pack(">IHHHH", 0x00010000, num_tables, search_range, entry_selector,
+                         num_tables * 16 - search_range)
+    offset = 12 + num_tables * 16
+    directory, body = b"", b""
+    for tag in tags:
+        table = tables[tag]
+        directory += struct.pack(">4sIII", tag, checksum(table), offset + len(body), len(table))
+        body += table + b"\0" * (-len(table) % 4)
+    font = bytearray(header + directory + body)
+    # head's checkSumAdjustment, worked out over the whole font with it at 0
+    head_offset = offset + sum(len(tables[tag]) + (-len(tables[tag]) % 4) for tag in tags[:tags.index(b"head")])
+    struct.pack_into(">I", font, head_offset + 8, (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF)
+    return bytes(font)
+
+
+def read_cmap(cmap):
+    """Every code point to its glyph, from the format 4 and 12 subtables."""
+    mapping = {}
+    num_subtables = struct.unpack(">H", cmap[2:4])[0]
+    for i in range(num_subtables):
+        platform, encoding, offset = struct.unpack(">HHI", cmap[4 + i * 8:12 + i * 8])
+        if platform != 3:
+            continue
+        sub = cmap[offset:]
+        fmt = struct.unpack(">H", sub[:2])[0]
+        if fmt == 4:
+            seg_count = struct.unpack(">H", sub[6:8])[0] // 2
+            ends = struct.unpack(">%dH" % seg_count, sub[14:14 + seg_count * 2])
+            base = 16 + seg_count * 2
+            starts = struct.unpack(">%dH" % seg_count, sub[base:base + seg_count * 2])
+            deltas = struct.unpack(">%dh" % seg_count, sub[base + seg_count * 2:base + seg_count * 4])
+            range_base = base + seg_count * 4
+            ranges = struct.unpack(">%dH" % seg_count, sub[range_base:range_base + seg_count * 2])
+            for seg in range(seg_count):
+                for code in range(starts[seg], ends[seg] + 1):
+                    if code == 0xFFFF:
+                        continue
+                    if ranges[seg] == 0:
+                        glyph = (code + deltas[seg]) & 0xFFFF
+                    else:
+                        at = range_base + seg * 2 + ranges[seg] + (code - starts[seg]) * 2
+                        glyph = struct.unpack(">H", sub[at:at + 2])[0]
+                        if glyph:
+                            glyph = (glyph + deltas[seg]) & 0xFFFF
+                    if glyph:
+                        mapping.setdefault(code, glyph)
+        elif fmt == 12:
+            num_groups = struct.unpack(">I", sub[12:16])[0]
+            for group in range(num_groups):
+                start, end, glyph = struct.unpack(">III", sub[16 + group * 12:28 + group * 12])
+                for code in range(start, end + 1):
+                    mapping.setdefault(code, glyph + code - start)
+    return mapping
+
+
+def write_cmap(mapping):
+    # runs of codes whose glyphs go up along with them, one segment each
+    segments = []
+    for code in sorted(code for code in mapping if code < 0xFFFF):
+        last = segments[-1] if segments else None
+        if last and code == last[1] + 1 and mapping[code] - code == mapping[last[0]] - last[0]:
+            last[1] = code
+        else:
+            segments.append([code, code])
+    segments.append([0xFFFF, 0xFFFF])
+    seg_count = len(segments)
+    entry_selector = int(math.log2(seg_count))
+    search_range = 2 << entry_selector
+    deltas = [(mapping[start] - start) & 0xFFFF if start != 0xFFFF else 1 for start, _ in segments]
+    fmt4 = struct.pack(">HHH", seg_count * 2, search_range, entry_selector) + struct.pack(">H", seg_count * 2 - search_range)
+    fmt4 += struct.pack(">%dH" % seg_count, *[end for _, end in segments]) + b"\0\0"
+    fmt4 += struct.pack(">%dH" % seg_count, *[start for start, _ in segments])
+    fmt4 += struct.pack(">%dH" % seg_count, *deltas)
+    fmt4 += struct.pack(">%dH" % seg_count, *[0] * seg_count)
+    fmt4 = struct.pack(">HHH", 4, len(fmt4) + 6, 0) + fmt4
+    groups = []
+    for code in sorted(mapping):
+        last = groups[-1] if groups else None
+        if last and code == last[1] + 1 and mapping[code] == last[2] + code - last[0]:
+            last[1] = code
+        else:
+            groups.append([code, code, mapping[code]])
+    fmt12 = struct.pack(">HHIII", 12, 0, 16 + len(groups) * 12, 0, len(groups))
+    fmt12 += b"".join(struct.pack(">III", *group) for group in groups)
+    records = [(0, 3, 4 + 3 * 8), (3, 1, 4 + 3 * 8), (3, 10, 4 + 3 * 8 + len(fmt4))]
+    cmap = struct.pack(">HH", 0, len(records))
+    cmap += b"".join(struct.pack(">HHI", *record) for record in records)
+    return cmap + fmt4 + fmt12
+
+
+def rename(name):
+    fmt, count, string_offset = struct.unpack(">HHH", name[:6])
+    records, strings = [], b""
+    for i in range(count):
+        platform, encoding, language, name_id, length, offset = struct.unpack(">6H", name[6 + i * 12:18 + i * 12])
+        raw = name[string_offset + offset:string_offset + offset + length]
+        codec = "utf-16-be" if platform in (0, 3) else "latin-1"
+        text = raw.decode(codec)
+        for old, new in OLD_NAMES:
+            text = text.replace(old, new)
+        raw = text.encode(codec)
+        records.append(struct.pack(">6H", platform, encoding, language, name_id, len(raw), len(strings)))
+        strings += raw
+    return struct.pack(">HHH", 0, count, 6 + count * 12) + b"".join(records) + strings
+
+
+def circle(cx, cy, r):
+    """A dot as 8 on-curve points with a control point between each two,
+    going clockwise like outer contours have to."""
+    points = []
+    for k in range(8):
+        angle = -k * math.pi / 4
+        points.append((round(cx + r * math.cos(angle)), round(cy + r * math.sin(angle)), True))
+        angle -= math.pi / 8
+        far = r / math.cos(math.pi / 8)
+        points.append((round(cx + far * math.cos(angle)), round(cy + far * math.sin(angle)), False))
+    return points
+
+
+def braille_glyph(bits, advance, ascent, descent):
+    """The glyph for the pattern with these dots, numbered the usual way:
+    1 to 3 down the left, 4 to 6 down the right, 7 and 8 under them."""
+    # two columns across the cell, four rows across the line, evenly spaced
+    # so the gaps between cells are the same as within them
+    col_x = [advance / 4, advance * 3 / 4]
+    row_height = (ascent - descent) / 4
+    row_y = [ascent - row_height * (row + 0.5) for row in range(4)]
+    radius = min(advance / 2, row_height) * 0.32
+    dots = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (0, 3), (1, 3)]
+    contours = [circle(col_x[col], row_y[row], radius) for i, (col, row) in enumerate(dots) if bits & 1 << i]
+    points = [point for contour in contours for point in contour]
+    xs, ys = [x for x, _, _ in points], [y for _, y, _ in points]
+    glyph = struct.pack(">hhhhh", len(contours), min(xs), min(ys), max(xs), max(ys))
+    ends, end = [], -1
+    for contour in contours:
+        end += len(contour)
+        ends.append(end)
+    glyph += struct.pack(">%dH" % len(ends), *ends) + struct.pack(">H", 0)
+    glyph += bytes(1 if on else 0 for _, _, on in points)
+    last_x = last_y = 0
+    deltas_x, deltas_y = b"", b""
+    for x, y, _ in points:
+        deltas_x += struct.pack(">h", x - last_x)
+        deltas_y += struct.pack(">h", y - last_y)
+        last_x, last_y = x, y
+    return glyph + deltas_x + deltas_y, min(xs), len(points), len(contours)
+
+
+def main(source, target):
+    data = open(source, "rb").read()
+    tables = {tag: table for tag, table in read_tables(data).items() if tag not in DROPPED}
+    head, maxp, hhea = bytearray(tables[b"head"]), bytearray(tables[b"maxp"]), bytearray(tables[b"hhea"])
+    num_glyphs = struct.unpack(">H", maxp[4:6])[0]
+    long_loca = struct.unpack(">h", head[50:52])[0] == 1
+    loca = tables[b"loca"]
+    if long_loca:
+        offsets = list(struct.unpack(">%dI" % (num_glyphs + 1), loca[:(num_glyphs + 1) * 4]))
+    else:
+        offsets = [offset * 2 for offset in struct.unpack(">%dH" % (num_glyphs + 1), loca[:(num_glyphs + 1) * 2])]
+    glyf = tables[b"glyf"]
+    glyphs = [glyf[offsets[i]:offsets[i + 1]] for i in range(num_glyphs)]
+    num_metrics = struct.unpack(">H", hhea[34:36])[0]
+    hmtx = tables[b"hmtx"]
+    metrics = [struct.unpack(">Hh", hmtx[i * 4:i * 4 + 4]) for i in range(num_metrics)]
+    lsbs = struct.unpack(">%dh" % (num_glyphs - num_metrics), hmtx[num_metrics * 4:num_metrics * 4 + (num_glyphs - num_metrics) * 2])
+    metrics += [(metrics[-1][0], lsb) for lsb in lsbs]
+
+    mapping = read_cmap(tables[b"cmap"])
+    advance = metrics[mapping[ord("M")]][0]
+    ascent, descent = struct.unpack(">hh", hhea[4:8])
+    max_points, max_contours = struct.unpack(">HH", maxp[6:10])
+    for bits in range(256):
+        code = 0x2800 + bits
+        if code in mapping:
+            continue
+        if bits == 0:
+            glyph, lsb, points, contours = b"", 0, 0, 0
+        else:
+            glyph, lsb, points, contours = braille_glyph(bits, advance, ascent, descent)
+        mapping[code] = len(glyphs)
+        glyphs.append(glyph)
+        metrics.append((advance, lsb))
+        max_points, max_contours = max(max_points, points), max(max_contours, contours)
+
+    glyf, offsets = b"", []
+    for glyph in glyphs:
+        offsets.append(len(glyf))
+        glyf += glyph + b"\0" * (-len(glyph) % 4)
+    offsets.append(len(glyf))
+    tables[b"glyf"] = glyf
+    tables[b"loca"] = struct.pack(">%dI" % len(offsets), *offsets)
+    struct.pack_into(">h", head, 50, 1)
+    struct.pack_into(">I", head, 8, 0)
+    tables[b"head"] = bytes(head)
+    tables[b"hmtx"] = b"".join(struct.pack(">Hh", *metric) for metric in metrics)
+    struct.pack_into(">H", hhea, 34, len(metrics))
+    tables[b"hhea"] = bytes(hhea)
+    struct.pack_into(">HHH", maxp, 4, len(glyphs), max_points, max_contours)
+    tables[b"maxp"] = bytes(maxp)
+    tables[b"cmap"] = write_cmap(mapping)
+    # version 3 has no glyph names, so there's none to come up with
+    tables[b"post"] = struct.pack(">I", 0x00030000) + tables[b"post"][4:32]
+    tables[b"name"] = rename(tables[b"name"])
+    open(target, "wb").write(write_font(tables))
+
+
+if __name__ == "__main__":
+    main(sys.argv[1], sys.argv[2])
@@ -0,0 +1,35 @@
+//! Feeds arbitrary bytes through decode and convert. Run with
+//! `cargo fuzz run decode_convert` from `ascii_artinator_core`; any input that
+//! makes it panic instead of returning an error is a bug.
+
+#![no_main]
+
+use ascii_artinator_core::{ConvertOptions, Charset, Mode};
+use libfuzzer_sys::fuzz_target;
+
+const FORMATS: [image::ImageFormat; 8] = [
+    image::ImageFormat::Png,
+    image::ImageFormat::Jpeg,
+    image::ImageFormat::Gif,
+    image::ImageFormat::WebP,
+    image::ImageFormat::Bmp,
+    image::ImageFormat::Ico,
+    image::ImageFormat::Tiff,
+    image::ImageFormat::Tga,
+];
+
+fuzz_target!(|data: &[u8]| {
+    // the first byte picks the decoder and options, so a single target
+    // covers all of them
+    let Some((&selector, bytes)) = data.split_first() else {
+        return;
+    };
+    let format = FORMATS[selector as usize % FORMATS.len()];
+    let opts = ConvertOptions {
+        charset: if selector & 0x10 == 0 { Charset::Braille } else { Charset::Quadrant },
+        mode: if selector & 0x20 == 0 { Mode::Dither } else { Mode::Edges },
+        border: selector & 0x40 != 0,
+        ..ConvertOptions::default()
+    };
+    let _ = ascii_artinator_core::convert_bytes(bytes, Some(format), &opts);
+});
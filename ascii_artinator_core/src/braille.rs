@@ -0,0 +1,254 @@
+//! Mapping dots to braille characters.
+
+pub const BRAILLE_CHARS: [char; 256] = [
+'⠀', '⠁', '⠂', '⠃', '⠄', '⠅', '⠆', '⠇', '⠈', '⠉', '⠊', '⠋', '⠌', '⠍', '⠎', '⠏', '⠐', '⠑', '⠒', '⠓', '⠔', '⠕', '⠖', '⠗', '⠘', '⠙',
+'⠚', '⠛', '⠜', '⠝', '⠞', '⠟', '⠠', '⠡', '⠢', '⠣', '⠤', '⠥', '⠦', '⠧', '⠨', '⠩', '⠪', '⠫', '⠬', '⠭', '⠮', '⠯', '⠰', '⠱', '⠲',
+'⠳', '⠴', '⠵', '⠶', '⠷', '⠸', '⠹', '⠺', '⠻', '⠼', '⠽', '⠾', '⠿', '⡀', '⡁', '⡂', '⡃', '⡄', '⡅', '⡆', '⡇', '⡈', '⡉', '⡊', '⡋',
+'⡌', '⡍', '⡎', '⡏', '⡐', '⡑', '⡒', '⡓', '⡔', '⡕', '⡖', '⡗', '⡘', '⡙', '⡚', '⡛', '⡜', '⡝', '⡞', '⡟', '⡠', '⡡', '⡢', '⡣', '⡤',
+'⡥', '⡦', '⡧', '⡨', '⡩', '⡪', '⡫', '⡬', '⡭', '⡮', '⡯', '⡰', '⡱', '⡲', '⡳', '⡴', '⡵', '⡶', '⡷', '⡸', '⡹', '⡺', '⡻', '⡼', '⡽',
+'⡾', '⡿', '⢀', '⢁', '⢂', '⢃', '⢄', '⢅', '⢆', '⢇', '⢈', '⢉', '⢊', '⢋', '⢌', '⢍', '⢎', '⢏', '⢐', '⢑', '⢒', '⢓', '⢔', '⢕', '⢖',
+'⢗', '⢘', '⢙', '⢚', '⢛', '⢜', '⢝', '⢞', '⢟', '⢠', '⢡', '⢢', '⢣', '⢤', '⢥', '⢦', '⢧', '⢨', '⢩', '⢪', '⢫', '⢬', '⢭', '⢮', '⢯',
+'⢰', '⢱', '⢲', '⢳', '⢴', '⢵', '⢶', '⢷', '⢸', '⢹', '⢺', '⢻', '⢼', '⢽', '⢾', '⢿', '⣀', '⣁', '⣂', '⣃', '⣄', '⣅', '⣆', '⣇', '⣈',
+'⣉', '⣊', '⣋', '⣌', '⣍', '⣎', '⣏', '⣐', '⣑', '⣒', '⣓', '⣔', '⣕', '⣖', '⣗', '⣘', '⣙', '⣚', '⣛', '⣜', '⣝', '⣞', '⣟', '⣠', '⣡',
+'⣢', '⣣', '⣤', '⣥', '⣦', '⣧', '⣨', '⣩', '⣪', '⣫', '⣬', '⣭', '⣮', '⣯', '⣰', '⣱', '⣲', '⣳', '⣴', '⣵', '⣶', '⣷', '⣸', '⣹', '⣺',
+'⣻', '⣼', '⣽', '⣾', '⣿'
+];
+
+/// Which bit of a braille pattern a dot sets, given its position in the
+/// cell's 2x4 block.
+fn bit_mask(x: u32, y: u32) -> u8 {
+    if x.is_multiple_of(2) {
+        match y % 4 {
+            0 => 0b00000001,
+            1 => 0b00000010,
+            2 => 0b00000100,
+            _ => 0b01000000
+        }
+    } else {
+        match y % 4 {
+            0 => 0b00001000,
+            1 => 0b00010000,
+            2 => 0b00100000,
+            _ => 0b10000000
+        }
+    }
+}
+
+/// How the cells of a grid are spread over the braille chars.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrailleStats {
+    /// How many cells there are of each char, blank ones as U+2800.
+    pub counts: std::collections::BTreeMap<char, usize>,
+    /// Raised dots out of all the dots there could be, in percent; 0 for an
+    /// empty grid.
+    pub fill: f64,
+}
+
+/// A grid of braille cells, each the dot pattern of one char, for working on
+/// the output as dots rather than as text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrailleGrid {
+    /// Row by row; every row has the same length.
+    pub cells: Vec<Vec<u8>>,
+}
+
+impl BrailleGrid {
+    /// An empty grid of `cols` by `rows` chars.
+    pub fn new(cols: usize, rows: usize) -> Self {
+        return Self { cells: vec![vec![0; cols]; rows] };
+    }
+
+    /// Reads converted rows back into dots. Anything that isn't braille, like
+    /// color escapes, borders or the spaces of other blank modes, is left
+    /// out, and short rows are padded with blank cells.
+    pub fn from_rows(rows: &[String]) -> Self {
+        let mut cells: Vec<Vec<u8>> = rows.iter().map(|row| {
+            return row.chars()
+                .filter_map(|c| (c as u32).checked_sub(0x2800).filter(|&val| val <= 0xff))
+                .map(|val| val as u8)
+                .collect();
+        }).collect();
+        let cols = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &mut cells {
+            row.resize(cols, 0);
+        }
+        return Self { cells };
+    }
+
+    /// Every pixel above 80 becomes a raised dot.
+    pub fn from_dots(gray_img: &image::GrayImage) -> Self {
+        let mut grid = Self::new(
+            gray_img.width().div_ceil(2) as usize,
+            gray_img.height().div_ceil(4) as usize
+        );
+        for (x, y, pix) in gray_img.enumerate_pixels() {
+            if pix.0[0] > 80 {
+                grid.set_dot(x, y, true);
+            }
+        }
+        return grid;
+    }
+
+    pub fn cols(&self) -> usize {
+        return self.cells.first().map(|row| row.len()).unwrap_or(0);
+    }
+
+    pub fn rows(&self) -> usize {
+        return self.cells.len();
+    }
+
+    /// Whether the dot at `x`, `y` (in dots, not chars) is raised.
+    pub fn get_dot(&self, x: u32, y: u32) -> bool {
+        return self.cells[(y / 4) as usize][(x / 2) as usize] & bit_mask(x, y) != 0;
+    }
+
+    pub fn set_dot(&mut self, x: u32, y: u32, raised: bool) {
+        let val = &mut self.cells[(y / 4) as usize][(x / 2) as usize];
+        let mask = bit_mask(x, y);
+        if raised {
+            *val |= mask;
+        } else {
+            *val &= !mask;
+        }
+    }
+
+    /// The cells of every row in reverse order, for right-to-left displays.
+    /// Each cell keeps its dots as they are, unless `mirror_dots`, which
+    /// swaps its two columns of dots too.
+    pub fn reverse_cells(&self, mirror_dots: bool) -> Self {
+        let mirror = |val: u8| -> u8 {
+            if !mirror_dots {
+                return val;
+            }
+            // dots 1-3 and 7 are the left column, 4-6 and 8 the right one
+            return (val & 0b0000_0111) << 3 | (val & 0b0011_1000) >> 3 | (val & 0b0100_0000) << 1 | (val & 0b1000_0000) >> 1;
+        };
+        return Self {
+            cells: self.cells.iter().map(|row| row.iter().rev().map(|&val| mirror(val)).collect()).collect(),
+        };
+    }
+
+    /// Just the top `rows` rows, with a row of its own under them with a
+    /// braille `…` in the middle if any were cut off.
+    pub fn preview(&self, rows: usize) -> Self {
+        if self.rows() <= rows {
+            return self.clone();
+        }
+        let mut preview = Self { cells: self.cells[..rows].to_vec() };
+        preview.cells.push(vec![0; self.cols()]);
+        // three dots low in the row, a cell apart
+        let (middle, y) = (self.cols() as i64 / 2 * 2, rows as i64 * 4 + 2);
+        for x in [middle - 2, middle, middle + 2] {
+            if (0..self.cols() as i64 * 2).contains(&x) {
+                preview.set_dot(x as u32, y as u32, true);
+            }
+        }
+        return preview;
+    }
+
+    /// Drops the blank rows and columns around the edges.
+    pub fn trim_blank(&self) -> Self {
+        let is_blank_col = |col: usize| self.cells.iter().all(|row| row[col] == 0);
+        let Some(top) = self.cells.iter().position(|row| row.iter().any(|&val| val != 0)) else {
+            return Self::new(0, 0);
+        };
+        let bottom = self.cells.iter().rposition(|row| row.iter().any(|&val| val != 0)).unwrap_or(top);
+        let left = (0..self.cols()).find(|&col| !is_blank_col(col)).unwrap_or(0);
+        let right = (0..self.cols()).rfind(|&col| !is_blank_col(col)).unwrap_or(left);
+        return Self { cells: self.cells[top..=bottom].iter().map(|row| row[left..=right].to_vec()).collect() };
+    }
+
+    /// A line of raised dots all around, in a ring of new cells.
+    pub fn add_border(&self) -> Self {
+        let (cols, rows) = (self.cols() + 2, self.rows() + 2);
+        let mut bordered = Self::new(cols, rows);
+        for (y, row) in self.cells.iter().enumerate() {
+            bordered.cells[y + 1][1..cols - 1].copy_from_slice(row);
+        }
+        let (max_x, max_y) = (cols as u32 * 2 - 1, rows as u32 * 4 - 1);
+        for x in 0..=max_x {
+            bordered.set_dot(x, 0, true);
+            bordered.set_dot(x, max_y, true);
+        }
+        for y in 0..=max_y {
+            bordered.set_dot(0, y, true);
+            bordered.set_dot(max_x, y, true);
+        }
+        return bordered;
+    }
+
+    /// Flips the dots over the diagonal, so rows become columns. Cells are
+    /// taller than wide, so the grid regroups: 4 columns of chars per 2 rows.
+    pub fn transpose(&self) -> Self {
+        let (dot_w, dot_h) = (self.cols() as u32 * 2, self.rows() as u32 * 4);
+        let mut transposed = Self::new(dot_h.div_ceil(2) as usize, dot_w.div_ceil(4) as usize);
+        for y in 0..dot_h {
+            for x in 0..dot_w {
+                if self.get_dot(x, y) {
+                    transposed.set_dot(y, x, true);
+                }
+            }
+        }
+        return transposed;
+    }
+
+    /// One string per row, with `blank` for the blank cells.
+    pub fn to_rows(&self, blank: char) -> Vec<String> {
+        return self.cells.iter().map(|row| {
+            return row.iter().map(|&val| {
+                if val == 0 {
+                    return blank;
+                }
+                return BRAILLE_CHARS[val as usize];
+            }).collect();
+        }).collect();
+    }
+
+    /// Which chars the cells are, and how full of dots the grid is.
+    pub fn stats(&self) -> BrailleStats {
+        let mut counts = std::collections::BTreeMap::new();
+        let mut raised = 0;
+        for &val in self.cells.iter().flatten() {
+            *counts.entry(BRAILLE_CHARS[val as usize]).or_insert(0) += 1;
+            raised += val.count_ones() as usize;
+        }
+        let dots = self.cols() * self.rows() * 8;
+        let fill = if dots == 0 { 0.0 } else { raised as f64 * 100.0 / dots as f64 };
+        return BrailleStats { counts, fill };
+    }
+}
+
+impl std::fmt::Display for BrailleGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", self.to_rows(BRAILLE_CHARS[0]).join("\n"));
+    }
+}
+
+/// Braille, with every pixel above 80 a raised dot. Empty cells get a
+/// single dot instead, since chat tends to eat blank braille.
+pub struct BrailleMapper;
+
+impl crate::mapper::CellMapper for BrailleMapper {
+    fn cell_size(&self) -> (u32, u32) {
+        return (2, 4);
+    }
+
+    fn map_cell(&self, block: &[u8]) -> char {
+        let mut val = 0;
+        for (i, &pix) in block.iter().enumerate() {
+            if pix > 80 {
+                val |= bit_mask(i as u32 % 2, i as u32 / 2);
+            }
+        }
+        if val == 0 {
+            return BRAILLE_CHARS[1];
+        }
+        return BRAILLE_CHARS[val as usize];
+    }
+}
+
+/// Gray pixels straight to braille rows, blank cells as a single dot the way
+/// `blank_mode=dot` has them.
+pub fn gray_to_braille(gray_img: &image::GrayImage) -> Vec<String> {
+    return crate::mapper::map_cells(gray_img, &BrailleMapper);
+}
@@ -0,0 +1,155 @@
+//! Meme-style captions above and below the image, drawn with a tiny font
+//! into dots so they come out in the same characters as the image.
+
+use image::GrayImage;
+
+/// Glyphs are 3 dots wide and 5 tall, one row per entry, leftmost dot in the
+/// highest bit.
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+/// A dot of space after every glyph and under every line.
+const CHAR_PITCH: u32 = GLYPH_WIDTH + 1;
+const LINE_PITCH: u32 = GLYPH_HEIGHT + 1;
+
+/// Lowercase is drawn as uppercase; anything missing is drawn as `?`.
+fn glyph(c: char) -> [u8; 5] {
+    return match c.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '"' => [0b101, 0b101, 0b000, 0b000, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '&' => [0b010, 0b101, 0b010, 0b101, 0b011],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+    };
+}
+
+/// Splits the text into lines of at most `max_chars`, breaking between
+/// words where it can and through them where it can't.
+pub fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let line_len = line.chars().count();
+        if line_len > 0 && line_len + 1 + word.len() <= max_chars {
+            line.push(' ');
+            line.extend(word);
+            continue;
+        }
+        if line_len > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > max_chars {
+            let rest = word.split_off(max_chars);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        line = word.into_iter().collect();
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    return lines;
+}
+
+/// Draws the caption as dots, wrapped and centered to `width` dots, with a
+/// height rounded up to whole rows of `cell_height` dots.
+pub fn caption_dots(text: &str, width: u32, cell_height: u32) -> GrayImage {
+    let max_chars = ((width + 1) / CHAR_PITCH).max(1) as usize;
+    let lines = wrap(text, max_chars);
+    let height = (lines.len() as u32 * LINE_PITCH).div_ceil(cell_height) * cell_height;
+    let mut dots = GrayImage::new(width, height);
+    for (row, line) in lines.iter().enumerate() {
+        let line_width = line.chars().count() as u32 * CHAR_PITCH - 1;
+        let left = width.saturating_sub(line_width) / 2;
+        let top = row as u32 * LINE_PITCH;
+        for (col, c) in line.chars().enumerate() {
+            draw_glyph(&mut dots, c, left + col as u32 * CHAR_PITCH, top, 1);
+        }
+    }
+    return dots;
+}
+
+/// Draws the text on its own, every dot of the font `scale` dots across,
+/// one line per line of the text, with a height rounded up to whole rows of
+/// `cell_height` dots.
+pub fn text_dots(text: &str, scale: u32, cell_height: u32) -> GrayImage {
+    let lines: Vec<&str> = text.lines().collect();
+    let longest = lines.iter().map(|line| line.chars().count() as u32).max().unwrap_or(0);
+    let width = (longest * CHAR_PITCH).saturating_sub(1).max(1) * scale;
+    let height = (lines.len() as u32 * LINE_PITCH * scale).max(1).div_ceil(cell_height) * cell_height;
+    let mut dots = GrayImage::new(width, height);
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            draw_glyph(&mut dots, c, col as u32 * CHAR_PITCH * scale, row as u32 * LINE_PITCH * scale, scale);
+        }
+    }
+    return dots;
+}
+
+// lights up the glyph's dots with its top left at (left, top), each one a
+// square `scale` dots across, skipping whatever falls off the image
+fn draw_glyph(dots: &mut GrayImage, c: char, left: u32, top: u32, scale: u32) {
+    for (dy, bits) in glyph(c).iter().enumerate() {
+        for dx in 0..GLYPH_WIDTH {
+            if bits & (0b100 >> dx) == 0 {
+                continue;
+            }
+            for sy in 0..scale {
+                for sx in 0..scale {
+                    let (x, y) = (left + dx * scale + sx, top + dy as u32 * scale + sy);
+                    if x < dots.width() && y < dots.height() {
+                        dots.put_pixel(x, y, image::Luma([255]));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Colored output, using ANSI escape sequences.
+
+use image::{DynamicImage, GenericImageView};
+
+use crate::braille::{self, BRAILLE_CHARS};
+
+/// Resets all colors and styles.
+pub const RESET: &str = "\x1b[0m";
+
+/// Foreground color for a cell, given which channels lit any of its dots;
+/// bit 0 is red, bit 1 green and bit 2 blue.
+const CHANNEL_COLORS: [&str; 8] = [
+    "", "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m", "\x1b[37m"
+];
+
+/// Dithers a single color channel (premultiplied by alpha) into dots.
+fn channel_dots(img: &DynamicImage, channel: usize) -> braille::BrailleGrid {
+    let mut gray_img = image::GrayImage::new(img.width(), img.height());
+    for (x, y, pix) in img.pixels() {
+        let val = pix.0[channel] as u32 * pix.0[3] as u32 / 255;
+        gray_img.put_pixel(x, y, image::Luma([val as u8]));
+    }
+    return braille::BrailleGrid::from_dots(&crate::dither_img(gray_img));
+}
+
+/// Converts each of the R, G and B channels to braille on its own, then
+/// overlays them: a cell shows the dots of every channel, colored by the mix
+/// of channels that lit it. Blank cells are `blank`. Without `colored`, the
+/// dots are all there but the colors aren't.
+pub fn rgb_split(img: &DynamicImage, blank: char, colored: bool) -> Vec<String> {
+    let channels = [channel_dots(img, 0), channel_dots(img, 1), channel_dots(img, 2)];
+    let mut rows = Vec::new();
+    for row_idx in 0..channels[0].rows() {
+        let mut row = String::new();
+        let mut cur_color = "";
+        for col_idx in 0..channels[0].cols() {
+            let mut val = 0;
+            let mut mix = 0;
+            for (bit, channel) in channels.iter().enumerate() {
+                let channel_val = channel.cells[row_idx][col_idx];
+                if channel_val != 0 {
+                    val |= channel_val;
+                    mix |= 1 << bit;
+                }
+            }
+            let color = if colored { CHANNEL_COLORS[mix] } else { "" };
+            if color != cur_color {
+                row.push_str(if color.is_empty() { RESET } else { color });
+                cur_color = color;
+            }
+            row.push(if val == 0 { blank } else { BRAILLE_CHARS[val as usize] });
+        }
+        if !cur_color.is_empty() {
+            row.push_str(RESET);
+        }
+        rows.push(row);
+    }
+    return rows;
+}
+
+/// Makes sure colored rows end on a reset, so the last color can't bleed
+/// into whatever the terminal prints next.
+pub fn end_with_reset(rows: &mut [String]) {
+    if !rows.iter().any(|row| row.contains('\x1b')) {
+        return;
+    }
+    if let Some(last) = rows.last_mut().filter(|last| !last.ends_with(RESET)) {
+        last.push_str(RESET);
+    }
+}
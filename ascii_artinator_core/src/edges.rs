@@ -0,0 +1,75 @@
+//! Sobel edge detection for `mode=edges`, with optional Canny-style
+//! double-threshold hysteresis to link up broken edges.
+
+use std::collections::VecDeque;
+
+/// Sobel gradient magnitude of every pixel, clamped to 0..=255. Borders are
+/// handled by clamping coordinates to the image.
+fn sobel(gray: &image::GrayImage) -> Vec<f32> {
+    let (width, height) = gray.dimensions();
+    let at = |x: i64, y: i64| -> f32 {
+        let x = x.clamp(0, width as i64 - 1) as u32;
+        let y = y.clamp(0, height as i64 - 1) as u32;
+        return gray.get_pixel(x, y).0[0] as f32;
+    };
+    let mut magnitudes = Vec::with_capacity((width * height) as usize);
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                - at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1);
+            let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                - at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1);
+            magnitudes.push((gx * gx + gy * gy).sqrt().min(255.0));
+        }
+    }
+    return magnitudes;
+}
+
+/// Returns a 0/255 image where edges are lit. Pixels at or above `high` are
+/// strong edges; if `low` is given, pixels at or above it are weak edges that
+/// are only kept when 8-connected (possibly through other weak edges) to a
+/// strong one.
+pub fn detect_edges(gray: &image::GrayImage, low: Option<u8>, high: u8) -> image::GrayImage {
+    return detect_weighted_edges(gray, low, high, 1.0);
+}
+
+/// [`detect_edges`] with every magnitude scaled by `weight` first, so at 1
+/// it's all the same edges and towards 0 fewer and fewer of the weaker ones.
+pub fn detect_weighted_edges(gray: &image::GrayImage, low: Option<u8>, high: u8, weight: f32) -> image::GrayImage {
+    let (width, height) = gray.dimensions();
+    let mut edges = image::GrayImage::new(width, height);
+    if width == 0 || height == 0 {
+        return edges;
+    }
+    let magnitudes: Vec<f32> = sobel(gray).into_iter().map(|mag| mag * weight).collect();
+    let mut queue = VecDeque::new();
+
+    for (i, &mag) in magnitudes.iter().enumerate() {
+        if mag >= high as f32 {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            edges.put_pixel(x, y, image::Luma([255]));
+            queue.push_back((x, y));
+        }
+    }
+
+    if let Some(low) = low {
+        while let Some((x, y)) = queue.pop_front() {
+            for dy in -1i64..=1 {
+                for dx in -1i64..=1 {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as u32, ny as u32);
+                    let weak = magnitudes[(ny * width + nx) as usize] >= low as f32;
+                    if weak && edges.get_pixel(nx, ny).0[0] == 0 {
+                        edges.put_pixel(nx, ny, image::Luma([255]));
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+        }
+    }
+
+    return edges;
+}
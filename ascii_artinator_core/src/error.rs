@@ -0,0 +1,99 @@
+use std::fmt;
+
+/// Everything that can go wrong between getting a link and giving back
+/// braille. The messages are what the user ends up seeing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The image could not be fetched.
+    Fetch,
+    /// The image's host took too long to accept the connection.
+    ConnectTimeout,
+    /// The image's host went quiet for too long partway.
+    ReadTimeout,
+    /// Too many conversions going on, gave up waiting for a slot.
+    Busy,
+    /// The content is not in a format we can read.
+    NotAnImage,
+    /// The content claims to be an image but failed to decode.
+    Decode,
+    /// The SVG could not be parsed or rasterized.
+    Svg,
+    /// The SVG needs the `svg` feature, which is off.
+    SvgUnsupported,
+    /// None of the places a favicon could be had one.
+    NoFavicon,
+    /// The link is a page, and no image could be found on it.
+    NoPageImage,
+    /// The requested frame is past the end of the animation.
+    NoSuchFrame(usize),
+    /// The image has more pixels than we are willing to decode.
+    TooLarge,
+    /// The image is a single color, and `warn_flat` was asked for.
+    FlatImage,
+    /// The font for `render.png` is not a font.
+    BadFont,
+    /// The PNG could not be encoded.
+    Render,
+    /// `render.png` needs the `render` feature, which is off.
+    RenderUnsupported,
+    /// The output is too long for chat.
+    TooTall,
+    /// Some option has a bad value.
+    BadOption(String),
+    /// The sink the output was being written into gave up.
+    Write,
+    /// The conversion ran past the deadline it was given.
+    DeadlineExceeded,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ConvertError::Fetch => write!(f, "failed to request image S OMEGALUL BAD"),
+            ConvertError::ConnectTimeout => write!(f, "image host didn't pick up, timed out connecting Sadge"),
+            ConvertError::ReadTimeout => write!(f, "image host stopped sending, timed out reading Sadge"),
+            ConvertError::Busy => write!(f, "too many images at once, try again later Sadge"),
+            ConvertError::NotAnImage => write!(f, "link is not an image KEEEEEEEEEK"),
+            ConvertError::Decode => write!(f, "failed to read image INSANECAT"),
+            ConvertError::Svg => write!(f, "failed to read svg INSANECAT"),
+            ConvertError::SvgUnsupported => write!(f, "svg support is not enabled on this server"),
+            ConvertError::NoFavicon => write!(f, "couldn't find a favicon there Sadge"),
+            ConvertError::NoPageImage => write!(f, "that's a page, not an image, and there's no image on it either Sadge"),
+            ConvertError::NoSuchFrame(n) => write!(f, "there is no frame {} in that image", n),
+            ConvertError::TooLarge => write!(f, "image is way too big monkaS"),
+            ConvertError::FlatImage => write!(f, "image is just one color, nothing to see here Hmm"),
+            ConvertError::BadFont => write!(f, "font for render.png is broken, tell the admin Sadge"),
+            ConvertError::Render => write!(f, "failed to make a png INSANECAT"),
+            ConvertError::RenderUnsupported => write!(f, "render.png is not enabled on this server"),
+            ConvertError::TooTall => write!(f, "image too tall smh"),
+            ConvertError::BadOption(msg) => write!(f, "{}", msg),
+            ConvertError::Write => write!(f, "couldn't write the output out Sadge"),
+            ConvertError::DeadlineExceeded => write!(f, "ran out of time for that one, try again later Sadge"),
+        };
+    }
+}
+
+impl ConvertError {
+    /// A short, stable name for the kind of failure, safe to show anyone.
+    pub fn category(&self) -> &'static str {
+        return match self {
+            ConvertError::Fetch
+            | ConvertError::ConnectTimeout
+            | ConvertError::ReadTimeout
+            | ConvertError::NoFavicon
+            | ConvertError::NoPageImage => "fetch",
+            ConvertError::Busy | ConvertError::DeadlineExceeded => "busy",
+            ConvertError::NotAnImage
+            | ConvertError::Decode
+            | ConvertError::Svg
+            | ConvertError::NoSuchFrame(_)
+            | ConvertError::FlatImage => "image",
+            ConvertError::SvgUnsupported | ConvertError::RenderUnsupported => "unsupported",
+            ConvertError::TooLarge | ConvertError::TooTall => "size",
+            ConvertError::BadOption(_) => "options",
+            ConvertError::BadFont | ConvertError::Render | ConvertError::Write => "server",
+        };
+    }
+}
+
+impl std::error::Error for ConvertError {}
@@ -0,0 +1,107 @@
+//! How big a conversion will come out, worked out from the size of the image
+//! alone, so nothing has to be decoded or converted to know.
+
+use crate::{caption, Charset, ConvertError, ConvertOptions, Format};
+
+/// The projected size of the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeEstimate {
+    pub cols: usize,
+    pub rows: usize,
+    /// Of the body, once formatted and encoded; none for `render.png`, whose
+    /// size depends on what's drawn. Color escapes aren't counted.
+    pub bytes: Option<usize>,
+}
+
+/// The width and height of the image in the bytes, from the header alone
+/// where the format allows. For GIFs and TIFFs that's the first frame.
+pub fn image_dimensions(
+    bytes: &[u8],
+    mime_format: Option<image::ImageFormat>,
+    ext_format: Option<image::ImageFormat>
+) -> Result<(u32, u32), ConvertError> {
+    if crate::svg::looks_like_svg(bytes) {
+        return crate::svg::raster_size(bytes);
+    }
+    let candidates = crate::format_candidates(bytes, mime_format, ext_format);
+    if candidates.is_empty() {
+        return Err(ConvertError::NotAnImage);
+    }
+    for format in candidates {
+        let reader = image::io::Reader::with_format(std::io::Cursor::new(bytes), format);
+        if let Ok(dimensions) = reader.into_dimensions() {
+            return Ok(dimensions);
+        }
+    }
+    return Err(ConvertError::Decode);
+}
+
+// what `DynamicImage::resize` comes to: as big as fits, keeping the ratio
+fn fit_within(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    let ratio = f64::min(max_width as f64 / width as f64, max_height as f64 / height as f64);
+    let fitted_w = ((width as f64 * ratio).round() as u32).max(1);
+    let fitted_h = ((height as f64 * ratio).round() as u32).max(1);
+    return (fitted_w, fitted_h);
+}
+
+/// The dots an image of this size gets resized to, the same as the
+/// conversion would. `trim_bg` can only make it smaller, and isn't counted.
+pub fn dot_size(width: u32, height: u32, opts: &ConvertOptions) -> (u32, u32) {
+    let (width, height) = crate::preprocess::rotated_size(width, height, opts.rotate);
+    if let (Some(cols), Some(rows)) = (opts.cols, opts.rows) {
+        let (cell_width, cell_height) = opts.charset.mapper().cell_size();
+        return (cols * cell_width, rows * cell_height);
+    }
+    if let (Some(src_width), Some(src_height)) = (opts.src_width, opts.src_height) {
+        return (src_width, src_height);
+    }
+    let target = crate::target_size(width, height, opts.aspect());
+    // see resize_img, only the plain resize keeps the ratio
+    if opts.center_weight == 1.0 && opts.aspect() == 1.0 {
+        return fit_within(width, height, target.0, target.1);
+    }
+    return target;
+}
+
+/// How big converting an image of this size with these options comes out.
+/// It's the most it could be: blank rows that `collapse_blanks` would
+/// collapse and the edges `trim_bg` or `trim_blank` would trim are all
+/// counted.
+pub fn estimate_size(width: u32, height: u32, opts: &ConvertOptions) -> Result<SizeEstimate, ConvertError> {
+    crate::validate(opts)?;
+    let (dot_w, dot_h) = dot_size(width, height, opts);
+    let mapper = opts.charset.mapper();
+    let (cell_w, cell_h) = mapper.cell_size();
+    // a grid of full cells the right size, put through the same steps the
+    // real rows go through
+    let full = mapper.map_cell(&vec![255; (cell_w * cell_h) as usize]);
+    let row: String = std::iter::repeat_n(full, dot_w.div_ceil(cell_w) as usize).collect();
+    let mut rows = dot_h.div_ceil(cell_h) as usize;
+    for text in [&opts.top, &opts.bottom].into_iter().flatten().filter(|text| !text.trim().is_empty()) {
+        rows += (caption::caption_dots(text, dot_w, cell_h).height() / cell_h) as usize;
+    }
+    // and the … row under what's left
+    if let Some(preview_rows) = opts.preview_rows.filter(|&preview_rows| rows > preview_rows) {
+        rows = preview_rows + 1;
+    }
+    // a border of dots comes out the same size as the box
+    let mut lines = crate::finish_rows(vec![row; rows], opts, opts.border);
+    let format = opts.format.unwrap_or_default();
+    if opts.summary && format == Format::Text {
+        let mut applied = opts.clone();
+        applied.dither = opts.dither.resolve(dot_w.div_ceil(cell_w));
+        lines.push(crate::summary_line(&lines, &applied));
+    }
+    let bytes = match format {
+        Format::RenderPng => None,
+        // and the ascii of the same grid next to it, full cells the same
+        Format::Multi => {
+            let ascii_full = Charset::Ascii.mapper().map_cell(&vec![255; (cell_w * cell_h) as usize]);
+            let ascii: Vec<String> = lines.iter().map(|line| line.replace(full, &ascii_full.to_string())).collect();
+            let text = crate::output::collect(|out| crate::output::write_multi(out, &lines, &ascii));
+            Some(crate::encode(text, opts.encoding).len())
+        },
+        _ => Some(crate::encode(crate::format_rows(&lines, format), opts.encoding).len()),
+    };
+    return Ok(SizeEstimate { cols: crate::rows::max_width(&lines), rows: lines.len(), bytes });
+}
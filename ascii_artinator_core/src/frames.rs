@@ -0,0 +1,150 @@
+//! Access to the frames of animated images, and the pages of TIFFs, which
+//! work the same way.
+
+use std::io::{Read, Seek};
+
+use image::AnimationDecoder;
+
+use crate::ConvertError;
+
+/// Decodes the `n`th frame (0-indexed) of an animated GIF. Frames come out
+/// composited over the previous ones, as they would be shown.
+pub fn gif_frame(bytes: &[u8], n: usize) -> Result<image::DynamicImage, ConvertError> {
+    let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(bytes))
+        .map_err(|_| ConvertError::Decode)?;
+    return match decoder.into_frames().nth(n) {
+        Some(Ok(frame)) => Ok(image::DynamicImage::ImageRgba8(frame.into_buffer())),
+        Some(Err(_)) => Err(ConvertError::Decode),
+        None => Err(ConvertError::NoSuchFrame(n)),
+    };
+}
+
+/// Most frames a GIF may have, so counting them has an end.
+pub const MAX_FRAMES: usize = 1000;
+
+/// Decodes the frames from `offset` up to `limit` of them, along with how many
+/// frames there are in total. An offset past the end gives no frames. The
+/// `deadline` is checked at every frame.
+pub fn gif_frames(
+    bytes: &[u8],
+    offset: usize,
+    limit: usize,
+    max_pixels: u64,
+    deadline: Option<std::time::Instant>
+) -> Result<(Vec<image::DynamicImage>, usize), ConvertError> {
+    let reader = image::io::Reader::with_format(std::io::Cursor::new(bytes), image::ImageFormat::Gif);
+    let (width, height) = reader.into_dimensions().map_err(|_| ConvertError::Decode)?;
+    if width as u64 * height as u64 > max_pixels {
+        return Err(ConvertError::TooLarge);
+    }
+    let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(bytes))
+        .map_err(|_| ConvertError::Decode)?;
+    let mut frames = Vec::new();
+    let mut total = 0;
+    for frame in decoder.into_frames() {
+        crate::options::check_deadline(deadline)?;
+        let frame = frame.map_err(|_| ConvertError::Decode)?;
+        if total >= offset && frames.len() < limit {
+            frames.push(image::DynamicImage::ImageRgba8(frame.into_buffer()));
+        }
+        total += 1;
+        if total > MAX_FRAMES {
+            return Err(ConvertError::TooLarge);
+        }
+    }
+    return Ok((frames, total));
+}
+
+/// Decodes the `n`th page (0-indexed) of a TIFF.
+pub fn tiff_page(bytes: &[u8], n: usize, max_pixels: u64) -> Result<image::DynamicImage, ConvertError> {
+    let mut decoder = tiff::decoder::Decoder::new(std::io::Cursor::new(bytes))
+        .map_err(|_| ConvertError::Decode)?;
+    for _ in 0..n {
+        if !decoder.more_images() {
+            return Err(ConvertError::NoSuchFrame(n));
+        }
+        decoder.next_image().map_err(|_| ConvertError::Decode)?;
+    }
+    return tiff_image(&mut decoder, max_pixels);
+}
+
+/// Like [`gif_frames`], for the pages of a TIFF.
+pub fn tiff_pages(
+    bytes: &[u8],
+    offset: usize,
+    limit: usize,
+    max_pixels: u64,
+    deadline: Option<std::time::Instant>
+) -> Result<(Vec<image::DynamicImage>, usize), ConvertError> {
+    let mut decoder = tiff::decoder::Decoder::new(std::io::Cursor::new(bytes))
+        .map_err(|_| ConvertError::Decode)?;
+    let mut pages = Vec::new();
+    let mut total = 0;
+    loop {
+        crate::options::check_deadline(deadline)?;
+        // only the pages asked for get decoded, the rest are just counted
+        if total >= offset && pages.len() < limit {
+            pages.push(tiff_image(&mut decoder, max_pixels)?);
+        }
+        total += 1;
+        if total > MAX_FRAMES {
+            return Err(ConvertError::TooLarge);
+        }
+        if !decoder.more_images() {
+            break;
+        }
+        decoder.next_image().map_err(|_| ConvertError::Decode)?;
+    }
+    return Ok((pages, total));
+}
+
+// decodes the page the decoder is on, if it's in one of the usual 8 or 16
+// bit gray or RGB layouts
+fn tiff_image<R: Read + Seek>(
+    decoder: &mut tiff::decoder::Decoder<R>,
+    max_pixels: u64
+) -> Result<image::DynamicImage, ConvertError> {
+    use image::DynamicImage as Img;
+    use tiff::decoder::DecodingResult as Data;
+    use tiff::ColorType;
+    let (width, height) = decoder.dimensions().map_err(|_| ConvertError::Decode)?;
+    if width as u64 * height as u64 > max_pixels {
+        return Err(ConvertError::TooLarge);
+    }
+    let color_type = decoder.colortype().map_err(|_| ConvertError::Decode)?;
+    let data = decoder.read_image().map_err(|_| ConvertError::Decode)?;
+    let img = match (color_type, data) {
+        (ColorType::Gray(8), Data::U8(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageLuma8),
+        (ColorType::GrayA(8), Data::U8(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageLumaA8),
+        (ColorType::RGB(8), Data::U8(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageRgb8),
+        (ColorType::RGBA(8), Data::U8(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageRgba8),
+        (ColorType::Gray(16), Data::U16(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageLuma16),
+        (ColorType::GrayA(16), Data::U16(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageLumaA16),
+        (ColorType::RGB(16), Data::U16(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageRgb16),
+        (ColorType::RGBA(16), Data::U16(buf)) => image::ImageBuffer::from_raw(width, height, buf).map(Img::ImageRgba16),
+        _ => None,
+    };
+    return img.ok_or(ConvertError::Decode);
+}
+
+/// Every frame of a GIF or page of a TIFF, for `frames=all`; anything else
+/// is the one image [`decode_with_hints`](crate::decode_with_hints) gives.
+pub fn decode_all_with_hints(
+    bytes: &[u8],
+    mime_format: Option<image::ImageFormat>,
+    ext_format: Option<image::ImageFormat>,
+    max_pixels: u64,
+    opts: &crate::ConvertOptions
+) -> Result<Vec<image::DynamicImage>, ConvertError> {
+    opts.check_deadline()?;
+    let (frames, _) = match crate::format_candidates(bytes, mime_format, ext_format).first() {
+        Some(image::ImageFormat::Gif) => gif_frames(bytes, 0, MAX_FRAMES, max_pixels, opts.deadline)?,
+        Some(image::ImageFormat::Tiff) => tiff_pages(bytes, 0, MAX_FRAMES, max_pixels, opts.deadline)?,
+        _ => (vec![crate::decode_with_hints(bytes, mime_format, ext_format, max_pixels, opts)?], 1),
+    };
+    // a GIF without a single frame
+    if frames.is_empty() {
+        return Err(ConvertError::Decode);
+    }
+    return Ok(frames);
+}
@@ -0,0 +1,29 @@
+//! Mapping dots to half block characters.
+
+// each half block char covers a 1x2 pixel block; bit 0 is the top pixel and
+// bit 1 the bottom one
+pub const HALFBLOCK_CHARS: [char; 4] = [' ', '▀', '▄', '█'];
+
+/// Half blocks, with every pixel above 80 a filled half.
+pub struct HalfblockMapper;
+
+impl crate::mapper::CellMapper for HalfblockMapper {
+    fn cell_size(&self) -> (u32, u32) {
+        return (1, 2);
+    }
+
+    fn map_cell(&self, block: &[u8]) -> char {
+        let mut val = 0;
+        for (bit, &pix) in block.iter().enumerate() {
+            if pix > 80 {
+                val |= 1 << bit;
+            }
+        }
+        return HALFBLOCK_CHARS[val];
+    }
+}
+
+/// Gray pixels straight to half block rows, blank cells as spaces.
+pub fn gray_to_halfblock(gray_img: &image::GrayImage) -> Vec<String> {
+    return crate::mapper::map_cells(gray_img, &HalfblockMapper);
+}
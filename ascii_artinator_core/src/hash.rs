@@ -0,0 +1,35 @@
+//! Perceptual hashing of the source image, so near-duplicates can be told
+//! apart from different images.
+
+use image::DynamicImage;
+
+/// An average hash: the image shrunk to 8x8 and grayed, one bit per pixel,
+/// set if that pixel is brighter than the mean. Similar images differ in
+/// few bits; compare them with [`hamming_distance`].
+pub fn average_hash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(8, 8, image::imageops::Triangle).into_luma8();
+    let sum: u32 = small.pixels().map(|pix| pix.0[0] as u32).sum();
+    let mean = sum / 64;
+    let mut hash = 0u64;
+    for (i, pix) in small.pixels().enumerate() {
+        if pix.0[0] as u32 > mean {
+            hash |= 1 << i;
+        }
+    }
+    return hash;
+}
+
+/// How many bits two hashes differ in, from 0 for the same to 64.
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    return (a ^ b).count_ones();
+}
+
+/// Most bits two hashes may differ in and still be the same image, give or
+/// take a crop, a resize or a recompression; different images land far
+/// past it, around half of the 64.
+pub const NEAR_DUPLICATE_BITS: u32 = 10;
+
+/// Whether two hashes look like the same image, see [`NEAR_DUPLICATE_BITS`].
+pub fn is_near_duplicate(a: u64, b: u64) -> bool {
+    return hamming_distance(a, b) <= NEAR_DUPLICATE_BITS;
+}
@@ -0,0 +1,70 @@
+//! Picking one of the sizes in an .ico, which favicons usually have a few
+//! of. `image` only ever decodes the one it likes best.
+
+use crate::ConvertError;
+
+/// One image in the .ico's directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IcoEntry {
+    pub width: u32,
+    pub height: u32,
+    // its place in the directory, and where its PNG or BMP data is
+    index: usize,
+    offset: usize,
+    len: usize,
+}
+
+/// Reads the directory at the start of an .ico, entries in file order.
+pub fn ico_entries(bytes: &[u8]) -> Result<Vec<IcoEntry>, ConvertError> {
+    let u16_at = |at: usize| bytes.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |at: usize| bytes.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    if u16_at(0) != Some(0) || u16_at(2) != Some(1) {
+        return Err(ConvertError::Decode);
+    }
+    let count = u16_at(4).ok_or(ConvertError::Decode)? as usize;
+    let mut entries = Vec::with_capacity(count);
+    for i in 0..count {
+        let at = 6 + i * 16;
+        let header = bytes.get(at..at + 16).ok_or(ConvertError::Decode)?;
+        // a size of 0 stands for 256, which doesn't fit in the byte
+        let side = |byte: u8| if byte == 0 { 256 } else { byte as u32 };
+        let len = u32_at(at + 8).ok_or(ConvertError::Decode)? as usize;
+        let offset = u32_at(at + 12).ok_or(ConvertError::Decode)? as usize;
+        if offset.checked_add(len).is_none_or(|end| end > bytes.len()) {
+            return Err(ConvertError::Decode);
+        }
+        entries.push(IcoEntry { width: side(header[0]), height: side(header[1]), index: i, offset, len });
+    }
+    return Ok(entries);
+}
+
+/// Which entry is closest to `size` across, the bigger one on a tie, or the
+/// biggest of all with no size asked for.
+pub fn pick_entry(entries: &[IcoEntry], size: Option<u32>) -> Option<IcoEntry> {
+    let side = |entry: &IcoEntry| entry.width.max(entry.height);
+    return match size {
+        Some(size) => entries.iter().copied()
+            .min_by_key(|entry| (side(entry).abs_diff(size), std::cmp::Reverse(side(entry)))),
+        None => entries.iter().copied().max_by_key(side),
+    };
+}
+
+/// Decodes the entry [`pick_entry`] picks, by handing `image` an .ico with
+/// nothing else in it.
+pub fn ico_image(bytes: &[u8], size: Option<u32>, max_pixels: u64) -> Result<image::DynamicImage, ConvertError> {
+    let entry = pick_entry(&ico_entries(bytes)?, size).ok_or(ConvertError::Decode)?;
+    let data = &bytes[entry.offset..entry.offset + entry.len];
+    let mut single = Vec::with_capacity(22 + data.len());
+    single.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    // the directory entry as it was, only pointing right after itself
+    let at = 6 + 16 * entry.index;
+    single.extend_from_slice(&bytes[at..at + 12]);
+    single.extend_from_slice(&22u32.to_le_bytes());
+    single.extend_from_slice(data);
+    let reader = image::io::Reader::with_format(std::io::Cursor::new(&single), image::ImageFormat::Ico);
+    let (width, height) = reader.into_dimensions().map_err(|_| ConvertError::Decode)?;
+    if width as u64 * height as u64 > max_pixels {
+        return Err(ConvertError::TooLarge);
+    }
+    return image::load_from_memory_with_format(&single, image::ImageFormat::Ico).map_err(|_| ConvertError::Decode);
+}
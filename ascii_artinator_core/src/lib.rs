@@ -0,0 +1,947 @@
+//! The conversion pipeline: decoding, pre-processing, dithering and mapping
+//! to characters. Nothing in here knows about HTTP.
+
+#![allow(clippy::needless_return)]
+
+use image::DynamicImage;
+
+pub mod braille;
+pub mod caption;
+pub mod color;
+pub mod edges;
+pub mod estimate;
+pub mod frames;
+pub mod halfblock;
+pub mod hash;
+pub mod ico;
+pub mod mapper;
+pub mod morph;
+mod error;
+pub mod options;
+pub mod output;
+pub mod palette;
+pub mod preprocess;
+pub mod quadrant;
+pub mod render;
+pub mod resample;
+pub mod rows;
+pub mod svg;
+
+pub use error::ConvertError;
+pub use image;
+pub use options::*;
+
+/// The size, in dots, an image of this size gets resized to. `aspect`
+/// stretches it vertically, for terminals whose cells aren't exactly twice
+/// as tall as wide.
+pub fn target_size(width: u32, height: u32, aspect: f32) -> (u32, u32) {
+    let aspect_ratio = width as f32 / height as f32;
+    // very wide images would round down to no rows at all
+    return (58, ((58.0 / aspect_ratio * aspect) as u32).max(1));
+}
+
+pub fn resize_img(img: image::DynamicImage, aspect: f32) -> image::DynamicImage {
+    let (target_width, target_height) = target_size(img.width(), img.height(), aspect);
+    // plain resize keeps the image's own aspect ratio, so it only does for
+    // the usual stretch of none
+    if aspect == 1.0 {
+        return img.resize(target_width, target_height, image::imageops::Triangle);
+    }
+    return img.resize_exact(target_width, target_height, image::imageops::Triangle);
+}
+
+// with AA_DEBUG_IMAGES set, debug builds save what the stages in between
+// look like to the working directory; off otherwise, so the tests and
+// anything else calling in don't litter files around
+#[cfg(debug_assertions)]
+fn debug_save(img: &image::GrayImage, name: &str) {
+    if std::env::var_os("AA_DEBUG_IMAGES").is_some() {
+        img.save(name).ok();
+    }
+}
+
+pub fn to_gray(img: &DynamicImage) -> image::GrayImage {
+    // an 8-bit grayscale source already is the luminance
+    if let DynamicImage::ImageLuma8(gray_img) = img {
+        #[cfg(debug_assertions)]
+        debug_save(gray_img, "gray.png");
+        return gray_img.clone();
+    }
+    let mut gray_img = image::GrayImage::new(img.width(), img.height());
+
+    let compute_lightness = |rgba: &[f32; 4]| -> u8 {
+        // gray pixels, R = G = B, are taken as they are, so the weights
+        // rounding off can't nudge them
+        let luma = if rgba[0] == rgba[1] && rgba[1] == rgba[2] {
+            rgba[0]
+        } else {
+            rgba[0] * 0.2126 + rgba[1] * 0.7152 + rgba[2] * 0.0722
+        };
+        return (luma * 255.0 * rgba[3])
+          .clamp(0.0, 255.0)
+          .round() as u8;
+    };
+
+    // 16-bit and float images would lose precision going through 8 bits per
+    // channel first, so the luminance is worked out from floats
+    let img = img.to_rgba32f();
+    for (x, y, pix) in img.enumerate_pixels() {
+        let lightness = compute_lightness(&pix.0);
+        gray_img.put_pixel(x, y, image::Luma::<u8>([lightness]));
+    }
+
+    #[cfg(debug_assertions)]
+    debug_save(&gray_img, "gray.png");
+
+    return gray_img;
+}
+
+pub fn dither_img(gray_img: image::GrayImage) -> image::GrayImage {
+    return dither_with(gray_img, Dither::Sierra, None, None).expect("there's no deadline to run past");
+}
+
+/// Dithers like [`dither_img`], but fully transparent pixels, going by the
+/// alpha in `alpha`, are left as background and the error stops at them, so
+/// soft edges don't spill stray dots out into the background.
+pub fn dither_alpha(gray_img: image::GrayImage, alpha: &image::GrayImage) -> image::GrayImage {
+    return dither_with(gray_img, Dither::Sierra, Some(alpha), None).expect("there's no deadline to run past");
+}
+
+/// Dithers the way `dither` says, which has to be resolved already; auto
+/// comes out as the default. See [`dither_alpha`] for `alpha`. The
+/// `deadline` is checked at every row.
+pub fn dither_as(
+    gray_img: image::GrayImage,
+    dither: Dither,
+    alpha: Option<&image::GrayImage>,
+    deadline: Option<std::time::Instant>
+) -> Result<image::GrayImage, ConvertError> {
+    if dither == Dither::Ordered {
+        return ordered_dither(gray_img, alpha, deadline);
+    }
+    return dither_with(gray_img, dither, alpha, deadline);
+}
+
+// a 4x4 Bayer matrix, no error carried anywhere, so it keeps a steady
+// pattern that holds up on few cells
+fn ordered_dither(
+    mut gray_img: image::GrayImage,
+    alpha: Option<&image::GrayImage>,
+    deadline: Option<std::time::Instant>
+) -> Result<image::GrayImage, ConvertError> {
+    const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+    for (x, y, pix) in gray_img.enumerate_pixels_mut() {
+        if x == 0 {
+            options::check_deadline(deadline)?;
+        }
+        let threshold = BAYER[y as usize % 4][x as usize % 4] * 16 + 8;
+        let transparent = alpha.and_then(|alpha| alpha.get_pixel_checked(x, y)).is_some_and(|a| a.0[0] == 0);
+        pix.0[0] = if pix.0[0] > threshold && !transparent { 255 } else { 0 };
+    }
+
+    #[cfg(debug_assertions)]
+    debug_save(&gray_img, "dithered.png");
+
+    return Ok(gray_img);
+}
+
+// error diffusion, Sierra's or Floyd–Steinberg's
+fn dither_with(
+    mut gray_img: image::GrayImage,
+    dither: Dither,
+    alpha: Option<&image::GrayImage>,
+    deadline: Option<std::time::Instant>
+) -> Result<image::GrayImage, ConvertError> {
+    let floyd = dither == Dither::Floyd;
+    // the weights add up to 16 for Floyd–Steinberg and 32 for Sierra
+    let shift = if floyd { 4 } else { 5 };
+    let transparent = |x: u32, y: u32| -> bool {
+        return alpha.and_then(|alpha| alpha.get_pixel_checked(x, y)).is_some_and(|a| a.0[0] == 0);
+    };
+    let add_error = |img: &mut image::GrayImage, x: Option<u32>, y: Option<u32>, err: &i32, importance: i32| {
+        if let Some(xpos) = x {
+            if let Some(ypos) = y {
+                if transparent(xpos, ypos) {
+                    return;
+                }
+                if let Some(pix) = img.get_pixel_mut_checked(xpos, ypos) {
+                    *pix = image::Luma([(pix.0[0] as i32 + err * importance).clamp(0, 255) as u8]);
+                }
+            }
+        }
+    };
+
+    for y in 0..gray_img.height() {
+        options::check_deadline(deadline)?;
+        for x in 0..gray_img.width() {
+            if transparent(x, y) {
+                gray_img.put_pixel(x, y, image::Luma([0]));
+                continue;
+            }
+            let cur_pix = gray_img.get_pixel_mut(x, y);
+            let error = if cur_pix.0[0] > 127 {
+                cur_pix.0[0] as i32 - 255
+            } else {
+                cur_pix.0[0] as i32
+            } >> shift;
+            if cur_pix.0[0] > 127 {
+                cur_pix.0[0] = 255;
+            } else {
+                cur_pix.0[0] = 0;
+            }
+
+            if floyd {
+                add_error(&mut gray_img, x.checked_add(1), Some(y)    , &error, 7);
+                add_error(&mut gray_img, x.checked_sub(1), Some(y + 1), &error, 3);
+                add_error(&mut gray_img, Some(x)         , Some(y + 1), &error, 5);
+                add_error(&mut gray_img, x.checked_add(1), Some(y + 1), &error, 1);
+                continue;
+            }
+            add_error(&mut gray_img, x.checked_add(1), Some(y)    , &error, 5);
+            add_error(&mut gray_img, x.checked_add(2), Some(y)    , &error, 3);
+            add_error(&mut gray_img, x.checked_sub(2), Some(y + 1), &error, 2);
+            add_error(&mut gray_img, x.checked_sub(1), Some(y + 1), &error, 4);
+            add_error(&mut gray_img, Some(x)         , Some(y + 1), &error, 5);
+            add_error(&mut gray_img, x.checked_add(1), Some(y + 1), &error, 4);
+            add_error(&mut gray_img, x.checked_add(2), Some(y + 1), &error, 2);
+            add_error(&mut gray_img, x.checked_sub(1), Some(y + 2), &error, 2);
+            add_error(&mut gray_img, Some(x)         , Some(y + 2), &error, 3);
+            add_error(&mut gray_img, x.checked_add(1), Some(y + 2), &error, 2);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    debug_save(&gray_img, "dithered.png");
+
+    return Ok(gray_img);
+}
+
+// turns every non-ascii char (i.e. the braille) into a \uXXXX escape, so the
+// output is plain ascii
+pub fn escape_unicode(s: &str) -> String {
+    return output::collect(|out| output::write_escaped(out, s, output::unicode_escape));
+}
+
+/// The rows as one Rust string literal, with `\n` between them, ready to
+/// paste into code.
+pub fn to_rust_literal(rows: &[String]) -> String {
+    return output::collect(|out| output::write_rust_literal(out, rows));
+}
+
+/// Same as [`to_rust_literal`], for C. Control chars go in octal, since a
+/// `\x` escape in C eats every hex digit that follows it.
+pub fn to_c_literal(rows: &[String]) -> String {
+    return output::collect(|out| output::write_c_literal(out, rows));
+}
+
+/// The rows as JSON Lines, one `{"row": i, "cells": "..."}` per row.
+pub fn to_jsonl(rows: &[String]) -> String {
+    return output::collect(|out| output::write_jsonl(out, rows));
+}
+
+/// Applies the output encoding to converted text.
+pub fn encode(text: String, encoding: Encoding) -> String {
+    return match encoding {
+        Encoding::Utf8 => text,
+        Encoding::Escaped => escape_unicode(&text),
+    };
+}
+
+/// Most dots `src_width` and `src_height` may ask for, as many as the
+/// biggest `cols` and `rows` come to in braille.
+pub const MAX_SRC_WIDTH: u32 = 200;
+pub const MAX_SRC_HEIGHT: u32 = 400;
+
+/// Default for how many pixels an image may have once decoded.
+pub const DEFAULT_MAX_PIXELS: u64 = 40_000_000;
+
+/// Decodes an image, in the given format or as an SVG if it looks like one.
+/// Never panics, no matter what the bytes are. The dimensions are read from
+/// the header first, so images over `max_pixels` are rejected before they get
+/// a chance to allocate anything (e.g. decompression bombs). Which frame to
+/// decode comes from the options.
+pub fn decode(
+    bytes: &[u8],
+    format: Option<image::ImageFormat>,
+    max_pixels: u64,
+    opts: &ConvertOptions
+) -> Result<DynamicImage, ConvertError> {
+    let img = if svg::looks_like_svg(bytes) {
+        svg::rasterize(bytes, max_pixels)?
+    } else if let Some(format) = format {
+        let reader = image::io::Reader::with_format(std::io::Cursor::new(bytes), format);
+        let (width, height) = reader.into_dimensions().map_err(|_| ConvertError::Decode)?;
+        if width as u64 * height as u64 > max_pixels {
+            return Err(ConvertError::TooLarge);
+        }
+        match opts.frame {
+            Some(n) if format == image::ImageFormat::Gif => frames::gif_frame(bytes, n)?,
+            Some(n) if format == image::ImageFormat::Tiff => frames::tiff_page(bytes, n, max_pixels)?,
+            // everything else only has the one frame
+            Some(n) if n > 0 => return Err(ConvertError::NoSuchFrame(n)),
+            _ if format == image::ImageFormat::Ico => ico::ico_image(bytes, opts.ico_size, max_pixels)?,
+            _ => image::load_from_memory_with_format(bytes, format).map_err(|_| ConvertError::Decode)?,
+        }
+    } else {
+        return Err(ConvertError::NotAnImage);
+    };
+    if img.width() == 0 || img.height() == 0 {
+        return Err(ConvertError::Decode);
+    }
+    return Ok(img);
+}
+
+/// The formats worth trying to decode the bytes as, best guess first. Magic
+/// bytes are authoritative when they match something; otherwise the file
+/// extension goes before the content type, since servers mislabel things as
+/// `application/octet-stream` much more often than links lie about it.
+pub fn format_candidates(
+    bytes: &[u8],
+    mime_format: Option<image::ImageFormat>,
+    ext_format: Option<image::ImageFormat>
+) -> Vec<image::ImageFormat> {
+    if let Ok(sniffed) = image::guess_format(bytes) {
+        return vec![sniffed];
+    }
+    let mut candidates = Vec::new();
+    for format in [ext_format, mime_format].into_iter().flatten() {
+        if !candidates.contains(&format) {
+            candidates.push(format);
+        }
+    }
+    return candidates;
+}
+
+/// Like [`decode`], but tries every one of [`format_candidates`] in turn
+/// until one works.
+pub fn decode_with_hints(
+    bytes: &[u8],
+    mime_format: Option<image::ImageFormat>,
+    ext_format: Option<image::ImageFormat>,
+    max_pixels: u64,
+    opts: &ConvertOptions
+) -> Result<DynamicImage, ConvertError> {
+    // no decoding at all if it's already too late
+    opts.check_deadline()?;
+    let candidates = format_candidates(bytes, mime_format, ext_format);
+    if candidates.is_empty() {
+        // could still be an SVG, or else it's not an image
+        return decode(bytes, None, max_pixels, opts);
+    }
+    let mut last_err = ConvertError::NotAnImage;
+    for format in candidates {
+        match decode(bytes, Some(format), max_pixels, opts) {
+            Ok(img) => return Ok(img),
+            // these would be the same in any format
+            Err(err @ (ConvertError::TooLarge | ConvertError::NoSuchFrame(_))) => return Err(err),
+            Err(err) => last_err = err,
+        }
+    }
+    return Err(last_err);
+}
+
+/// JPEG's end-of-image marker. Truncated files are missing it, and with it put
+/// back the decoder fills in the scan data that never arrived instead of
+/// giving up on the whole thing.
+const JPEG_EOI: [u8; 2] = [0xFF, 0xD9];
+
+/// Decodes a JPEG that got cut off partway, keeping whatever did arrive (the
+/// rest comes out gray). Only worth trying once [`decode_with_hints`] failed.
+pub fn decode_truncated_jpeg(
+    bytes: &[u8],
+    max_pixels: u64,
+    opts: &ConvertOptions
+) -> Result<DynamicImage, ConvertError> {
+    if image::guess_format(bytes).ok() != Some(image::ImageFormat::Jpeg) || bytes.ends_with(&JPEG_EOI) {
+        return Err(ConvertError::Decode);
+    }
+    let mut patched = bytes.to_vec();
+    patched.extend_from_slice(&JPEG_EOI);
+    return decode(&patched, Some(image::ImageFormat::Jpeg), max_pixels, opts);
+}
+
+/// Below this luminance variance an image counts as a single color; that's
+/// a standard deviation of 2 out of 255.
+const FLAT_VARIANCE: f64 = 4.0;
+
+/// Whether the image is (close enough to) a single flat color, which would
+/// come out as nothing but identical cells.
+pub fn is_flat(gray_img: &image::GrayImage) -> bool {
+    let count = gray_img.pixels().len() as f64;
+    if count == 0.0 {
+        return true;
+    }
+    let mean = gray_img.pixels().map(|pix| pix.0[0] as f64).sum::<f64>() / count;
+    let variance = gray_img.pixels()
+        .map(|pix| (pix.0[0] as f64 - mean).powi(2))
+        .sum::<f64>() / count;
+    return variance < FLAT_VARIANCE;
+}
+
+/// Checks the options for values that make no sense together.
+pub fn validate(opts: &ConvertOptions) -> Result<(), ConvertError> {
+    if opts.edge_low.is_some_and(|low| low > opts.edge_high) {
+        return Err(ConvertError::BadOption("edge_low can't be above edge_high".to_owned()));
+    }
+    if opts.mode == Mode::Rgbsplit && opts.charset != Charset::Braille {
+        return Err(ConvertError::BadOption("rgbsplit only works with braille".to_owned()));
+    }
+    if opts.mode == Mode::Rgbsplit && (opts.dilate > 0 || opts.erode > 0) {
+        return Err(ConvertError::BadOption("dilate and erode don't work with rgbsplit".to_owned()));
+    }
+    if opts.format == Some(Format::Multi)
+        && (opts.charset != Charset::Braille || opts.mode == Mode::Rgbsplit || opts.rtl || opts.preview_rows.is_some()) {
+        return Err(ConvertError::BadOption(
+            "multi is plain braille and ascii, so no charset, rgbsplit, rtl or preview_rows".to_owned()
+        ));
+    }
+    if opts.frames == Frames::All && opts.frame.is_some() {
+        return Err(ConvertError::BadOption("frames=all and frame or page don't go together".to_owned()));
+    }
+    if opts.frames == Frames::All && opts.format.is_some_and(|format| format != Format::Text) {
+        return Err(ConvertError::BadOption("frames=all only comes as text".to_owned()));
+    }
+    if opts.preview_rows == Some(0) {
+        return Err(ConvertError::BadOption("preview_rows has to be at least 1".to_owned()));
+    }
+    if opts.preview_rows.is_some() && (opts.charset != Charset::Braille || opts.mode == Mode::Rgbsplit) {
+        return Err(ConvertError::BadOption("preview_rows only works on plain braille".to_owned()));
+    }
+    if opts.summary && opts.format.is_some_and(|format| format != Format::Text) {
+        return Err(ConvertError::BadOption("summary only goes on text".to_owned()));
+    }
+    if opts.dither != Dither::default() && opts.mode != Mode::Dither {
+        return Err(ConvertError::BadOption("dither only works with mode=dither".to_owned()));
+    }
+    if let Some(density) = opts.target_density {
+        if !(0.0..=1.0).contains(&density) {
+            return Err(ConvertError::BadOption("target_density has to be between 0 and 1".to_owned()));
+        }
+        if opts.mode != Mode::Dither {
+            return Err(ConvertError::BadOption("target_density only works with dither".to_owned()));
+        }
+    }
+    if let Some(factor) = opts.edge_blend {
+        if !(0.0..=1.0).contains(&factor) {
+            return Err(ConvertError::BadOption("edge_blend has to be between 0 and 1".to_owned()));
+        }
+        if opts.mode != Mode::Dither {
+            return Err(ConvertError::BadOption("edge_blend only works with dither".to_owned()));
+        }
+    }
+    if opts.alpha_dither && opts.mode != Mode::Dither {
+        return Err(ConvertError::BadOption("alpha_dither only works with dither".to_owned()));
+    }
+    if opts.mode == Mode::Rgbsplit && opts.stencil {
+        return Err(ConvertError::BadOption("stencil doesn't work with rgbsplit".to_owned()));
+    }
+    if opts.cols.is_some() != opts.rows.is_some() {
+        return Err(ConvertError::BadOption("cols and rows go together".to_owned()));
+    }
+    if [opts.cols, opts.rows].iter().flatten().any(|n| !(1..=100).contains(n)) {
+        return Err(ConvertError::BadOption("cols and rows have to be between 1 and 100".to_owned()));
+    }
+    if opts.cols.is_some() && opts.center_weight != 1.0 {
+        return Err(ConvertError::BadOption("center_weight doesn't work with cols and rows".to_owned()));
+    }
+    if opts.src_width.is_some() != opts.src_height.is_some() {
+        return Err(ConvertError::BadOption("src_width and src_height go together".to_owned()));
+    }
+    if opts.src_width.is_some_and(|width| !(1..=MAX_SRC_WIDTH).contains(&width))
+        || opts.src_height.is_some_and(|height| !(1..=MAX_SRC_HEIGHT).contains(&height)) {
+        return Err(ConvertError::BadOption(format!(
+            "src_width goes from 1 to {} and src_height from 1 to {}", MAX_SRC_WIDTH, MAX_SRC_HEIGHT
+        )));
+    }
+    if opts.src_width.is_some() && (opts.cols.is_some() || opts.center_weight != 1.0) {
+        return Err(ConvertError::BadOption("src_width and src_height don't work with cols, rows or center_weight".to_owned()));
+    }
+    if !(0.25..=4.0).contains(&opts.aspect) {
+        return Err(ConvertError::BadOption("aspect has to be between 0.25 and 4".to_owned()));
+    }
+    if opts.rtl && (opts.charset != Charset::Braille || opts.mode == Mode::Rgbsplit) {
+        return Err(ConvertError::BadOption("rtl only works with plain braille".to_owned()));
+    }
+    if opts.rtl_mirror && !opts.rtl {
+        return Err(ConvertError::BadOption("rtl_mirror goes with rtl".to_owned()));
+    }
+    if opts.mode == Mode::Rgbsplit && opts.terminal_bg.is_some() {
+        return Err(ConvertError::BadOption("terminal_bg doesn't work with rgbsplit".to_owned()));
+    }
+    if opts.mode == Mode::Rgbsplit && opts.sample != Sample::Avg {
+        return Err(ConvertError::BadOption("sample doesn't work with rgbsplit".to_owned()));
+    }
+    if opts.denoise > 3 {
+        return Err(ConvertError::BadOption("denoise goes up to 3".to_owned()));
+    }
+    if opts.dilate > 4 || opts.erode > 4 {
+        return Err(ConvertError::BadOption("dilate and erode go up to 4".to_owned()));
+    }
+    if opts.top.iter().chain(opts.bottom.iter()).any(|caption| caption.chars().count() > 200) {
+        return Err(ConvertError::BadOption("captions go up to 200 chars".to_owned()));
+    }
+    if !(1..=8).contains(&opts.cell_repeat) {
+        return Err(ConvertError::BadOption("cell_repeat has to be between 1 and 8".to_owned()));
+    }
+    if opts.ico_size.is_some_and(|size| !(1..=256).contains(&size)) {
+        return Err(ConvertError::BadOption("ico_size has to be between 1 and 256".to_owned()));
+    }
+    if opts.wrap == Some(0) {
+        return Err(ConvertError::BadOption("wrap has to be at least 1".to_owned()));
+    }
+    if opts.collapse_blanks == Some(0) {
+        return Err(ConvertError::BadOption("collapse_blanks has to be at least 1".to_owned()));
+    }
+    if !(0.0..=25.0).contains(&opts.autocontrast_clip) {
+        return Err(ConvertError::BadOption("autocontrast_clip has to be between 0 and 25".to_owned()));
+    }
+    if !(1.0..=4.0).contains(&opts.center_weight) {
+        return Err(ConvertError::BadOption("center_weight has to be between 1 and 4".to_owned()));
+    }
+    if !opts.rotate.is_finite() {
+        return Err(ConvertError::BadOption("rotate has to be a number of degrees".to_owned()));
+    }
+    if opts.rotate_fill.as_deref().is_some_and(|fill| render::parse_hex_color(fill).is_none()) {
+        return Err(ConvertError::BadOption("colors go like ff8800".to_owned()));
+    }
+    if opts.format == Some(Format::RenderPng) {
+        if opts.encoding != Encoding::Utf8 {
+            return Err(ConvertError::BadOption("render.png can't be escaped".to_owned()));
+        }
+        if !(4.0..=128.0).contains(&opts.font_size) {
+            return Err(ConvertError::BadOption("font_size has to be between 4 and 128".to_owned()));
+        }
+        if render::parse_hex_color(&opts.render_fg).is_none()
+            || render::parse_hex_color(&opts.render_bg).is_none() {
+            return Err(ConvertError::BadOption("colors go like ff8800".to_owned()));
+        }
+    }
+    return Ok(());
+}
+
+/// The most text [`text_to_braille`] will draw.
+pub const MAX_TEXT_CHARS: usize = 100;
+
+/// Draws text in braille in the caption font, no image needed. Lines of the
+/// text stay lines.
+pub fn text_to_braille(text: &str, font: TextFont, blank_mode: BlankMode) -> Result<Vec<String>, ConvertError> {
+    if text.trim().is_empty() {
+        return Err(ConvertError::BadOption("nothing to draw, the text is empty".to_owned()));
+    }
+    if text.chars().count() > MAX_TEXT_CHARS {
+        return Err(ConvertError::BadOption(format!("text goes up to {} chars", MAX_TEXT_CHARS)));
+    }
+    let cell_height = Charset::Braille.mapper().cell_size().1;
+    let dots = caption::text_dots(text, font.scale(), cell_height);
+    return Ok(braille::BrailleGrid::from_dots(&dots).to_rows(blank_mode.blank_char()));
+}
+
+/// Converts a decoded image to text, before the output encoding is applied.
+pub fn convert(img: DynamicImage, opts: &ConvertOptions) -> Result<String, ConvertError> {
+    return convert_with_progress(img, opts, None);
+}
+
+/// Like [`convert`], but calls `progress` with how far along it is, from 0 to
+/// 1, as each stage finishes.
+pub fn convert_with_progress(
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>
+) -> Result<String, ConvertError> {
+    let mut text = String::new();
+    write_converted(&mut text, img, opts, progress, Encoding::Utf8)?;
+    return Ok(text);
+}
+
+/// Like [`convert`], along with the options as they were applied, with
+/// `dither=auto` settled and `target_density` or `stencil_threshold=auto`
+/// turned into a `stencil_threshold`.
+pub fn convert_with_applied(img: DynamicImage, opts: &ConvertOptions) -> Result<(String, ConvertOptions), ConvertError> {
+    let mut text = String::new();
+    let applied = write_converted(&mut text, img, opts, None, Encoding::Utf8)?;
+    return Ok((text, applied));
+}
+
+/// Converts straight into `out`, formatted and encoded, so the whole output
+/// never has to sit in a string of its own; for when it's going right out,
+/// to a response body or stdout.
+pub fn write_output(
+    out: &mut dyn std::fmt::Write,
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>
+) -> Result<(), ConvertError> {
+    write_converted(out, img, opts, progress, opts.encoding)?;
+    return Ok(());
+}
+
+/// [`write_output`] for each of `imgs`, one frame after another with a line
+/// between them, for `frames=all`.
+pub fn write_frames(
+    out: &mut dyn std::fmt::Write,
+    imgs: Vec<DynamicImage>,
+    opts: &ConvertOptions
+) -> Result<(), ConvertError> {
+    for (i, img) in imgs.into_iter().enumerate() {
+        if i > 0 {
+            out.write_char('\n').map_err(|_| ConvertError::Write)?;
+        }
+        write_output(out, img, opts, None)?;
+    }
+    return Ok(());
+}
+
+// converts and writes in the output format, with `encoding`; gives back the
+// options as applied
+fn write_converted(
+    out: &mut dyn std::fmt::Write,
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>,
+    encoding: Encoding
+) -> Result<ConvertOptions, ConvertError> {
+    let format = opts.format.unwrap_or_default();
+    let (written, applied) = if format == Format::Multi {
+        let (outputs, applied) = convert_charsets(img, opts, progress, &[Charset::Braille, Charset::Ascii])?;
+        let (braille, ascii) = (&outputs[0], &outputs[1]);
+        let written = match encoding {
+            Encoding::Utf8 => output::write_multi(out, braille, ascii),
+            Encoding::Escaped => output::write_multi(&mut output::Escaping(&mut *out), braille, ascii),
+        };
+        (written, applied)
+    } else {
+        let (lines, applied) = output_rows(img, opts, progress)?;
+        (output::write_encoded(out, &lines, format, encoding), applied)
+    };
+    written.map_err(|_| ConvertError::Write)?;
+    if let Some(progress) = progress {
+        progress(1.0);
+    }
+    return Ok(applied);
+}
+
+// the rows, with the summary if there's one, and the options as applied
+fn output_rows(
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>
+) -> Result<(Vec<String>, ConvertOptions), ConvertError> {
+    let (mut lines, applied) = convert_rows_applied(img, opts, progress)?;
+    // never on the formats meant for machines, even if they were only picked
+    // after the options were checked
+    if opts.summary && opts.format.unwrap_or_default() == Format::Text {
+        lines.push(summary_line(&lines, &applied));
+    }
+    return Ok((lines, applied));
+}
+
+/// The `summary=true` line, saying what the rows came out as and the
+/// settings that were actually applied, for `applied` being the options with
+/// anything left to work out worked out.
+pub fn summary_line(lines: &[String], applied: &ConvertOptions) -> String {
+    let name = |val: &dyn std::fmt::Debug| format!("{:?}", val).to_lowercase();
+    let mut line = format!("# {}x{} {}", rows::max_width(lines), lines.len(), name(&applied.charset));
+    if let Some(source) = &applied.source {
+        let source = source.split_once("://").map(|(_, rest)| rest).unwrap_or(source);
+        line.push_str(&format!(" from {}", source));
+    }
+    let mut settings = vec![format!("mode={}", name(&applied.mode))];
+    match applied.mode {
+        Mode::Dither => {
+            settings.push(format!("dither={}", name(&applied.dither)));
+            if let Some(factor) = applied.edge_blend {
+                settings.push(format!("edge_blend={}", factor));
+            }
+        },
+        Mode::Edges => {
+            if let Some(low) = applied.edge_low {
+                settings.push(format!("edge_low={}", low));
+            }
+            settings.push(format!("edge_high={}", applied.edge_high));
+        },
+        Mode::Rgbsplit => settings.push(format!("color={}", if applied.colored() { "always" } else { "never" })),
+    }
+    if applied.stencil {
+        settings.push(format!("threshold={}", applied.stencil_threshold));
+    }
+    if applied.invert == Invert::On {
+        settings.push("inverted".to_owned());
+    }
+    line.push_str(&format!(" ({})", settings.join(", ")));
+    return line;
+}
+
+/// Puts the rows together in the output format.
+pub fn format_rows(lines: &[String], format: Format) -> String {
+    return output::collect(|out| output::write_rows(out, lines, format));
+}
+
+// the image resized, by whichever of the ways to size it the options pick;
+// `scale` times as many dots across for anything but a plain resize
+fn resize_for(img: DynamicImage, opts: &ConvertOptions, scale: u32) -> Result<DynamicImage, ConvertError> {
+    if let (Some(cols), Some(rows)) = (opts.cols, opts.rows) {
+        let (cell_width, cell_height) = opts.charset.mapper().cell_size();
+        return preprocess::fit(img, cols * cell_width * scale, rows * cell_height * scale, opts.fit, opts.deadline);
+    }
+    if let (Some(width), Some(height)) = (opts.src_width, opts.src_height) {
+        return resample::resize_exact(&img, width * scale, height * scale, opts.deadline);
+    }
+    if opts.center_weight == 1.0 {
+        // the same as resize_img, checking the deadline as it goes
+        let (width, height) = target_size(img.width(), img.height(), opts.aspect());
+        if opts.aspect() == 1.0 {
+            return resample::resize(&img, width, height, opts.deadline);
+        }
+        return resample::resize_exact(&img, width, height, opts.deadline);
+    }
+    return preprocess::center_weight(img, opts.center_weight, opts.aspect(), scale, opts.deadline);
+}
+
+/// Everything [`convert_with_progress`] does except putting the rows together
+/// in the output format.
+pub fn convert_to_rows(
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>
+) -> Result<Vec<String>, ConvertError> {
+    return convert_rows_applied(img, opts, progress).map(|(lines, _)| lines);
+}
+
+// the rows, and the options as they were applied, with dither=auto settled
+fn convert_rows_applied(
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>
+) -> Result<(Vec<String>, ConvertOptions), ConvertError> {
+    let (mut outputs, applied) = convert_charsets(img, opts, progress, &[opts.charset])?;
+    return Ok((outputs.remove(0), applied));
+}
+
+/// The braille and ascii rows `format=multi` puts together, mapped from the
+/// very same dots, so the image is only decoded and resized the once.
+pub fn convert_to_multi(
+    img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>
+) -> Result<(Vec<String>, Vec<String>), ConvertError> {
+    let (mut outputs, _) = convert_charsets(img, opts, progress, &[Charset::Braille, Charset::Ascii])?;
+    let ascii = outputs.pop().unwrap();
+    return Ok((outputs.pop().unwrap(), ascii));
+}
+
+// the whole pipeline, with a set of rows for each charset in `charsets`
+fn convert_charsets(
+    mut img: DynamicImage,
+    opts: &ConvertOptions,
+    progress: Option<&dyn Fn(f32)>,
+    charsets: &[Charset]
+) -> Result<(Vec<Vec<String>>, ConvertOptions), ConvertError> {
+    // every report is a checkpoint for the deadline too
+    let report = |done: f32| {
+        if let Some(progress) = progress {
+            progress(done);
+        }
+        return opts.check_deadline();
+    };
+    validate(opts)?;
+    report(0.0)?;
+    if opts.rotate != 0.0 {
+        let fill = match opts.rotate_fill.as_deref().and_then(render::parse_hex_color) {
+            Some([r, g, b]) => image::Rgba([r, g, b, 255]),
+            None => image::Rgba([0, 0, 0, 0]),
+        };
+        img = preprocess::rotate(img, opts.rotate, fill);
+    }
+    img = preprocess::flip(img, opts.flip);
+    if opts.trim_bg == TrimBg::Auto {
+        img = preprocess::trim_background(img, opts.trim_tolerance, opts.trim_crop);
+    }
+    let mut applied = opts.clone();
+    applied.invert = opts.invert.resolve(&img);
+    // alpha is left alone, so transparent parts stay blank, as if the image
+    // had been laid over white before inverting; inverting for both the
+    // option and a light terminal cancels out
+    if (applied.invert == Invert::On) != (opts.resolved_terminal_bg() == Some(TerminalBg::Light)) {
+        img.invert();
+    }
+    report(0.2)?;
+    // the other samplings need the pixels behind every dot, so a bigger copy
+    let source = (opts.sample != Sample::Avg).then(|| img.clone());
+    // the resize is what takes long on big images
+    let mut resized = resize_for(img, opts, 1)?;
+    // a checkpoint before the denoise, which takes a while too
+    opts.check_deadline()?;
+    if opts.denoise > 0 {
+        resized = preprocess::median_filter(&resized, opts.denoise);
+    }
+    report(0.6)?;
+    let mut gray_img = match source {
+        Some(source) => {
+            let block = preprocess::SAMPLE_BLOCK;
+            let detail = if opts.cols.is_none() && opts.src_width.is_none() && opts.center_weight == 1.0 {
+                // a plain resize rounds the sides its own way, so this goes
+                // by what it came to
+                let (width, height) = (resized.width() * block, resized.height() * block);
+                resample::resize_exact(&source, width, height, opts.deadline)?
+            } else {
+                resize_for(source, opts, block)?
+            };
+            opts.check_deadline()?;
+            let sampled = preprocess::sample_blocks(&to_gray(&detail), block, opts.sample);
+            if opts.denoise > 0 {
+                preprocess::median_filter(&DynamicImage::ImageLuma8(sampled), opts.denoise).to_luma8()
+            } else {
+                sampled
+            }
+        },
+        None => to_gray(&resized),
+    };
+    if opts.warn_flat && is_flat(&gray_img) {
+        return Err(ConvertError::FlatImage);
+    }
+    if opts.autocontrast {
+        gray_img = preprocess::auto_contrast(gray_img, opts.autocontrast_clip);
+    }
+    if let Some(density) = opts.target_density {
+        applied.stencil = true;
+        applied.stencil_threshold = preprocess::density_threshold(&gray_img, density);
+    } else if applied.stencil && opts.stencil_auto {
+        applied.stencil_threshold = preprocess::otsu_threshold(&gray_img);
+    }
+    if applied.stencil {
+        gray_img = preprocess::stencil(gray_img, applied.stencil_threshold);
+    }
+    let mapper = opts.charset.mapper();
+    // braille goes through a grid of dot patterns, only made into text at
+    // the end
+    let to_grid = |dots: &image::GrayImage| -> braille::BrailleGrid {
+        let grid = braille::BrailleGrid::from_dots(dots);
+        if opts.rtl {
+            return grid.reverse_cells(opts.rtl_mirror);
+        }
+        return grid;
+    };
+    let to_chars = |dots: &image::GrayImage, charset: Charset| -> Vec<String> {
+        if charset == Charset::Braille {
+            return to_grid(dots).to_rows(opts.blank_mode.blank_char());
+        }
+        return mapper::map_cells(dots, charset.mapper().as_ref());
+    };
+    let dots = if opts.mode == Mode::Rgbsplit {
+        None
+    } else {
+        let mut dots = match opts.mode {
+            Mode::Edges => edges::detect_edges(&gray_img, opts.edge_low, opts.edge_high),
+            _ => {
+                let edges = opts.edge_blend
+                    .filter(|&factor| factor > 0.0)
+                    .map(|factor| edges::detect_weighted_edges(&gray_img, opts.edge_low, opts.edge_high, factor));
+                let alpha = opts.alpha_dither.then(|| preprocess::alpha_channel(&resized));
+                let cols = resized.width().div_ceil(mapper.cell_size().0);
+                applied.dither = opts.dither.resolve(cols);
+                let mut dots = dither_as(gray_img, applied.dither, alpha.as_ref(), opts.deadline)?;
+                // a dot lit in either stays lit
+                if let Some(edges) = edges {
+                    for (dot, edge) in dots.pixels_mut().zip(edges.pixels()) {
+                        dot.0[0] = dot.0[0].max(edge.0[0]);
+                    }
+                }
+                dots
+            },
+        };
+        // eroding first, so both together clean up specks before thickening
+        if opts.erode > 0 {
+            dots = morph::erode(&dots, opts.erode);
+        }
+        if opts.dilate > 0 {
+            dots = morph::dilate(&dots, opts.dilate);
+        }
+        Some(dots)
+    };
+    let cell_height = mapper.cell_size().1;
+    let caption_dots = |text: &Option<String>| {
+        return text.as_deref()
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| caption::caption_dots(text, resized.width(), cell_height));
+    };
+    let (top, bottom) = (caption_dots(&opts.top), caption_dots(&opts.bottom));
+    // the same dots for every charset asked for, only mapped again
+    let mut outputs = Vec::with_capacity(charsets.len());
+    for &charset in charsets {
+        let lines = match &dots {
+            // captions and all in the one grid, so a preview cuts it as a
+            // whole
+            Some(dots) if charset == Charset::Braille => {
+                let mut grid = braille::BrailleGrid::new(0, 0);
+                for part in [top.as_ref(), Some(dots), bottom.as_ref()].into_iter().flatten() {
+                    grid.cells.append(&mut to_grid(part).cells);
+                }
+                if opts.trim_blank {
+                    grid = grid.trim_blank();
+                }
+                if let Some(preview_rows) = opts.preview_rows {
+                    grid = grid.preview(preview_rows);
+                }
+                if opts.border && opts.border_style == BorderStyle::Dots {
+                    grid = grid.add_border();
+                }
+                grid.to_rows(opts.blank_mode.blank_char())
+            },
+            _ => {
+                let mut lines = match &dots {
+                    Some(dots) => to_chars(dots, charset),
+                    None => color::rgb_split(&resized, opts.blank_mode.blank_char(), opts.colored()),
+                };
+                if let Some(top) = &top {
+                    let mut captioned = to_chars(top, charset);
+                    captioned.append(&mut lines);
+                    lines = captioned;
+                }
+                if let Some(bottom) = &bottom {
+                    lines.append(&mut to_chars(bottom, charset));
+                }
+                lines
+            },
+        };
+        // dots already put a border on the braille grid
+        let dotted = dots.is_some() && charset == Charset::Braille && opts.border_style == BorderStyle::Dots;
+        let boxed = opts.border && !dotted;
+        outputs.push(finish_rows(lines, opts, boxed));
+    }
+    report(0.9)?;
+    return Ok((outputs, applied));
+}
+
+// everything done to the rows once they're chars
+fn finish_rows(mut lines: Vec<String>, opts: &ConvertOptions, boxed: bool) -> Vec<String> {
+    if let Some(min_run) = opts.collapse_blanks {
+        lines = rows::collapse_blank_rows(lines, min_run);
+    }
+    if opts.cell_repeat > 1 {
+        lines = rows::repeat_cells(lines, opts.cell_repeat);
+    }
+    if opts.col_gap {
+        lines = rows::add_col_gaps(lines);
+    }
+    if opts.layout == Layout::Vertical {
+        lines = rows::to_vertical(&lines);
+    }
+    if let Some(width) = opts.wrap {
+        lines = rows::wrap_columns(&lines, width);
+    }
+    if boxed {
+        lines = rows::add_border(&lines);
+    }
+    color::end_with_reset(&mut lines);
+    return lines;
+}
+
+/// The whole thing in one go: decode, convert and encode.
+pub fn convert_bytes(
+    bytes: &[u8],
+    format: Option<image::ImageFormat>,
+    opts: &ConvertOptions
+) -> Result<String, ConvertError> {
+    let img = decode(bytes, format, DEFAULT_MAX_PIXELS, opts)?;
+    return Ok(encode(convert(img, opts)?, opts.encoding));
+}
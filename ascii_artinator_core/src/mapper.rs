@@ -0,0 +1,71 @@
+//! The step that turns blocks of dots into characters, behind a trait so a
+//! new charset is one more implementation instead of another branch in the
+//! pipeline.
+
+use image::GrayImage;
+
+/// Maps one block of pixels to the char that stands for it.
+pub trait CellMapper {
+    /// Width and height, in pixels, of the block one char covers.
+    fn cell_size(&self) -> (u32, u32);
+    /// Picks the char for a block, given row by row. Pixels past the edge of
+    /// the image come in as 0.
+    fn map_cell(&self, block: &[u8]) -> char;
+}
+
+/// Runs a mapper over the whole image, one row of chars per row of blocks.
+pub fn map_cells(gray_img: &GrayImage, mapper: &dyn CellMapper) -> Vec<String> {
+    let (cell_w, cell_h) = mapper.cell_size();
+    let cols = gray_img.width().div_ceil(cell_w);
+    let rows = gray_img.height().div_ceil(cell_h);
+    // one buffer for every block, refilled each time
+    let mut block = vec![0u8; (cell_w * cell_h) as usize];
+    let mut lines = Vec::with_capacity(rows as usize);
+    for cy in 0..rows {
+        let mut line = String::with_capacity(cols as usize * 3);
+        for cx in 0..cols {
+            for (i, val) in block.iter_mut().enumerate() {
+                let x = cx * cell_w + i as u32 % cell_w;
+                let y = cy * cell_h + i as u32 / cell_w;
+                *val = gray_img.get_pixel_checked(x, y).map(|pix| pix.0[0]).unwrap_or(0);
+            }
+            line.push(mapper.map_cell(&block));
+        }
+        lines.push(line);
+    }
+    return lines;
+}
+
+/// Plain ASCII, from darkest to brightest.
+pub const DEFAULT_RAMP: &str = " .:-=+*#%@";
+
+/// Like aalib's output, from darkest to brightest.
+pub const AALIB_RAMP: &str = " .,:;irsXA253hMHGS#9B&@";
+
+/// Picks a char from a ramp by the average brightness of a braille-sized
+/// block; on dithered dots that average is the density.
+pub struct RampMapper {
+    ramp: Vec<char>,
+}
+
+impl RampMapper {
+    /// `ramp` goes from darkest to brightest, and can't be empty.
+    pub fn new(ramp: &str) -> Self {
+        let ramp: Vec<char> = ramp.chars().collect();
+        assert!(!ramp.is_empty(), "the ramp needs at least one char");
+        return Self { ramp };
+    }
+}
+
+impl CellMapper for RampMapper {
+    fn cell_size(&self) -> (u32, u32) {
+        return (2, 4);
+    }
+
+    fn map_cell(&self, block: &[u8]) -> char {
+        let sum: u32 = block.iter().map(|&val| val as u32).sum();
+        let average = sum / block.len().max(1) as u32;
+        let i = average as usize * self.ramp.len() / 256;
+        return self.ramp[i];
+    }
+}
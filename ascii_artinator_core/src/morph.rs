@@ -0,0 +1,40 @@
+//! Binary morphology on the dot bitmap, to thicken or thin out lines before
+//! they are mapped to characters.
+
+use image::GrayImage;
+
+/// Same threshold the character mappings use for a dot being on.
+fn is_set(pix: &image::Luma<u8>) -> bool {
+    return pix.0[0] > 80;
+}
+
+/// Turns a dot on if any dot within `radius` of it is on, or, with
+/// `want_all`, only if every one of them is. The image's edges don't count
+/// as off.
+fn morph(dots: &GrayImage, radius: u32, want_all: bool) -> GrayImage {
+    let (width, height) = dots.dimensions();
+    let mut out = GrayImage::new(width, height);
+    for (x, y, pix) in out.enumerate_pixels_mut() {
+        let xs = x.saturating_sub(radius)..=(x + radius).min(width - 1);
+        let ys = y.saturating_sub(radius)..=(y + radius).min(height - 1);
+        let mut neighbors = ys.flat_map(|ny| xs.clone().map(move |nx| (nx, ny)))
+            .map(|(nx, ny)| is_set(dots.get_pixel(nx, ny)));
+        let on = if want_all {
+            neighbors.all(|set| set)
+        } else {
+            neighbors.any(|set| set)
+        };
+        pix.0[0] = if on { 255 } else { 0 };
+    }
+    return out;
+}
+
+/// Grows every dot by `radius` in all directions.
+pub fn dilate(dots: &GrayImage, radius: u32) -> GrayImage {
+    return morph(dots, radius, false);
+}
+
+/// Shrinks every run of dots by `radius`; anything thinner disappears.
+pub fn erode(dots: &GrayImage, radius: u32) -> GrayImage {
+    return morph(dots, radius, true);
+}
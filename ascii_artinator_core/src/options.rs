@@ -0,0 +1,662 @@
+//! Everything that can be tweaked about a conversion.
+
+use std::collections::HashMap;
+
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::Deserialize;
+
+use crate::ConvertError;
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Escaped
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Rows separated by spaces, for chat.
+    #[default]
+    Text,
+    /// A small HTML page with the rows one under the other.
+    Html,
+    /// The rows drawn into a PNG with a font.
+    #[serde(rename = "render.png")]
+    RenderPng,
+    /// A Rust string literal, for pasting into code.
+    Rust,
+    /// A C string literal, likewise.
+    C,
+    /// One JSON object per row, for reading row by row as it streams in.
+    Jsonl,
+    /// A JSON object with the braille and an ASCII fallback of the same
+    /// dots, each as rows one per line.
+    Multi
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Charset {
+    #[default]
+    Braille,
+    Quadrant,
+    /// Upper and lower half blocks, a cell per two pixels down.
+    Halfblock,
+    /// Plain ASCII from a brightness ramp.
+    Ascii,
+    /// ASCII from a longer ramp, of the letters the classic aalib leaned on.
+    Aalib
+}
+
+impl Charset {
+    /// The mapper that turns dots into this charset.
+    pub fn mapper(&self) -> Box<dyn crate::mapper::CellMapper> {
+        return match self {
+            Charset::Braille => Box::new(crate::braille::BrailleMapper),
+            Charset::Quadrant => Box::new(crate::quadrant::QuadrantMapper),
+            Charset::Halfblock => Box::new(crate::halfblock::HalfblockMapper),
+            Charset::Ascii => Box::new(crate::mapper::RampMapper::new(crate::mapper::DEFAULT_RAMP)),
+            Charset::Aalib => Box::new(crate::mapper::RampMapper::new(crate::mapper::AALIB_RAMP)),
+        };
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Dither,
+    Edges,
+    Rgbsplit
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrimBg {
+    #[default]
+    Off,
+    Auto
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Flip {
+    #[default]
+    None,
+    /// Mirrored left to right.
+    H,
+    /// Upside down.
+    V,
+    Both
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    /// Box-drawing lines, around whatever the rows came out as.
+    #[default]
+    Box,
+    /// A line of raised dots, drawn into the braille grid itself; other
+    /// charsets get the box.
+    Dots
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Frames {
+    /// The one `frame` says, or the first.
+    #[default]
+    One,
+    /// Every frame of a GIF, or page of a TIFF, one after the other.
+    All
+}
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    #[default]
+    Grid,
+    /// One char per line, row after row.
+    Vertical
+}
+
+/// How big [`crate::text_to_braille`] draws the letters.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TextFont {
+    /// The caption font as is, two cells across a letter.
+    Small,
+    /// Twice that.
+    #[default]
+    Big
+}
+
+impl TextFont {
+    pub fn scale(&self) -> u32 {
+        return match self {
+            TextFont::Small => 1,
+            TextFont::Big => 2,
+        };
+    }
+}
+
+/// What blank braille cells are drawn as, for places that render U+2800
+/// oddly or collapse it.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BlankMode {
+    /// A single dot, since chat tends to eat blank braille.
+    #[default]
+    Dot,
+    /// Real blank braille, U+2800.
+    Braille,
+    Space,
+    /// A non-breaking space, for places that collapse plain ones.
+    Nbsp
+}
+
+impl BlankMode {
+    pub fn blank_char(&self) -> char {
+        return match self {
+            BlankMode::Dot => crate::braille::BRAILLE_CHARS[1],
+            BlankMode::Braille => crate::braille::BRAILLE_CHARS[0],
+            BlankMode::Space => ' ',
+            BlankMode::Nbsp => '\u{a0}',
+        };
+    }
+}
+
+/// Terminals with known cell shapes, for `terminal`.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Terminal {
+    /// Cells exactly twice as tall as wide, as chat mostly has them.
+    #[default]
+    None,
+    Iterm,
+    Kitty,
+    /// Windows Terminal.
+    Windows,
+    /// GNOME Terminal and the others built on VTE.
+    Vte
+}
+
+impl Terminal {
+    /// How much to stretch the image vertically so dots come out round with
+    /// the terminal's default font: 2 over its cell's height to width.
+    pub fn aspect(&self) -> f32 {
+        return match self {
+            Terminal::None => 1.0,
+            Terminal::Iterm => 0.9,
+            Terminal::Kitty => 0.95,
+            Terminal::Windows => 0.94,
+            Terminal::Vte => 0.96,
+        };
+    }
+}
+
+/// Whether colored modes actually color.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Always,
+    Never,
+    /// Only if the output goes to a terminal, which only the client can know.
+    Auto
+}
+
+/// The background the output is going to be read on. Dots show in the text
+/// color, so on a light background they have to go where the image is dark.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalBg {
+    Dark,
+    #[default]
+    Light
+}
+
+/// How the image goes into a fixed `cols` by `rows` size.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Fit {
+    /// Fits inside, with blank cells around whatever doesn't match.
+    #[default]
+    Contain,
+    /// Fills it, cutting off whatever sticks out.
+    Cover,
+    /// Squashes the image into it.
+    Stretch
+}
+
+/// How the block of the source behind each dot comes down to one brightness.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Sample {
+    /// Smooth, the way resizing does it.
+    #[default]
+    Avg,
+    /// The darkest pixel, so thin dark lines don't fade out.
+    Min,
+    /// The brightest pixel, same for thin light lines.
+    Max,
+    Median
+}
+
+/// How gray becomes dots in the dither mode.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Dither {
+    /// Sierra's error diffusion.
+    #[default]
+    Sierra,
+    /// Floyd–Steinberg's, finer on big outputs.
+    Floyd,
+    /// A Bayer pattern, steadier on small ones.
+    Ordered,
+    /// Ordered below [`AUTO_DITHER_COLS`] columns of output, Floyd–Steinberg
+    /// from there up.
+    Auto
+}
+
+/// Where `dither=auto` switches from ordered to Floyd–Steinberg.
+pub const AUTO_DITHER_COLS: u32 = 40;
+
+impl Dither {
+    /// What auto comes to for an output `cols` wide; the rest stay as they are.
+    pub fn resolve(&self, cols: u32) -> Dither {
+        return match self {
+            Dither::Auto if cols < AUTO_DITHER_COLS => Dither::Ordered,
+            Dither::Auto => Dither::Floyd,
+            other => *other,
+        };
+    }
+}
+
+/// Whether to swap bright and dark before anything gets mapped.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Invert {
+    #[default]
+    #[serde(alias = "false")]
+    Off,
+    #[serde(alias = "true")]
+    On,
+    /// On when the corners are brighter than the middle, i.e. a dark subject
+    /// on a bright background, so the subject is what gets the dots.
+    Auto
+}
+
+impl Invert {
+    /// What auto comes to for this image; on and off stay as they are.
+    pub fn resolve(&self, img: &image::DynamicImage) -> Invert {
+        return match self {
+            Invert::Auto if crate::preprocess::corners_brighter(img) => Invert::On,
+            Invert::Auto => Invert::Off,
+            other => *other,
+        };
+    }
+}
+
+/// A named bundle of options, set before the rest so any of them can still
+/// be changed.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// The look of the classic aalib: `charset=aalib`, and `autocontrast=true`
+    /// with `autocontrast_clip=2` for its punchy contrast.
+    Aalib
+}
+
+impl Profile {
+    pub fn apply(&self, opts: &mut ConvertOptions) {
+        match self {
+            Profile::Aalib => {
+                opts.charset = Charset::Aalib;
+                opts.autocontrast = true;
+                opts.autocontrast_clip = 2.0;
+            },
+        }
+    }
+}
+
+/// The options for a conversion. Built from the query string by
+/// [`parse_options`], with anything missing taking its default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvertOptions {
+    pub encoding: Encoding,
+    /// Left out, the server picks one from the Accept header.
+    pub format: Option<Format>,
+    pub charset: Charset,
+    pub mode: Mode,
+    /// Left out, colors unless NO_COLOR is set; see [`ConvertOptions::resolve_color`].
+    pub color: Option<Color>,
+    /// Only for braille; the other charsets already use spaces.
+    pub blank_mode: BlankMode,
+    /// Without it, edges are a plain threshold at `edge_high`; with it, weak
+    /// edges touching strong ones are kept too.
+    pub edge_low: Option<u8>,
+    pub edge_high: u8,
+    /// How much of the edges, from 0 to 1, to light on top of the usual
+    /// dots, for outlines along with the fill.
+    pub edge_blend: Option<f32>,
+    /// Light up only what's brighter than `stencil_threshold`, solidly, so the
+    /// subject comes out as a blank cut-out.
+    pub stencil: bool,
+    pub stencil_threshold: u8,
+    /// `stencil_threshold=auto`: Otsu's threshold for each image, instead of
+    /// the one number for all of them.
+    pub stencil_auto: bool,
+    /// About what fraction of the dots to light, from 0 to 1; a stencil with
+    /// its threshold picked to match, instead of `stencil_threshold`.
+    pub target_density: Option<f32>,
+    pub dither: Dither,
+    /// Keep the dithering away from fully transparent parts, for logos with
+    /// soft edges.
+    pub alpha_dither: bool,
+    /// How many dots to grow the dots by, to make thin lines thicker.
+    pub dilate: u32,
+    /// A fixed size in chars, instead of the usual width and a height to
+    /// match; both or neither.
+    pub cols: Option<u32>,
+    pub rows: Option<u32>,
+    /// Cuts braille off after this many rows, with a `…` row under them to
+    /// say so. Unlike `rows`, nothing gets shrunk to fit; it's the full size
+    /// conversion, cropped.
+    pub preview_rows: Option<usize>,
+    pub fit: Fit,
+    /// Exact dots to resize to, aspect ratio be damned; both or neither.
+    pub src_width: Option<u32>,
+    pub src_height: Option<u32>,
+    /// How many dots to shrink the dots by, done before `dilate`.
+    pub erode: u32,
+    /// Meme captions, drawn in a tiny font above and below the image.
+    pub top: Option<String>,
+    pub bottom: Option<String>,
+    pub layout: Layout,
+    /// Cells in reverse order within each row, for right-to-left displays;
+    /// with `rtl_mirror`, each cell's dots are mirrored as well.
+    pub rtl: bool,
+    pub rtl_mirror: bool,
+    /// A space between every two columns.
+    pub col_gap: bool,
+    /// A last line saying what the conversion came out as; text only.
+    pub summary: bool,
+    /// Where the image came from, for the summary; set by whoever fetched
+    /// it, not from the query.
+    pub source: Option<String>,
+    /// Past this, the conversion gives up at its next checkpoint; set by
+    /// whoever's waiting on it, not from the query.
+    pub deadline: Option<std::time::Instant>,
+    /// Most columns per line; wider output is cut into blocks stacked one
+    /// under the other, left block first.
+    pub wrap: Option<usize>,
+    pub border: bool,
+    pub border_style: BorderStyle,
+    /// Braille only: drops the blank rows and columns around the edges.
+    pub trim_blank: bool,
+    /// Runs of at least this many blank rows get collapsed into one.
+    pub collapse_blanks: Option<usize>,
+    /// How many times each char is repeated horizontally.
+    pub cell_repeat: usize,
+    /// Vertical stretch, over whatever `terminal` sets.
+    pub aspect: f32,
+    pub terminal: Terminal,
+    /// Left out, it's light, except for rgbsplit, which has its own colors
+    /// and takes no hint.
+    pub terminal_bg: Option<TerminalBg>,
+    pub invert: Invert,
+    /// How many times the center is magnified over the edges, for more detail
+    /// where the subject of e.g. a portrait is; 1 samples evenly.
+    pub center_weight: f32,
+    /// Radius of a median filter run over the resized image, to clean up
+    /// specks from noisy photos.
+    pub denoise: u32,
+    pub sample: Sample,
+    /// Degrees clockwise, applied before flipping.
+    pub rotate: f32,
+    /// Hex color for the corners rotation opens up; transparent without it.
+    pub rotate_fill: Option<String>,
+    pub flip: Flip,
+    pub trim_bg: TrimBg,
+    pub trim_tolerance: u8,
+    pub trim_crop: bool,
+    /// Which frame of an animated GIF, or page of a TIFF, to convert,
+    /// 0-indexed. `page` sets it too.
+    pub frame: Option<usize>,
+    /// `all` for every frame instead, as text.
+    pub frames: Frames,
+    /// Which of the sizes in an .ico to convert, the closest one across;
+    /// without it, the biggest. Anything but an .ico ignores it.
+    pub ico_size: Option<u32>,
+    /// Say so instead of converting when the image is a single color.
+    pub warn_flat: bool,
+    /// Stretch the luminance to the full range before dithering.
+    pub autocontrast: bool,
+    /// Percentage of outlier pixels ignored at each end when stretching.
+    pub autocontrast_clip: f32,
+    /// In pixels, for `render.png`.
+    pub font_size: f32,
+    /// Hex colors for `render.png`.
+    pub render_fg: String,
+    pub render_bg: String,
+}
+
+impl ConvertOptions {
+    /// The background the dots are laid out for, light when it's left out;
+    /// none for rgbsplit.
+    pub fn resolved_terminal_bg(&self) -> Option<TerminalBg> {
+        if self.mode == Mode::Rgbsplit {
+            return None;
+        }
+        return Some(self.terminal_bg.unwrap_or_default());
+    }
+
+    /// Settles `color` to always or never, for whoever knows whether
+    /// NO_COLOR is set and whether the output goes to a terminal. An explicit
+    /// always or never wins over both.
+    pub fn resolve_color(&mut self, no_color: bool, terminal: bool) {
+        let colored = match self.color {
+            Some(Color::Always) => true,
+            Some(Color::Never) => false,
+            Some(Color::Auto) => terminal && !no_color,
+            None => !no_color,
+        };
+        self.color = Some(if colored { Color::Always } else { Color::Never });
+    }
+
+    /// Whether to color. Left unresolved, that's a yes, since conversions
+    /// colored before there was a choice.
+    pub fn colored(&self) -> bool {
+        return self.color != Some(Color::Never);
+    }
+
+    /// A checkpoint: fails once the deadline has gone by.
+    pub fn check_deadline(&self) -> Result<(), crate::ConvertError> {
+        return check_deadline(self.deadline);
+    }
+}
+
+// the same checkpoint, for the steps that get the deadline on its own
+pub(crate) fn check_deadline(deadline: Option<std::time::Instant>) -> Result<(), ConvertError> {
+    if deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+        return Err(ConvertError::DeadlineExceeded);
+    }
+    return Ok(());
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        return Self {
+            encoding: Encoding::default(),
+            format: None,
+            charset: Charset::default(),
+            mode: Mode::default(),
+            blank_mode: BlankMode::default(),
+            color: None,
+            edge_low: None,
+            edge_high: 100,
+            edge_blend: None,
+            stencil: false,
+            stencil_threshold: 128,
+            stencil_auto: false,
+            dither: Dither::default(),
+            alpha_dither: false,
+            target_density: None,
+            dilate: 0,
+            erode: 0,
+            cols: None,
+            rows: None,
+            preview_rows: None,
+            src_width: None,
+            src_height: None,
+            fit: Fit::default(),
+            top: None,
+            bottom: None,
+            layout: Layout::default(),
+            rtl: false,
+            rtl_mirror: false,
+            col_gap: false,
+            wrap: None,
+            summary: false,
+            source: None,
+            deadline: None,
+            border: false,
+            border_style: BorderStyle::default(),
+            trim_blank: false,
+            collapse_blanks: None,
+            cell_repeat: 1,
+            aspect: 1.0,
+            terminal: Terminal::default(),
+            center_weight: 1.0,
+            denoise: 0,
+            sample: Sample::default(),
+            terminal_bg: None,
+            invert: Invert::default(),
+            rotate: 0.0,
+            rotate_fill: None,
+            flip: Flip::default(),
+            trim_bg: TrimBg::default(),
+            trim_tolerance: 24,
+            trim_crop: false,
+            frame: None,
+            frames: Frames::default(),
+            ico_size: None,
+            warn_flat: false,
+            autocontrast: false,
+            autocontrast_clip: 0.5,
+            font_size: 16.0,
+            render_fg: "ffffff".to_owned(),
+            render_bg: "000000".to_owned(),
+        };
+    }
+}
+
+impl ConvertOptions {
+    /// The vertical stretch actually applied, the terminal's times `aspect`.
+    pub fn aspect(&self) -> f32 {
+        return self.aspect * self.terminal.aspect();
+    }
+}
+
+fn bad_value(key: &str, val: &str) -> ConvertError {
+    return ConvertError::BadOption(format!("bad value for {}: {}", key, val));
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, val: &str) -> Result<T, ConvertError> {
+    return val.parse().map_err(|_| bad_value(key, val));
+}
+
+// the option enums go through serde, so their names live in the derives
+fn parse_enum<T: DeserializeOwned>(key: &str, val: &str) -> Result<T, ConvertError> {
+    let de: serde::de::value::StrDeserializer<serde::de::value::Error> = val.into_deserializer();
+    return T::deserialize(de).map_err(|_| bad_value(key, val));
+}
+
+/// Builds the options from query parameters on top of `defaults`, rejecting
+/// unknown ones and bad values by name, then checks that they make sense
+/// together.
+pub fn parse_options(
+    query: &HashMap<String, String>,
+    defaults: &ConvertOptions
+) -> Result<ConvertOptions, ConvertError> {
+    let mut opts = defaults.clone();
+    // the same option by two names, so which one won would be down to the
+    // order
+    if query.contains_key("frame") && query.contains_key("page") {
+        return Err(ConvertError::BadOption("frame and page are the same thing, so only one of them".to_owned()));
+    }
+    // first, as the query comes in no particular order
+    if let Some(profile) = query.get("profile") {
+        parse_enum::<Profile>("profile", profile)?.apply(&mut opts);
+    }
+    for (key, val) in query {
+        let (key, val) = (key.as_str(), val.as_str());
+        match key {
+            "profile" => {},
+            "encoding" => opts.encoding = parse_enum(key, val)?,
+            "format" => opts.format = Some(parse_enum(key, val)?),
+            "charset" => opts.charset = parse_enum(key, val)?,
+            "mode" => opts.mode = parse_enum(key, val)?,
+            "blank_mode" => opts.blank_mode = parse_enum(key, val)?,
+            "color" => opts.color = Some(parse_enum(key, val)?),
+            "edge_low" => opts.edge_low = Some(parse_value(key, val)?),
+            "edge_high" => opts.edge_high = parse_value(key, val)?,
+            "edge_blend" => opts.edge_blend = Some(parse_value(key, val)?),
+            "stencil" => opts.stencil = parse_value(key, val)?,
+            "stencil_threshold" if val == "auto" => opts.stencil_auto = true,
+            "stencil_threshold" => opts.stencil_threshold = parse_value(key, val)?,
+            "dither" => opts.dither = parse_enum(key, val)?,
+            "alpha_dither" => opts.alpha_dither = parse_value(key, val)?,
+            "target_density" => opts.target_density = Some(parse_value(key, val)?),
+            "dilate" => opts.dilate = parse_value(key, val)?,
+            "erode" => opts.erode = parse_value(key, val)?,
+            "cols" => opts.cols = Some(parse_value(key, val)?),
+            "src_width" => opts.src_width = Some(parse_value(key, val)?),
+            "src_height" => opts.src_height = Some(parse_value(key, val)?),
+            "rows" => opts.rows = Some(parse_value(key, val)?),
+            "preview_rows" => opts.preview_rows = Some(parse_value(key, val)?),
+            "fit" => opts.fit = parse_enum(key, val)?,
+            "top" => opts.top = Some(val.to_owned()),
+            "bottom" => opts.bottom = Some(val.to_owned()),
+            "layout" => opts.layout = parse_enum(key, val)?,
+            "rtl" => opts.rtl = parse_value(key, val)?,
+            "rtl_mirror" => opts.rtl_mirror = parse_value(key, val)?,
+            "col_gap" => opts.col_gap = parse_value(key, val)?,
+            "wrap" => opts.wrap = Some(parse_value(key, val)?),
+            "summary" => opts.summary = parse_value(key, val)?,
+            "border" => opts.border = parse_value(key, val)?,
+            "border_style" => opts.border_style = parse_enum(key, val)?,
+            "trim_blank" => opts.trim_blank = parse_value(key, val)?,
+            "collapse_blanks" => opts.collapse_blanks = Some(parse_value(key, val)?),
+            "cell_repeat" => opts.cell_repeat = parse_value(key, val)?,
+            "aspect" => opts.aspect = parse_value(key, val)?,
+            "terminal" => opts.terminal = parse_enum(key, val)?,
+            "center_weight" => opts.center_weight = parse_value(key, val)?,
+            "denoise" => opts.denoise = parse_value(key, val)?,
+            "sample" => opts.sample = parse_enum(key, val)?,
+            "terminal_bg" => opts.terminal_bg = Some(parse_enum(key, val)?),
+            "invert" => opts.invert = parse_enum(key, val)?,
+            "rotate" => opts.rotate = parse_value(key, val)?,
+            "rotate_fill" => opts.rotate_fill = Some(val.to_owned()),
+            "flip" => opts.flip = parse_enum(key, val)?,
+            "trim_bg" => opts.trim_bg = parse_enum(key, val)?,
+            "trim_tolerance" => opts.trim_tolerance = parse_value(key, val)?,
+            "trim_crop" => opts.trim_crop = parse_value(key, val)?,
+            "frame" | "page" => opts.frame = Some(parse_value(key, val)?),
+            "frames" => opts.frames = parse_enum(key, val)?,
+            "ico_size" => opts.ico_size = Some(parse_value(key, val)?),
+            "warn_flat" => opts.warn_flat = parse_value(key, val)?,
+            "autocontrast" => opts.autocontrast = parse_value(key, val)?,
+            "autocontrast_clip" => opts.autocontrast_clip = parse_value(key, val)?,
+            "font_size" => opts.font_size = parse_value(key, val)?,
+            "render_fg" => opts.render_fg = val.to_owned(),
+            "render_bg" => opts.render_bg = val.to_owned(),
+            _ => return Err(ConvertError::BadOption(format!("unknown option: {}", key))),
+        }
+    }
+    crate::validate(&opts)?;
+    return Ok(opts);
+}
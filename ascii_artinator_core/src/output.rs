@@ -0,0 +1,218 @@
+//! Writing the finished rows out in each format, into any [`fmt::Write`]
+//! sink, so a big conversion can go straight into a response body or stdout
+//! instead of being built up as one string and copied over. The `to_*`
+//! functions around the crate are these, collected into a string.
+
+use std::fmt;
+
+use crate::{Encoding, Format};
+
+/// Runs a writer into a fresh string, which can't fail.
+pub fn collect(write: impl FnOnce(&mut String) -> fmt::Result) -> String {
+    let mut out = String::new();
+    write(&mut out).expect("writing to a String can't fail");
+    return out;
+}
+
+/// Writes the rows in the output format.
+pub fn write_rows(out: &mut dyn fmt::Write, lines: &[String], format: Format) -> fmt::Result {
+    return match format {
+        Format::Text => write_joined(out, lines),
+        Format::Html => write_html(out, lines),
+        // render_png wants the rows one per line
+        Format::RenderPng => write_lines(out, lines),
+        Format::Rust => write_rust_literal(out, lines),
+        Format::C => write_c_literal(out, lines),
+        Format::Jsonl => write_jsonl(out, lines),
+        // it wants braille and ascii rows both, see write_multi; handed one
+        // set, that set goes in both
+        Format::Multi => write_multi(out, lines, lines),
+    };
+}
+
+/// Same as [`write_rows`], with the output encoding applied on the way out.
+pub fn write_encoded(
+    out: &mut dyn fmt::Write,
+    lines: &[String],
+    format: Format,
+    encoding: Encoding
+) -> fmt::Result {
+    return match encoding {
+        Encoding::Utf8 => write_rows(out, lines, format),
+        Encoding::Escaped => write_rows(&mut Escaping(out), lines, format),
+    };
+}
+
+/// A sink that turns every non-ascii char into `\uXXXX` escapes before
+/// passing it on, like [`crate::escape_unicode`].
+pub struct Escaping<W: fmt::Write>(pub W);
+
+impl<W: fmt::Write> fmt::Write for Escaping<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        return write_escaped(&mut self.0, s, unicode_escape);
+    }
+}
+
+/// Lets an [`std::io::Write`] take the output; the io error itself is kept
+/// in `error`, since [`fmt::Error`] can't carry it.
+pub struct IoSink<W: std::io::Write> {
+    pub inner: W,
+    pub error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> IoSink<W> {
+    pub fn new(inner: W) -> Self {
+        return Self { inner, error: None };
+    }
+}
+
+impl<W: std::io::Write> fmt::Write for IoSink<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        return self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            return fmt::Error;
+        });
+    }
+}
+
+// writes `s` with every char `escape` has an escape for swapped for it
+pub(crate) fn write_escaped(
+    out: &mut (impl fmt::Write + ?Sized),
+    s: &str,
+    escape: impl Fn(char) -> Option<String>
+) -> fmt::Result {
+    for c in s.chars() {
+        match escape(c) {
+            Some(seq) => out.write_str(&seq)?,
+            None => out.write_char(c)?,
+        }
+    }
+    return Ok(());
+}
+
+// every non-ascii char (i.e. the braille) as \uXXXX, surrogate pairs for
+// anything past the BMP
+pub(crate) fn unicode_escape(c: char) -> Option<String> {
+    if c.is_ascii() {
+        return None;
+    }
+    let mut buf = [0u16; 2];
+    return Some(c.encode_utf16(&mut buf).iter().map(|unit| format!("\\u{:04x}", unit)).collect());
+}
+
+/// See [`crate::rows::join_rows`].
+pub fn write_joined(out: &mut dyn fmt::Write, rows: &[String]) -> fmt::Result {
+    for row in rows {
+        out.write_char(' ')?;
+        out.write_str(row)?;
+    }
+    return Ok(());
+}
+
+fn write_lines(out: &mut dyn fmt::Write, rows: &[String]) -> fmt::Result {
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            out.write_char('\n')?;
+        }
+        out.write_str(row)?;
+    }
+    return Ok(());
+}
+
+/// See [`crate::rows::to_html`].
+pub fn write_html(out: &mut dyn fmt::Write, rows: &[String]) -> fmt::Result {
+    out.write_str(concat!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n",
+        "<body>\n<pre style=\"line-height: 1;\">\n"
+    ))?;
+    for row in rows {
+        write_escaped(out, row, |c| {
+            return match c {
+                '<' => Some("&lt;".to_owned()),
+                '>' => Some("&gt;".to_owned()),
+                '&' => Some("&amp;".to_owned()),
+                _ => None,
+            };
+        })?;
+        out.write_char('\n')?;
+    }
+    return out.write_str("</pre>\n</body>\n</html>\n");
+}
+
+// a literal's body, the rows with `\n` between them, through `escape`
+fn write_literal(
+    out: &mut dyn fmt::Write,
+    rows: &[String],
+    escape: impl Fn(char) -> Option<String>
+) -> fmt::Result {
+    out.write_char('"')?;
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            out.write_str("\\n")?;
+        }
+        write_escaped(out, row, &escape)?;
+    }
+    return out.write_char('"');
+}
+
+/// See [`crate::to_rust_literal`].
+pub fn write_rust_literal(out: &mut dyn fmt::Write, rows: &[String]) -> fmt::Result {
+    return write_literal(out, rows, |c| {
+        return match c {
+            '"' => Some("\\\"".to_owned()),
+            '\\' => Some("\\\\".to_owned()),
+            '\n' => Some("\\n".to_owned()),
+            _ if c.is_ascii_control() => Some(format!("\\x{:02x}", c as u32)),
+            _ if !c.is_ascii() => Some(format!("\\u{{{:x}}}", c as u32)),
+            _ => None,
+        };
+    });
+}
+
+/// See [`crate::to_c_literal`].
+pub fn write_c_literal(out: &mut dyn fmt::Write, rows: &[String]) -> fmt::Result {
+    return write_literal(out, rows, |c| {
+        return match c {
+            '"' => Some("\\\"".to_owned()),
+            '\\' => Some("\\\\".to_owned()),
+            '\n' => Some("\\n".to_owned()),
+            // so "??" can't turn into a trigraph
+            '?' => Some("\\?".to_owned()),
+            _ if c.is_ascii_control() => Some(format!("\\{:03o}", c as u32)),
+            _ if c as u32 > 0xffff => Some(format!("\\U{:08x}", c as u32)),
+            _ if !c.is_ascii() => Some(format!("\\u{:04x}", c as u32)),
+            _ => None,
+        };
+    });
+}
+
+// what has to be escaped inside a JSON string
+fn json_escape(c: char) -> Option<String> {
+    return match c {
+        '"' => Some("\\\"".to_owned()),
+        '\\' => Some("\\\\".to_owned()),
+        _ if (c as u32) < 0x20 => Some(format!("\\u{:04x}", c as u32)),
+        _ => None,
+    };
+}
+
+/// See [`crate::to_jsonl`].
+pub fn write_jsonl(out: &mut dyn fmt::Write, rows: &[String]) -> fmt::Result {
+    for (i, row) in rows.iter().enumerate() {
+        write!(out, "{{\"row\": {}, \"cells\": \"", i)?;
+        write_escaped(out, row, json_escape)?;
+        out.write_str("\"}\n")?;
+    }
+    return Ok(());
+}
+
+/// `format=multi`: `{"braille": "...", "ascii": "..."}`, the rows of each
+/// one per line.
+pub fn write_multi(out: &mut dyn fmt::Write, braille: &[String], ascii: &[String]) -> fmt::Result {
+    for (i, (key, rows)) in [("braille", braille), ("ascii", ascii)].into_iter().enumerate() {
+        write!(out, "{}\"{}\": \"", if i == 0 { "{" } else { ", " }, key)?;
+        write_escaped(out, &rows.join("\n"), json_escape)?;
+        out.write_char('"')?;
+    }
+    return out.write_str("}\n");
+}
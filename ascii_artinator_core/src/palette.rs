@@ -0,0 +1,72 @@
+//! Picking out the dominant colors of an image, by median cut.
+
+use image::DynamicImage;
+
+/// The image gets shrunk to fit this before sampling, which is plenty to
+/// find the main colors and keeps big images fast.
+const SAMPLE_SIZE: u32 = 64;
+
+/// Most colors that can be asked for.
+pub const MAX_COLORS: usize = 32;
+
+/// A box of pixels, to be split along its widest channel.
+struct ColorBox {
+    pixels: Vec<[u8; 3]>,
+}
+
+impl ColorBox {
+    /// The channel with the widest spread, and how wide it is.
+    fn widest_channel(&self) -> (usize, u8) {
+        let mut widest = (0, 0);
+        for c in 0..3 {
+            let min = self.pixels.iter().map(|pix| pix[c]).min().unwrap_or(0);
+            let max = self.pixels.iter().map(|pix| pix[c]).max().unwrap_or(0);
+            if max - min > widest.1 {
+                widest = (c, max - min);
+            }
+        }
+        return widest;
+    }
+
+    fn average(&self) -> [u8; 3] {
+        let mut sum = [0u64; 3];
+        for pix in &self.pixels {
+            for (total, &channel) in sum.iter_mut().zip(pix.iter()) {
+                *total += channel as u64;
+            }
+        }
+        let count = self.pixels.len().max(1) as u64;
+        return sum.map(|total| (total / count) as u8);
+    }
+}
+
+/// Up to `n` dominant colors, most common first. Fully transparent pixels
+/// don't count, so a logo's background doesn't win.
+pub fn dominant_colors(img: &DynamicImage, n: usize) -> Vec<[u8; 3]> {
+    let sample = img.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE).into_rgba8();
+    let pixels: Vec<[u8; 3]> = sample.pixels()
+        .filter(|pix| pix.0[3] > 0)
+        .map(|pix| [pix.0[0], pix.0[1], pix.0[2]])
+        .collect();
+    if pixels.is_empty() || n == 0 {
+        return Vec::new();
+    }
+    let mut boxes = vec![ColorBox { pixels }];
+    while boxes.len() < n {
+        // split whichever box has the widest spread, until none can be
+        let Some((i, (channel, _))) = boxes.iter()
+            .map(|b| b.widest_channel())
+            .enumerate()
+            .filter(|(_, (_, spread))| *spread > 0)
+            .max_by_key(|(_, (_, spread))| *spread) else {
+            break;
+        };
+        let mut pixels = boxes.swap_remove(i).pixels;
+        pixels.sort_unstable_by_key(|pix| pix[channel]);
+        let upper = pixels.split_off(pixels.len() / 2);
+        boxes.push(ColorBox { pixels });
+        boxes.push(ColorBox { pixels: upper });
+    }
+    boxes.sort_by_key(|b| std::cmp::Reverse(b.pixels.len()));
+    return boxes.iter().map(|b| b.average()).collect();
+}
@@ -0,0 +1,385 @@
+//! Transformations done on the image before it is mapped to characters.
+
+use image::{DynamicImage, GenericImageView, GrayImage};
+
+use crate::{Fit, Flip, Sample};
+
+/// How many pixels across the block behind each dot is, for the samplings
+/// that need the pixels themselves instead of a resize.
+pub const SAMPLE_BLOCK: u32 = 4;
+
+/// Rotates the image clockwise by any angle, growing the canvas so nothing
+/// gets cut off and filling the corners that open up with `fill`. Quarter
+/// turns take the exact path, anything else is sampled bilinearly.
+pub fn rotate(img: DynamicImage, degrees: f32, fill: image::Rgba<u8>) -> DynamicImage {
+    let degrees = degrees.rem_euclid(360.0);
+    match degrees {
+        0.0 => return img,
+        90.0 => return img.rotate90(),
+        180.0 => return img.rotate180(),
+        270.0 => return img.rotate270(),
+        _ => {},
+    }
+    let (dst_w, dst_h) = rotated_size(img.width(), img.height(), degrees);
+    let src = img.into_rgba8();
+    let (src_w, src_h) = (src.width() as f32, src.height() as f32);
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (src_cx, src_cy) = (src_w / 2.0, src_h / 2.0);
+    let (dst_cx, dst_cy) = (dst_w as f32 / 2.0, dst_h as f32 / 2.0);
+    // anything off the source reads as the fill, so edges blend into it too
+    let get = |x: i64, y: i64| -> [f32; 4] {
+        if x < 0 || y < 0 || x >= src.width() as i64 || y >= src.height() as i64 {
+            return fill.0.map(|c| c as f32);
+        }
+        return src.get_pixel(x as u32, y as u32).0.map(|c| c as f32);
+    };
+    let mut dst = image::RgbaImage::new(dst_w, dst_h);
+    for (x, y, pix) in dst.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - dst_cx;
+        let dy = y as f32 + 0.5 - dst_cy;
+        // walk back from the destination to where it came from
+        let sx = src_cx + dx * cos + dy * sin - 0.5;
+        let sy = src_cy - dx * sin + dy * cos - 0.5;
+        let (x0, y0) = (sx.floor() as i64, sy.floor() as i64);
+        let (fx, fy) = (sx - sx.floor(), sy - sy.floor());
+        let (tl, tr) = (get(x0, y0), get(x0 + 1, y0));
+        let (bl, br) = (get(x0, y0 + 1), get(x0 + 1, y0 + 1));
+        for c in 0..4 {
+            let top = tl[c] * (1.0 - fx) + tr[c] * fx;
+            let bottom = bl[c] * (1.0 - fx) + br[c] * fx;
+            pix.0[c] = (top * (1.0 - fy) + bottom * fy).round() as u8;
+        }
+    }
+    return DynamicImage::ImageRgba8(dst);
+}
+
+/// Just the alpha, as a grayscale image.
+pub fn alpha_channel(img: &DynamicImage) -> GrayImage {
+    let rgba = img.to_rgba8();
+    return GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| image::Luma([rgba.get_pixel(x, y).0[3]]));
+}
+
+/// How big [`rotate`] makes an image of this size.
+pub fn rotated_size(width: u32, height: u32, degrees: f32) -> (u32, u32) {
+    let degrees = degrees.rem_euclid(360.0);
+    if degrees == 90.0 || degrees == 270.0 {
+        return (height, width);
+    }
+    if degrees == 0.0 || degrees == 180.0 {
+        return (width, height);
+    }
+    let (width, height) = (width as f32, height as f32);
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let rotated_w = (width * cos.abs() + height * sin.abs()).round().max(1.0) as u32;
+    let rotated_h = (width * sin.abs() + height * cos.abs()).round().max(1.0) as u32;
+    return (rotated_w, rotated_h);
+}
+
+/// Resizes the image to exactly `width` by `height`, the way `fit` says to.
+pub fn fit(
+    img: DynamicImage,
+    width: u32,
+    height: u32,
+    fit: Fit,
+    deadline: Option<std::time::Instant>
+) -> Result<DynamicImage, crate::ConvertError> {
+    return Ok(match fit {
+        Fit::Stretch => crate::resample::resize_exact(&img, width, height, deadline)?,
+        Fit::Cover => crate::resample::resize_to_fill(&img, width, height, deadline)?,
+        Fit::Contain => {
+            let inner = crate::resample::resize(&img, width, height, deadline)?;
+            // the bars are transparent, which comes out blank
+            let mut boxed = image::RgbaImage::new(width, height);
+            let left = (width - inner.width()) / 2;
+            let top = (height - inner.height()) / 2;
+            image::imageops::overlay(&mut boxed, &inner.to_rgba8(), left as i64, top as i64);
+            DynamicImage::ImageRgba8(boxed)
+        },
+    });
+}
+
+/// Where a point `u`, from -1 to 1 across the output, samples the source. The
+/// slope is `1 / weight` in the middle, so the center is magnified `weight`
+/// times, and rises towards the edges to fit the rest in.
+fn center_warp(u: f32, weight: f32) -> f32 {
+    return u / weight + (1.0 - 1.0 / weight) * u.powi(3);
+}
+
+/// Resizes like [`crate::resize_img`], but sampling the source unevenly so the
+/// middle gets more of the dots, a bit like a barrel lens. The result is
+/// `scale` times the usual size. The `deadline` is checked at every row.
+pub fn center_weight(
+    img: DynamicImage,
+    weight: f32,
+    aspect: f32,
+    scale: u32,
+    deadline: Option<std::time::Instant>
+) -> Result<DynamicImage, crate::ConvertError> {
+    let (width, height) = crate::target_size(img.width(), img.height(), aspect);
+    let (width, height) = (width * scale, height * scale);
+    // enough detail for the magnified middle to be worth magnifying
+    let detail = crate::resample::resize(
+        &img,
+        (width as f32 * weight).ceil() as u32,
+        (height as f32 * weight).ceil() as u32,
+        deadline
+    )?.into_rgba8();
+    let (detail_w, detail_h) = (detail.width() as f32, detail.height() as f32);
+    let sample = |pos: u32, size: u32, detail_size: f32| -> f32 {
+        let u = (pos as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+        let src = (center_warp(u, weight) + 1.0) / 2.0 * detail_size - 0.5;
+        return src.clamp(0.0, detail_size - 1.0);
+    };
+    let mut dst = image::RgbaImage::new(width, height);
+    for (x, y, pix) in dst.enumerate_pixels_mut() {
+        if x == 0 {
+            crate::options::check_deadline(deadline)?;
+        }
+        let (sx, sy) = (sample(x, width, detail_w), sample(y, height, detail_h));
+        if let Some(sampled) = image::imageops::interpolate_bilinear(&detail, sx, sy) {
+            *pix = sampled;
+        }
+    }
+    return Ok(DynamicImage::ImageRgba8(dst));
+}
+
+/// Cuts the image down to two levels: everything brighter than `threshold`
+/// fully lit, everything else (the subject, on a bright background) blank,
+/// for a solid cut-out instead of dithered shading.
+pub fn stencil(mut gray_img: GrayImage, threshold: u8) -> GrayImage {
+    for pix in gray_img.pixels_mut() {
+        pix.0[0] = if pix.0[0] > threshold { 255 } else { 0 };
+    }
+    return gray_img;
+}
+
+/// Most halvings [`density_threshold`] does, enough to get down to any one
+/// of the 256 levels.
+pub const MAX_DENSITY_STEPS: usize = 8;
+
+/// The threshold that leaves about `density` of the pixels brighter than it,
+/// i.e. lit by [`stencil`]. It's a binary search over the histogram, so the
+/// pixels are only gone over the once.
+pub fn density_threshold(gray_img: &GrayImage, density: f32) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pix in gray_img.pixels() {
+        histogram[pix.0[0] as usize] += 1;
+    }
+    // how many are brighter than each level
+    let mut brighter = [0u64; 256];
+    for level in (0..255).rev() {
+        brighter[level] = brighter[level + 1] + histogram[level + 1];
+    }
+    let wanted = density as f64 * gray_img.pixels().len() as f64;
+    let off = |level: usize| (brighter[level] as f64 - wanted).abs();
+    // the fewer are lit the higher it goes, so this finds the lowest level
+    // lighting no more than wanted
+    let (mut low, mut high) = (0, 255);
+    for _ in 0..MAX_DENSITY_STEPS {
+        if low >= high {
+            break;
+        }
+        let mid = (low + high) / 2;
+        if brighter[mid] as f64 > wanted {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    // the level under it lights a bit more than wanted, and may be closer
+    if low > 0 && off(low - 1) < off(low) {
+        return (low - 1) as u8;
+    }
+    return low as u8;
+}
+
+/// Whether the corners of the image, where the background usually is, are
+/// brighter than its middle, where the subject usually is. Goes by a small
+/// thumbnail, so it's cheap on any size.
+pub fn corners_brighter(img: &DynamicImage) -> bool {
+    let thumb = crate::to_gray(&img.thumbnail(64, 64));
+    let (width, height) = thumb.dimensions();
+    // an eighth of each side for the corners, the middle half for the subject
+    let (corner_w, corner_h) = ((width / 8).max(1), (height / 8).max(1));
+    let in_corner = |x: u32, y: u32| {
+        return (x < corner_w || x >= width - corner_w) && (y < corner_h || y >= height - corner_h);
+    };
+    let in_middle = |x: u32, y: u32| (width / 4..width - width / 4).contains(&x) && (height / 4..height - height / 4).contains(&y);
+    let (mut corners, mut corner_n, mut middle, mut middle_n) = (0u64, 0u64, 0u64, 0u64);
+    for (x, y, pix) in thumb.enumerate_pixels() {
+        if in_corner(x, y) {
+            corners += pix.0[0] as u64;
+            corner_n += 1;
+        } else if in_middle(x, y) {
+            middle += pix.0[0] as u64;
+            middle_n += 1;
+        }
+    }
+    if corner_n == 0 || middle_n == 0 {
+        return false;
+    }
+    return corners * middle_n > middle * corner_n;
+}
+
+/// Otsu's threshold for the image: the level that best splits it into a dark
+/// and a bright group, i.e. a good guess for `stencil_threshold`. A flat image
+/// has no split at all and gets 0.
+pub fn otsu_threshold(gray_img: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pix in gray_img.pixels() {
+        histogram[pix.0[0] as usize] += 1;
+    }
+    let total = gray_img.pixels().len() as f64;
+    let sum: f64 = histogram.iter().enumerate().map(|(level, &n)| level as f64 * n as f64).sum();
+    let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+    let (mut best, mut best_variance) = (0, 0.0);
+    for (level, &n) in histogram.iter().enumerate() {
+        dark_count += n as f64;
+        dark_sum += level as f64 * n as f64;
+        let bright_count = total - dark_count;
+        if dark_count == 0.0 {
+            continue;
+        }
+        if bright_count == 0.0 {
+            break;
+        }
+        let mean_gap = dark_sum / dark_count - (sum - dark_sum) / bright_count;
+        let variance = dark_count * bright_count * mean_gap * mean_gap;
+        if variance > best_variance {
+            (best, best_variance) = (level as u8, variance);
+        }
+    }
+    return best;
+}
+
+/// Mirrors the image as asked.
+pub fn flip(img: DynamicImage, flip: Flip) -> DynamicImage {
+    return match flip {
+        Flip::None => img,
+        Flip::H => img.fliph(),
+        Flip::V => img.flipv(),
+        // both at once is the same as half a turn
+        Flip::Both => img.rotate180(),
+    };
+}
+
+/// Guesses the background color from the four corners and makes every pixel
+/// within `tolerance` of it fully transparent, which turns its dots off. If
+/// `crop` is set, the result is also cropped to whatever is left.
+pub fn trim_background(img: DynamicImage, tolerance: u8, crop: bool) -> DynamicImage {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return img;
+    }
+    let mut rgba = img.into_rgba8();
+    let corners = [(0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)];
+    let mut bg = [0u32; 3];
+    for (x, y) in corners {
+        let pix = rgba.get_pixel(x, y);
+        for (sum, &channel) in bg.iter_mut().zip(pix.0.iter()) {
+            *sum += channel as u32;
+        }
+    }
+    let bg = bg.map(|sum| (sum / 4) as i32);
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+    for (x, y, pix) in rgba.enumerate_pixels_mut() {
+        let is_bg = (0..3).all(|c| (pix.0[c] as i32 - bg[c]).abs() <= tolerance as i32);
+        if is_bg {
+            pix.0[3] = 0;
+        } else if pix.0[3] != 0 {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+
+    let img = DynamicImage::ImageRgba8(rgba);
+    if crop && min_x <= max_x && min_y <= max_y {
+        return img.crop_imm(min_x, min_y, max_x - min_x + 1, max_y - min_y + 1);
+    }
+    return img;
+}
+
+/// Replaces every pixel, channel by channel, with the median of the square
+/// within `radius` of it. Unlike a blur, lone specks vanish entirely and
+/// edges stay sharp.
+pub fn median_filter(img: &DynamicImage, radius: u32) -> DynamicImage {
+    let src = img.to_rgba8();
+    let (width, height) = src.dimensions();
+    let mut dst = image::RgbaImage::new(width, height);
+    let mut window = Vec::with_capacity(((radius * 2 + 1) * (radius * 2 + 1)) as usize);
+    for (x, y, pix) in dst.enumerate_pixels_mut() {
+        let xs = x.saturating_sub(radius)..=(x + radius).min(width - 1);
+        let ys = y.saturating_sub(radius)..=(y + radius).min(height - 1);
+        for c in 0..4 {
+            window.clear();
+            for ny in ys.clone() {
+                window.extend(xs.clone().map(|nx| src.get_pixel(nx, ny).0[c]));
+            }
+            let mid = window.len() / 2;
+            pix.0[c] = *window.select_nth_unstable(mid).1;
+        }
+    }
+    return DynamicImage::ImageRgba8(dst);
+}
+
+/// Shrinks the image `block` times, each pixel of the result coming from the
+/// `block` by `block` square of pixels it covers as `sample` says.
+pub fn sample_blocks(gray_img: &GrayImage, block: u32, sample: Sample) -> GrayImage {
+    let (width, height) = (gray_img.width() / block, gray_img.height() / block);
+    let mut values = Vec::with_capacity((block * block) as usize);
+    return GrayImage::from_fn(width, height, |x, y| {
+        values.clear();
+        for dy in 0..block {
+            values.extend((0..block).map(|dx| gray_img.get_pixel(x * block + dx, y * block + dy).0[0]));
+        }
+        let value = match sample {
+            Sample::Avg => {
+                let sum: u32 = values.iter().map(|&v| v as u32).sum();
+                (sum as f32 / values.len() as f32).round() as u8
+            },
+            Sample::Min => *values.iter().min().unwrap(),
+            Sample::Max => *values.iter().max().unwrap(),
+            Sample::Median => {
+                let mid = values.len() / 2;
+                *values.select_nth_unstable(mid).1
+            },
+        };
+        return image::Luma([value]);
+    });
+}
+
+/// Stretches the luminance linearly so the darkest pixel becomes 0 and the
+/// brightest 255. `clip` is the percentage of pixels at each end that may be
+/// pushed past the range, so a few outliers don't keep it from stretching.
+pub fn auto_contrast(mut gray_img: GrayImage, clip: f32) -> GrayImage {
+    let mut histogram = [0usize; 256];
+    for pix in gray_img.pixels() {
+        histogram[pix.0[0] as usize] += 1;
+    }
+    let total: usize = histogram.iter().sum();
+    let skip = (total as f32 * clip / 100.0) as usize;
+    // walks in from each end until more than `skip` pixels were passed
+    let (mut low, mut high) = (0, 255);
+    let mut seen = 0;
+    while low < 255 && seen + histogram[low] <= skip {
+        seen += histogram[low];
+        low += 1;
+    }
+    seen = 0;
+    while high > 0 && seen + histogram[high] <= skip {
+        seen += histogram[high];
+        high -= 1;
+    }
+    if high <= low {
+        return gray_img;
+    }
+    let scale = 255.0 / (high - low) as f32;
+    for pix in gray_img.pixels_mut() {
+        let stretched = (pix.0[0] as f32 - low as f32) * scale;
+        pix.0[0] = stretched.round().clamp(0.0, 255.0) as u8;
+    }
+    return gray_img;
+}
@@ -0,0 +1,32 @@
+//! Mapping dots to quadrant block characters.
+
+// each quadrant char covers a 2x2 pixel block; bit 0 is the top left pixel,
+// bit 1 the top right, bit 2 the bottom left and bit 3 the bottom right
+pub const QUADRANT_CHARS: [char; 16] = [
+' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█'
+];
+
+/// Quadrant blocks, with every pixel above 80 a filled quarter.
+pub struct QuadrantMapper;
+
+impl crate::mapper::CellMapper for QuadrantMapper {
+    fn cell_size(&self) -> (u32, u32) {
+        return (2, 2);
+    }
+
+    fn map_cell(&self, block: &[u8]) -> char {
+        // the block comes row by row, which is already the bit order
+        let mut val = 0;
+        for (bit, &pix) in block.iter().enumerate() {
+            if pix > 80 {
+                val |= 1 << bit;
+            }
+        }
+        return QUADRANT_CHARS[val];
+    }
+}
+
+/// Gray pixels straight to quadrant rows, blank cells as spaces.
+pub fn gray_to_quadrant(gray_img: &image::GrayImage) -> Vec<String> {
+    return crate::mapper::map_cells(gray_img, &QuadrantMapper);
+}
@@ -0,0 +1,95 @@
+//! Rendering the text output back into a PNG, for places that take images but
+//! not walls of braille. Needs the `render` feature, which pulls in `ab_glyph`.
+
+use crate::ConvertError;
+
+/// Parses a color like `ff8800`, with or without the leading `#`.
+pub fn parse_hex_color(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let mut rgb = [0; 3];
+    for (i, channel) in rgb.iter_mut().enumerate() {
+        *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    return Some(rgb);
+}
+
+/// The font [`render_png`] is meant to get, built in: DejaVu Sans Mono with
+/// the braille patterns added on its own grid (see `fonts/add_braille.py`),
+/// so every char takes the same cell. Empty without the `render` feature.
+#[cfg(feature = "render")]
+pub const DEFAULT_FONT: &[u8] = include_bytes!("../fonts/ArtinatorMono.ttf");
+
+#[cfg(not(feature = "render"))]
+pub const DEFAULT_FONT: &[u8] = &[];
+
+/// Draws the rows (one per line of `text`) with the given font, every char
+/// in a cell as wide as a blank braille one so the grid stays in place even
+/// if the font isn't monospace. Colors from the colored modes are dropped.
+#[cfg(feature = "render")]
+pub fn render_png(
+    text: &str,
+    font_data: &[u8],
+    font_size: f32,
+    fg: [u8; 3],
+    bg: [u8; 3]
+) -> Result<Vec<u8>, ConvertError> {
+    use ab_glyph::{Font, FontRef, ScaleFont};
+    let font = FontRef::try_from_slice(font_data).map_err(|_| ConvertError::BadFont)?;
+    let font = font.as_scaled(font_size);
+    let cell_width = font.h_advance(font.glyph_id('⠀'));
+    let line_height = font.height() + font.line_gap();
+    let rows: Vec<Vec<char>> = text.lines()
+        .map(|row| crate::rows::visible_chars(row).collect())
+        .collect();
+    let cols = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    // a bit of margin all around, half a cell
+    let pad = (cell_width / 2.0).ceil();
+    let width = (cols as f32 * cell_width + pad * 2.0).ceil() as u32;
+    let height = (rows.len() as f32 * line_height + pad * 2.0).ceil() as u32;
+    if width as u64 * height as u64 > crate::DEFAULT_MAX_PIXELS {
+        return Err(ConvertError::TooLarge);
+    }
+    let mut img = image::RgbImage::from_pixel(width.max(1), height.max(1), image::Rgb(bg));
+    for (y, row) in rows.iter().enumerate() {
+        let baseline = pad + y as f32 * line_height + font.ascent();
+        for (x, &c) in row.iter().enumerate() {
+            let glyph = font.glyph_id(c)
+                .with_scale_and_position(font_size, ab_glyph::point(pad + x as f32 * cell_width, baseline));
+            let outline = match font.outline_glyph(glyph) {
+                Some(outline) => outline,
+                // blanks have nothing to draw
+                None => continue,
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px as u32 >= width || py as u32 >= height {
+                    return;
+                }
+                let pix = img.get_pixel_mut(px as u32, py as u32);
+                for (channel, &fg_channel) in pix.0.iter_mut().zip(fg.iter()) {
+                    let blended = *channel as f32 * (1.0 - coverage) + fg_channel as f32 * coverage;
+                    *channel = blended.round() as u8;
+                }
+            });
+        }
+    }
+    let mut png = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png, image::ImageOutputFormat::Png).map_err(|_| ConvertError::Render)?;
+    return Ok(png.into_inner());
+}
+
+#[cfg(not(feature = "render"))]
+pub fn render_png(
+    _text: &str,
+    _font_data: &[u8],
+    _font_size: f32,
+    _fg: [u8; 3],
+    _bg: [u8; 3]
+) -> Result<Vec<u8>, ConvertError> {
+    return Err(ConvertError::RenderUnsupported);
+}
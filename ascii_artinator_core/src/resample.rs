@@ -0,0 +1,253 @@
+//! The Triangle resize `image` does, done here instead so the deadline can be
+//! checked as it goes: huge images spend most of their conversion in it.
+//! It samples exactly the way `image` 0.24 does, pass for pass, so what comes
+//! out is the same to the byte.
+
+use image::{DynamicImage, GenericImageView, ImageBuffer, Pixel, Primitive, Rgba32FImage};
+use num_traits::{NumCast, ToPrimitive};
+
+use crate::ConvertError;
+
+use std::time::Instant;
+
+fn triangle(x: f32) -> f32 {
+    return if x.abs() < 1.0 { 1.0 - x.abs() } else { 0.0 };
+}
+
+fn clamp<N: PartialOrd>(a: N, min: N, max: N) -> N {
+    if a < min {
+        return min;
+    }
+    if a > max {
+        return max;
+    }
+    return a;
+}
+
+// rounds to the nearest integer on the way into an integer channel, and
+// leaves floats be, the same as `image`'s own
+struct FloatNearest(f32);
+
+impl ToPrimitive for FloatNearest {
+    fn to_i8(&self) -> Option<i8> {
+        return self.0.round().to_i8();
+    }
+    fn to_i16(&self) -> Option<i16> {
+        return self.0.round().to_i16();
+    }
+    fn to_i64(&self) -> Option<i64> {
+        return self.0.round().to_i64();
+    }
+    fn to_u8(&self) -> Option<u8> {
+        return self.0.round().to_u8();
+    }
+    fn to_u16(&self) -> Option<u16> {
+        return self.0.round().to_u16();
+    }
+    fn to_u64(&self) -> Option<u64> {
+        return self.0.round().to_u64();
+    }
+    fn to_f64(&self) -> Option<f64> {
+        return self.0.to_f64();
+    }
+}
+
+// the input pixels an output one at `out` takes from, start and end, and the
+// weight of each, along one side of `size` going to `new_size`
+fn weights(out: u32, size: u32, new_size: u32, ws: &mut Vec<f32>) -> u32 {
+    let ratio = size as f32 / new_size as f32;
+    let sratio = if ratio < 1.0 { 1.0 } else { ratio };
+    let src_support = sratio;
+    let input = (out as f32 + 0.5) * ratio;
+    let left = (input - src_support).floor() as i64;
+    let left = clamp(left, 0, <i64 as From<_>>::from(size) - 1) as u32;
+    let right = (input + src_support).ceil() as i64;
+    let right = clamp(right, <i64 as From<_>>::from(left) + 1, <i64 as From<_>>::from(size)) as u32;
+    // the kernel has a pixel's center at 0
+    let input = input - 0.5;
+    ws.clear();
+    let mut sum = 0.0;
+    for i in left..right {
+        let w = triangle((i as f32 - input) / sratio);
+        ws.push(w);
+        sum += w;
+    }
+    ws.iter_mut().for_each(|w| *w /= sum);
+    return left;
+}
+
+// each column down to `new_height`, in floats; checks at every output row
+fn vertical_sample<P, S>(
+    image: &ImageBuffer<P, Vec<S>>,
+    new_height: u32,
+    deadline: Option<Instant>
+) -> Result<Rgba32FImage, ConvertError>
+where
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(width, new_height);
+    let mut ws = Vec::new();
+    for outy in 0..new_height {
+        crate::options::check_deadline(deadline)?;
+        let left = weights(outy, height, new_height, &mut ws);
+        for x in 0..width {
+            let mut t = (0.0, 0.0, 0.0, 0.0);
+            for (i, w) in ws.iter().enumerate() {
+                #[allow(deprecated)]
+                let (k1, k2, k3, k4) = image.get_pixel(x, left + i as u32).channels4();
+                let vec: (f32, f32, f32, f32) = (
+                    NumCast::from(k1).unwrap(),
+                    NumCast::from(k2).unwrap(),
+                    NumCast::from(k3).unwrap(),
+                    NumCast::from(k4).unwrap(),
+                );
+                t.0 += vec.0 * w;
+                t.1 += vec.1 * w;
+                t.2 += vec.2 * w;
+                t.3 += vec.3 * w;
+            }
+            // not necessarily RGBA, the channels just pass through in order
+            #[allow(deprecated)]
+            let t = Pixel::from_channels(t.0, t.1, t.2, t.3);
+            out.put_pixel(x, outy, t);
+        }
+    }
+    return Ok(out);
+}
+
+// each row across to `new_width`, back in the image's own pixels; checks at
+// every output column
+fn horizontal_sample<P, S>(
+    image: &Rgba32FImage,
+    new_width: u32,
+    deadline: Option<Instant>
+) -> Result<ImageBuffer<P, Vec<S>>, ConvertError>
+where
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    let (width, height) = image.dimensions();
+    let mut out = ImageBuffer::new(new_width, height);
+    let mut ws = Vec::new();
+    let max: f32 = NumCast::from(S::DEFAULT_MAX_VALUE).unwrap();
+    let min: f32 = NumCast::from(S::DEFAULT_MIN_VALUE).unwrap();
+    for outx in 0..new_width {
+        crate::options::check_deadline(deadline)?;
+        let left = weights(outx, width, new_width, &mut ws);
+        for y in 0..height {
+            let mut t = (0.0, 0.0, 0.0, 0.0);
+            for (i, w) in ws.iter().enumerate() {
+                #[allow(deprecated)]
+                let vec = image.get_pixel(left + i as u32, y).channels4();
+                t.0 += vec.0 * w;
+                t.1 += vec.1 * w;
+                t.2 += vec.2 * w;
+                t.3 += vec.3 * w;
+            }
+            #[allow(deprecated)]
+            let t = Pixel::from_channels(
+                NumCast::from(FloatNearest(clamp(t.0, min, max))).unwrap(),
+                NumCast::from(FloatNearest(clamp(t.1, min, max))).unwrap(),
+                NumCast::from(FloatNearest(clamp(t.2, min, max))).unwrap(),
+                NumCast::from(FloatNearest(clamp(t.3, min, max))).unwrap(),
+            );
+            out.put_pixel(outx, y, t);
+        }
+    }
+    return Ok(out);
+}
+
+fn resize_buffer<P, S>(
+    image: &ImageBuffer<P, Vec<S>>,
+    width: u32,
+    height: u32,
+    deadline: Option<Instant>
+) -> Result<ImageBuffer<P, Vec<S>>, ConvertError>
+where
+    P: Pixel<Subpixel = S> + 'static,
+    S: Primitive + 'static,
+{
+    if (width, height) == image.dimensions() {
+        return Ok(image.clone());
+    }
+    let tmp = vertical_sample(image, height, deadline)?;
+    return horizontal_sample(&tmp, width, deadline);
+}
+
+/// What `img.resize_exact(width, height, Triangle)` gives, checking the
+/// `deadline` at every row and column it works out.
+pub fn resize_exact(
+    img: &DynamicImage,
+    width: u32,
+    height: u32,
+    deadline: Option<Instant>
+) -> Result<DynamicImage, ConvertError> {
+    return Ok(match img {
+        DynamicImage::ImageLuma8(buf) => DynamicImage::ImageLuma8(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageLumaA8(buf) => DynamicImage::ImageLumaA8(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageRgb8(buf) => DynamicImage::ImageRgb8(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageRgba8(buf) => DynamicImage::ImageRgba8(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageLuma16(buf) => DynamicImage::ImageLuma16(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageLumaA16(buf) => DynamicImage::ImageLumaA16(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageRgb16(buf) => DynamicImage::ImageRgb16(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageRgba16(buf) => DynamicImage::ImageRgba16(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageRgb32F(buf) => DynamicImage::ImageRgb32F(resize_buffer(buf, width, height, deadline)?),
+        DynamicImage::ImageRgba32F(buf) => DynamicImage::ImageRgba32F(resize_buffer(buf, width, height, deadline)?),
+        // whatever gets added later, unchecked
+        _ => img.resize_exact(width, height, image::imageops::Triangle),
+    });
+}
+
+// the size `DynamicImage::resize` (`fill` false) or `resize_to_fill` (true)
+// go to, as big as fits in or covers `width` by `height` keeping the ratio
+fn resize_dimensions(img: &DynamicImage, width: u32, height: u32, fill: bool) -> (u32, u32) {
+    let wratio = width as f64 / img.width() as f64;
+    let hratio = height as f64 / img.height() as f64;
+    let ratio = if fill { f64::max(wratio, hratio) } else { f64::min(wratio, hratio) };
+    let nw = ((img.width() as f64 * ratio).round() as u64).max(1);
+    let nh = ((img.height() as f64 * ratio).round() as u64).max(1);
+    if nw > <u64 as From<_>>::from(u32::MAX) {
+        let ratio = u32::MAX as f64 / img.width() as f64;
+        return (u32::MAX, ((img.height() as f64 * ratio).round() as u32).max(1));
+    }
+    if nh > <u64 as From<_>>::from(u32::MAX) {
+        let ratio = u32::MAX as f64 / img.height() as f64;
+        return (((img.width() as f64 * ratio).round() as u32).max(1), u32::MAX);
+    }
+    return (nw as u32, nh as u32);
+}
+
+/// What `img.resize(width, height, Triangle)` gives, see [`resize_exact`].
+pub fn resize(
+    img: &DynamicImage,
+    width: u32,
+    height: u32,
+    deadline: Option<Instant>
+) -> Result<DynamicImage, ConvertError> {
+    if (width, height) == img.dimensions() {
+        return Ok(img.clone());
+    }
+    let (width, height) = resize_dimensions(img, width, height, false);
+    return resize_exact(img, width, height, deadline);
+}
+
+/// What `img.resize_to_fill(width, height, Triangle)` gives, see
+/// [`resize_exact`].
+pub fn resize_to_fill(
+    img: &DynamicImage,
+    width: u32,
+    height: u32,
+    deadline: Option<Instant>
+) -> Result<DynamicImage, ConvertError> {
+    let (fill_width, fill_height) = resize_dimensions(img, width, height, true);
+    let mut intermediate = resize_exact(img, fill_width, fill_height, deadline)?;
+    let (iwidth, iheight) = intermediate.dimensions();
+    let ratio = <u64 as From<_>>::from(iwidth) * <u64 as From<_>>::from(height);
+    let nratio = <u64 as From<_>>::from(width) * <u64 as From<_>>::from(iheight);
+    if nratio > ratio {
+        return Ok(intermediate.crop(0, (iheight - height) / 2, width, height));
+    }
+    return Ok(intermediate.crop((iwidth - width) / 2, 0, width, height));
+}
@@ -0,0 +1,208 @@
+//! Post-processing done on the rows of the output, after the image has been
+//! mapped to characters.
+
+/// Blank braille, used for padding.
+const BLANK: char = '⠀';
+
+/// Whether a char shows nothing. The single dot counts too, since that is
+/// what the braille mapping uses in place of blank cells by default.
+fn is_blank_char(c: char) -> bool {
+    return c == BLANK || c == crate::braille::BRAILLE_CHARS[1] || c == ' ' || c == '\u{a0}';
+}
+
+/// The chars of a row that actually show up, skipping over ANSI escape
+/// sequences from the colored modes.
+pub fn visible_chars(row: &str) -> impl Iterator<Item = char> + '_ {
+    let mut in_escape = false;
+    return row.chars().filter(move |&c| {
+        if in_escape {
+            in_escape = !c.is_ascii_alphabetic();
+            return false;
+        } else if c == '\x1b' {
+            in_escape = true;
+            return false;
+        }
+        return true;
+    });
+}
+
+/// Repeats every visible char `times` times, to widen the output on
+/// terminals with tall cells. Escape sequences are kept as they are, once,
+/// so color runs stay where they were.
+pub fn repeat_cells(rows: Vec<String>, times: usize) -> Vec<String> {
+    return rows.into_iter().map(|row| {
+        let mut repeated = String::with_capacity(row.len() * times);
+        let mut in_escape = false;
+        for c in row.chars() {
+            if in_escape {
+                in_escape = !c.is_ascii_alphabetic();
+                repeated.push(c);
+            } else if c == '\x1b' {
+                in_escape = true;
+                repeated.push(c);
+            } else {
+                for _ in 0..times {
+                    repeated.push(c);
+                }
+            }
+        }
+        return repeated;
+    }).collect();
+}
+
+/// Puts a plain space between every two visible chars, for readers who find
+/// dense braille hard to tell apart.
+pub fn add_col_gaps(rows: Vec<String>) -> Vec<String> {
+    return rows.into_iter().map(|row| {
+        let mut gapped = String::with_capacity(row.len() * 2);
+        let mut in_escape = false;
+        let mut first = true;
+        for c in row.chars() {
+            if in_escape || c == '\x1b' {
+                in_escape = c == '\x1b' || !c.is_ascii_alphabetic();
+            } else if first {
+                first = false;
+            } else {
+                gapped.push(' ');
+            }
+            gapped.push(c);
+        }
+        return gapped;
+    }).collect();
+}
+
+/// Cuts a row into pieces of at most `width` visible chars. A color that is
+/// still on where a piece ends is reset there and set again at the start of
+/// the next one, so every piece stands on its own.
+fn split_visible(row: &str, width: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    let mut count = 0;
+    let mut escape = String::new();
+    let mut active: Option<String> = None;
+    for c in row.chars() {
+        if !escape.is_empty() || c == '\x1b' {
+            escape.push(c);
+            if c.is_ascii_alphabetic() {
+                piece.push_str(&escape);
+                active = Some(std::mem::take(&mut escape)).filter(|e| e != crate::color::RESET);
+            }
+            continue;
+        }
+        if count == width {
+            if active.is_some() {
+                piece.push_str(crate::color::RESET);
+            }
+            pieces.push(std::mem::replace(&mut piece, active.clone().unwrap_or_default()));
+            count = 0;
+        }
+        piece.push(c);
+        count += 1;
+    }
+    piece.push_str(&escape);
+    pieces.push(piece);
+    return pieces;
+}
+
+/// Reflows the grid into blocks of at most `width` columns, stacked one under
+/// the other with a blank line between them: first the left `width` columns
+/// of every row, then the next `width`, and so on.
+pub fn wrap_columns(rows: &[String], width: usize) -> Vec<String> {
+    let split: Vec<Vec<String>> = rows.iter().map(|row| split_visible(row, width)).collect();
+    let blocks = split.iter().map(|pieces| pieces.len()).max().unwrap_or(0);
+    let mut wrapped = Vec::with_capacity(rows.len() * blocks + blocks);
+    for block in 0..blocks {
+        if block > 0 {
+            wrapped.push(String::new());
+        }
+        for pieces in &split {
+            wrapped.push(pieces.get(block).cloned().unwrap_or_default());
+        }
+    }
+    return wrapped;
+}
+
+/// Puts every visible char on a line of its own, row after row, for a single
+/// vertical column. Escape sequences stay with the char right after them, or
+/// with the last char of the row if nothing comes after.
+pub fn to_vertical(rows: &[String]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for row in rows {
+        let mut pending = String::new();
+        let mut in_escape = false;
+        for c in row.chars() {
+            if in_escape || c == '\x1b' {
+                in_escape = c == '\x1b' || !c.is_ascii_alphabetic();
+                pending.push(c);
+            } else {
+                pending.push(c);
+                lines.push(std::mem::take(&mut pending));
+            }
+        }
+        if let (false, Some(last)) = (pending.is_empty(), lines.last_mut()) {
+            last.push_str(&pending);
+        }
+    }
+    return lines;
+}
+
+/// Whether every char in a row shows nothing.
+pub fn is_blank_row(row: &str) -> bool {
+    return visible_chars(row).all(is_blank_char);
+}
+
+/// Replaces every run of at least `min_run` blank rows with a single one.
+/// Shorter runs are left alone.
+pub fn collapse_blank_rows(rows: Vec<String>, min_run: usize) -> Vec<String> {
+    let mut collapsed = Vec::with_capacity(rows.len());
+    let mut run: Vec<String> = Vec::new();
+    for row in rows {
+        if is_blank_row(&row) {
+            run.push(row);
+            continue;
+        }
+        if run.len() >= min_run {
+            collapsed.push(run.swap_remove(0));
+        } else {
+            collapsed.append(&mut run);
+        }
+        run.clear();
+        collapsed.push(row);
+    }
+    if run.len() >= min_run {
+        collapsed.push(run.swap_remove(0));
+    } else {
+        collapsed.append(&mut run);
+    }
+    return collapsed;
+}
+
+/// Joins rows the way chat wants them: every row is preceded by a space, so
+/// at the right chat width each one wraps onto its own line.
+pub fn join_rows(rows: &[String]) -> String {
+    return crate::output::collect(|out| crate::output::write_joined(out, rows));
+}
+
+/// Wraps the rows in a minimal HTML page, one row per line.
+pub fn to_html(rows: &[String]) -> String {
+    return crate::output::collect(|out| crate::output::write_html(out, rows));
+}
+
+/// Width of the longest row, in chars.
+pub fn max_width(rows: &[String]) -> usize {
+    return rows.iter().map(|row| visible_chars(row).count()).max().unwrap_or(0);
+}
+
+/// Surrounds the rows with a box-drawing border. Shorter rows are padded
+/// with blank braille up to the longest one, so the right edge lines up.
+pub fn add_border(rows: &[String]) -> Vec<String> {
+    let width = max_width(rows);
+    let mut bordered = Vec::with_capacity(rows.len() + 2);
+    bordered.push(format!("┌{}┐", "─".repeat(width)));
+    for row in rows {
+        let padding = width - visible_chars(row).count();
+        bordered.push(format!("│{}{}│", row, BLANK.to_string().repeat(padding)));
+    }
+    bordered.push(format!("└{}┘", "─".repeat(width)));
+    return bordered;
+}
@@ -0,0 +1,63 @@
+//! SVG input support. The `image` crate can't decode SVGs, so with the `svg`
+//! feature on they get rasterized via `resvg` before conversion.
+
+use crate::ConvertError;
+
+/// Width SVGs are rasterized at, a few times the braille width so the resize
+/// afterwards still has detail to work with.
+#[cfg(feature = "svg")]
+const RASTER_WIDTH: u32 = 232;
+
+/// Sniffs the start of the body for an XML/SVG root, since servers are not
+/// very reliable about the content-type they send for SVGs.
+pub fn looks_like_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    return head.starts_with('<') && head.contains("<svg");
+}
+
+// how tall the SVG comes out at RASTER_WIDTH, and the scale that takes
+#[cfg(feature = "svg")]
+fn raster_height(tree: &resvg::usvg::Tree) -> (u32, f32) {
+    let size = tree.size();
+    let scale = RASTER_WIDTH as f32 / size.width();
+    return (((size.height() * scale).round() as u32).max(1), scale);
+}
+
+/// The size [`rasterize`] would rasterize the SVG at, without drawing it.
+#[cfg(feature = "svg")]
+pub fn raster_size(bytes: &[u8]) -> Result<(u32, u32), ConvertError> {
+    let tree = resvg::usvg::Tree::from_data(bytes, &resvg::usvg::Options::default())
+        .map_err(|_| ConvertError::Svg)?;
+    return Ok((RASTER_WIDTH, raster_height(&tree).0));
+}
+
+#[cfg(not(feature = "svg"))]
+pub fn raster_size(_bytes: &[u8]) -> Result<(u32, u32), ConvertError> {
+    return Err(ConvertError::SvgUnsupported);
+}
+
+#[cfg(feature = "svg")]
+pub fn rasterize(bytes: &[u8], max_pixels: u64) -> Result<image::DynamicImage, ConvertError> {
+    use resvg::{tiny_skia, usvg};
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())
+        .map_err(|_| ConvertError::Svg)?;
+    let (height, scale) = raster_height(&tree);
+    if RASTER_WIDTH as u64 * height as u64 > max_pixels {
+        return Err(ConvertError::TooLarge);
+    }
+    let mut pixmap = tiny_skia::Pixmap::new(RASTER_WIDTH, height)
+        .ok_or(ConvertError::Svg)?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    let mut img = image::RgbaImage::new(RASTER_WIDTH, height);
+    for (pix, skia_pix) in img.pixels_mut().zip(pixmap.pixels()) {
+        let c = skia_pix.demultiply();
+        *pix = image::Rgba([c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+    return Ok(image::DynamicImage::ImageRgba8(img));
+}
+
+#[cfg(not(feature = "svg"))]
+pub fn rasterize(_bytes: &[u8], _max_pixels: u64) -> Result<image::DynamicImage, ConvertError> {
+    return Err(ConvertError::SvgUnsupported);
+}
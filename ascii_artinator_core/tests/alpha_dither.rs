@@ -0,0 +1,69 @@
+//! With `alpha_dither`, a soft edge shouldn't spill dots into the fully
+//! transparent background around it.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image;
+
+// a light disc fading out to nothing over its outer half
+fn soft_circle(size: u32) -> image::RgbaImage {
+    let center = size as f32 / 2.0;
+    return image::RgbaImage::from_fn(size, size, |x, y| {
+        let dist = ((x as f32 + 0.5 - center).powi(2) + (y as f32 + 0.5 - center).powi(2)).sqrt();
+        let alpha = (2.0 - 2.0 * dist / (center * 0.9)).clamp(0.0, 1.0);
+        return image::Rgba([150, 150, 150, (alpha * 255.0).round() as u8]);
+    });
+}
+
+fn lit_in_background(dots: &image::GrayImage, img: &image::RgbaImage) -> usize {
+    return dots.enumerate_pixels()
+        .filter(|(x, y, dot)| dot.0[0] != 0 && img.get_pixel(*x, *y).0[3] == 0)
+        .count();
+}
+
+#[test]
+fn background_stays_clean() {
+    let img = soft_circle(96);
+    let alpha = ascii_artinator_core::preprocess::alpha_channel(&image::DynamicImage::ImageRgba8(img.clone()));
+    // light all over, transparent parts too, so plain dithering would fill
+    // the background in
+    let gray = image::GrayImage::from_pixel(96, 96, image::Luma([200]));
+    let plain = ascii_artinator_core::dither_img(gray.clone());
+    assert!(lit_in_background(&plain, &img) > 0);
+    let aware = ascii_artinator_core::dither_alpha(gray, &alpha);
+    assert_eq!(lit_in_background(&aware, &img), 0);
+    assert!(aware.pixels().any(|dot| dot.0[0] != 0));
+}
+
+#[test]
+fn soft_edges_convert_clean() {
+    let opts = common::opts(&[("alpha_dither", "true"), ("blank_mode", "braille")]);
+    let img = image::DynamicImage::ImageRgba8(soft_circle(200));
+    let rows = ascii_artinator_core::convert_to_rows(img, &opts, None).unwrap();
+    // the corners are all background
+    for row in [rows.first().unwrap(), rows.last().unwrap()] {
+        assert!(row.starts_with('\u{2800}') && row.ends_with('\u{2800}'), "{}", row);
+    }
+    assert!(rows.iter().any(|row| row.chars().any(|c| c != '\u{2800}')));
+}
+
+#[test]
+fn opaque_images_are_unchanged() {
+    let img = common::open("grad16.png").to_rgb8();
+    let dynamic = image::DynamicImage::ImageRgb8(img);
+    let gray = ascii_artinator_core::to_gray(&dynamic);
+    let alpha = ascii_artinator_core::preprocess::alpha_channel(&dynamic);
+    assert_eq!(ascii_artinator_core::dither_alpha(gray.clone(), &alpha), ascii_artinator_core::dither_img(gray));
+}
+
+#[test]
+fn only_with_dither() {
+    for mode in ["edges", "rgbsplit"] {
+        let img = image::DynamicImage::ImageRgba8(soft_circle(32));
+        let rows = common::parse(&[("alpha_dither", "true"), ("mode", mode)])
+            .and_then(|opts| ascii_artinator_core::convert_to_rows(img, &opts, None));
+        assert!(rows.is_err());
+    }
+}
@@ -0,0 +1,42 @@
+//! `autocontrast=true` stretches the grays out to the full range, with
+//! `autocontrast_clip` percent at each end allowed past it.
+
+#![allow(clippy::needless_return)]
+
+use ascii_artinator_core::image::{GrayImage, Luma};
+use ascii_artinator_core::preprocess::auto_contrast;
+
+// 100 to 150, left to right
+fn dull() -> GrayImage {
+    return GrayImage::from_fn(51, 4, |x, _| Luma([100 + x as u8]));
+}
+
+fn range(gray_img: &GrayImage) -> (u8, u8) {
+    let levels = gray_img.pixels().map(|pix| pix.0[0]);
+    return (levels.clone().min().unwrap(), levels.max().unwrap());
+}
+
+#[test]
+fn stretches_to_the_full_range() {
+    let stretched = auto_contrast(dull(), 0.0);
+    assert_eq!(range(&stretched), (0, 255));
+    // and evenly, so the middle stays in the middle
+    assert!((stretched.get_pixel(25, 0).0[0] as i32 - 128).abs() <= 2);
+}
+
+#[test]
+fn clipping_ignores_outliers() {
+    let mut img = dull();
+    img.put_pixel(0, 0, Luma([0]));
+    img.put_pixel(50, 0, Luma([255]));
+    // without clipping they're the ends already, so nothing moves
+    assert_eq!(auto_contrast(img.clone(), 0.0), img);
+    let clipped = auto_contrast(img, 1.0);
+    assert!(clipped.get_pixel(1, 1).0[0] < 20, "{:?}", clipped.get_pixel(1, 1));
+}
+
+#[test]
+fn flat_is_left_alone() {
+    let flat = GrayImage::from_pixel(4, 4, Luma([120]));
+    assert_eq!(auto_contrast(flat.clone(), 0.0), flat);
+}
@@ -0,0 +1,24 @@
+//! `blank_mode` swaps the blank cells and nothing else.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+fn rows_with(query: &[(&str, &str)]) -> Vec<String> {
+    return common::rows_of("circle.png", query);
+}
+
+#[test]
+fn each_mode_swaps_only_blanks() {
+    // real blank braille can't be mistaken for a drawn cell, unlike the dot
+    let reference = rows_with(&[("blank_mode", "braille")]);
+    assert!(reference.iter().any(|row| row.contains('⠀')));
+    for (mode, blank) in [("dot", '⠁'), ("space", ' '), ("nbsp", '\u{a0}')] {
+        let expected: Vec<String> = reference.iter().map(|row| row.replace('⠀', &blank.to_string())).collect();
+        assert_eq!(rows_with(&[("blank_mode", mode)]), expected, "blank_mode={}", mode);
+    }
+}
+
+#[test]
+fn dot_is_the_default() {
+    assert_eq!(rows_with(&[]), rows_with(&[("blank_mode", "dot")]));
+}
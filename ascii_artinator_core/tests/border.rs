@@ -0,0 +1,61 @@
+//! `border=true` boxes the rows in, two rows and two columns more than the
+//! longest row, with short rows padded out to it. `border_style=dots` draws
+//! it in braille dots on the grid instead.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::rows::add_border;
+
+#[test]
+fn counts_the_corners() {
+    let plain = common::rows_of("circle.png", &[]);
+    let bordered = common::rows_of("circle.png", &[("border", "true")]);
+    assert_eq!(bordered.len(), plain.len() + 2);
+    let width = plain[0].chars().count();
+    for row in &bordered {
+        assert_eq!(row.chars().count(), width + 2, "{}", row);
+    }
+    assert_eq!(bordered[0], format!("┌{}┐", "─".repeat(width)));
+    assert_eq!(bordered[bordered.len() - 1], format!("└{}┘", "─".repeat(width)));
+    for (row, inner) in bordered[1..bordered.len() - 1].iter().zip(&plain) {
+        assert_eq!(*row, format!("│{}│", inner));
+    }
+}
+
+#[test]
+fn pads_short_rows() {
+    let rows = vec!["⣿⣿⣿".to_owned(), "⣿".to_owned(), String::new()];
+    assert_eq!(add_border(&rows), vec![
+        "┌───┐".to_owned(),
+        "│⣿⣿⣿│".to_owned(),
+        "│⣿⠀⠀│".to_owned(),
+        "│⠀⠀⠀│".to_owned(),
+        "└───┘".to_owned(),
+    ]);
+}
+
+#[test]
+fn colors_take_no_width() {
+    let rows = vec!["\u{1b}[31m⣿\u{1b}[0m⣿".to_owned(), "⣿".to_owned()];
+    let bordered = add_border(&rows);
+    assert_eq!(bordered[0], "┌──┐");
+    assert_eq!(bordered[2], "│⣿⠀│");
+}
+
+#[test]
+fn dots_go_on_the_grid() {
+    let plain = common::rows_of("circle.png", &[]);
+    let dotted = common::rows_of("circle.png", &[("border", "true"), ("border_style", "dots")]);
+    assert_eq!(dotted.len(), plain.len() + 2);
+    let width = plain[0].chars().count();
+    assert_eq!(dotted[0], format!("⡏{}⢹", "⠉".repeat(width)));
+    assert_eq!(dotted[dotted.len() - 1], format!("⣇{}⣸", "⣀".repeat(width)));
+    for (row, inner) in dotted[1..dotted.len() - 1].iter().zip(&plain) {
+        assert_eq!(*row, format!("⡇{}⢸", inner));
+    }
+    // no braille grid to draw on, so a box
+    let blocks = common::rows_of("circle.png", &[("charset", "quadrant"), ("border", "true"), ("border_style", "dots")]);
+    assert!(blocks[0].starts_with('┌'), "{}", blocks[0]);
+}
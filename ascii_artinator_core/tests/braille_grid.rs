@@ -0,0 +1,126 @@
+//! The dots of a `BrailleGrid`: where they land, and how they come back out
+//! as rows.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::braille::{gray_to_braille, BRAILLE_CHARS, BrailleGrid};
+use ascii_artinator_core::image::{GrayImage, Luma};
+
+#[test]
+fn new_is_blank() {
+    let grid = BrailleGrid::new(3, 2);
+    assert_eq!((grid.cols(), grid.rows()), (3, 2));
+    assert!(grid.cells.iter().flatten().all(|&val| val == 0));
+    assert_eq!(BrailleGrid::new(0, 0).cols(), 0);
+}
+
+#[test]
+fn dots_land_on_their_bits() {
+    // dot numbers 1 to 8, by position in the cell
+    let bits = [((0, 0), 0x01), ((0, 1), 0x02), ((0, 2), 0x04), ((1, 0), 0x08),
+        ((1, 1), 0x10), ((1, 2), 0x20), ((0, 3), 0x40), ((1, 3), 0x80)];
+    for ((x, y), bit) in bits {
+        let mut grid = BrailleGrid::new(2, 2);
+        grid.set_dot(x + 2, y + 4, true);
+        assert_eq!(grid.cells[1][1], bit, "({}, {})", x, y);
+        assert!(grid.get_dot(x + 2, y + 4));
+        assert!(!grid.get_dot(x, y));
+        grid.set_dot(x + 2, y + 4, false);
+        assert_eq!(grid.cells[1][1], 0);
+    }
+}
+
+#[test]
+fn from_dots_rounds_up() {
+    let mut img = GrayImage::new(3, 5);
+    img.put_pixel(2, 4, Luma([255]));
+    img.put_pixel(0, 0, Luma([80]));
+    let grid = BrailleGrid::from_dots(&img);
+    assert_eq!((grid.cols(), grid.rows()), (2, 2));
+    assert!(grid.get_dot(2, 4));
+    // only above 80 counts
+    assert!(!grid.get_dot(0, 0));
+}
+
+#[test]
+fn from_rows_skips_what_isnt_braille() {
+    let rows = vec!["\u{1b}[31m⣿⠁\u{1b}[0m".to_owned(), "│⠃".to_owned()];
+    let grid = BrailleGrid::from_rows(&rows);
+    assert_eq!(grid.cells, vec![vec![0xff, 0x01], vec![0x03, 0]]);
+}
+
+#[test]
+fn to_rows_round_trips() {
+    let rows = vec!["⣿⠁⠀".to_owned(), "⠃⠀⡀".to_owned()];
+    let grid = BrailleGrid::from_rows(&rows);
+    assert_eq!(grid.to_rows(BRAILLE_CHARS[0]), rows);
+    assert_eq!(grid.to_rows(' '), vec!["⣿⠁ ".to_owned(), "⠃ ⡀".to_owned()]);
+    assert_eq!(grid.to_string(), rows.join("\n"));
+}
+
+#[test]
+fn preview_keeps_short_grids() {
+    let grid = BrailleGrid { cells: vec![vec![0xff; 4]; 3] };
+    assert_eq!(grid.preview(3), grid);
+    let preview = grid.preview(1);
+    assert_eq!(preview.rows(), 2);
+    assert_eq!(preview.cells[0], grid.cells[0]);
+    assert_eq!(preview.to_rows(BRAILLE_CHARS[0])[1], "⠀⠄⠄⠄");
+}
+
+fn grid_of(rows: &[&str]) -> BrailleGrid {
+    return BrailleGrid::from_rows(&rows.iter().map(|&row| row.to_owned()).collect::<Vec<_>>());
+}
+
+#[test]
+fn trim_blank_keeps_the_dots() {
+    let grid = grid_of(&["⠀⠀⠀⠀", "⠀⠁⠀⠀", "⠀⠀⡀⠀", "⠀⠀⠀⠀"]);
+    assert_eq!(grid.trim_blank(), grid_of(&["⠁⠀", "⠀⡀"]));
+    // nothing to trim off a full one, and nothing left of a blank one
+    let full = grid_of(&["⣿⣿", "⣿⣿"]);
+    assert_eq!(full.trim_blank(), full);
+    let blank = grid_of(&["⠀⠀", "⠀⠀"]).trim_blank();
+    assert_eq!((blank.cols(), blank.rows()), (0, 0));
+}
+
+#[test]
+fn add_border_rings_the_cells() {
+    let bordered = grid_of(&["⣿"]).add_border();
+    assert_eq!(bordered.to_rows(BRAILLE_CHARS[0]), vec!["⡏⠉⢹", "⡇⣿⢸", "⣇⣀⣸"]);
+    let blank = grid_of(&["⠀⠀"]).add_border();
+    assert_eq!(blank.to_rows(BRAILLE_CHARS[0]), vec!["⡏⠉⠉⢹", "⡇⠀⠀⢸", "⣇⣀⣀⣸"]);
+}
+
+#[test]
+fn transpose_swaps_the_dots() {
+    // a cell is 2 dots by 4, so one cell across becomes two
+    let transposed = grid_of(&["⠈"]).transpose();
+    assert_eq!(transposed.to_rows(BRAILLE_CHARS[0]), vec!["⠂⠀"]);
+    assert_eq!(grid_of(&["⡀"]).transpose().to_rows(BRAILLE_CHARS[0]), vec!["⠀⠈"]);
+    // square in dots, so twice over is where it started
+    let grid = grid_of(&["⠁⢰"]);
+    assert_eq!(grid.transpose().transpose(), grid);
+    assert!(grid.transpose().get_dot(0, 0));
+}
+
+#[test]
+fn pipeline_trims_on_the_grid() {
+    // a lit square in the middle of a dark frame
+    let img = GrayImage::from_fn(80, 80, |x, y| Luma([if (30..50).contains(&x) && (30..50).contains(&y) { 255 } else { 0 }]));
+    let convert = |query: &[(&str, &str)]| {
+        let opts = common::opts(query);
+        return ascii_artinator_core::convert_to_rows(img.clone().into(), &opts, None).unwrap();
+    };
+    let full = convert(&[("blank_mode", "braille"), ("terminal_bg", "dark")]);
+    let trimmed = convert(&[("blank_mode", "braille"), ("terminal_bg", "dark"), ("trim_blank", "true")]);
+    assert_eq!(trimmed, BrailleGrid::from_rows(&full).trim_blank().to_rows(BRAILLE_CHARS[0]));
+    assert!(trimmed.len() < full.len() && trimmed[0].chars().count() < full[0].chars().count());
+}
+
+#[test]
+fn gray_to_braille_is_the_grid() {
+    let img = GrayImage::from_fn(7, 9, |x, y| Luma([if (x + y) % 3 == 0 { 255 } else { 0 }]));
+    assert_eq!(gray_to_braille(&img), BrailleGrid::from_dots(&img).to_rows(BRAILLE_CHARS[1]));
+}
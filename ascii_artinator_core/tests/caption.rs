@@ -0,0 +1,46 @@
+//! `top` and `bottom` captions wrap to the image's width and sit centered
+//! on it.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::caption::{caption_dots, wrap};
+
+#[test]
+fn wraps_between_words() {
+    assert_eq!(wrap("hello there general kenobi", 10), ["hello", "there", "general", "kenobi"]);
+    assert_eq!(wrap("a b c d", 3), ["a b", "c d"]);
+    // and through a word too long for a line
+    assert_eq!(wrap("abcdefgh", 3), ["abc", "def", "gh"]);
+}
+
+#[test]
+fn long_caption_takes_more_rows() {
+    // 40 dots is 10 chars of the font, so a word a line, 6 dots each
+    let short = caption_dots("HI", 40, 4);
+    let long = caption_dots("HELLO THERE GENERAL KENOBI", 40, 4);
+    assert_eq!(short.height() / 4, 2);
+    assert_eq!(long.height() / 4, 6);
+    assert_eq!(long.width(), 40);
+}
+
+#[test]
+fn short_caption_is_centered() {
+    let dots = caption_dots("HI", 40, 4);
+    let lit: Vec<u32> = dots.enumerate_pixels().filter(|(_, _, pix)| pix.0[0] > 0).map(|(x, _, _)| x).collect();
+    let (left, right) = (*lit.iter().min().unwrap(), *lit.iter().max().unwrap());
+    assert!(left.abs_diff(39 - right) <= 1, "{} {}", left, right);
+}
+
+#[test]
+fn wraps_in_the_conversion() {
+    let rows_with = |top: &str| common::rows_of("circle.png", &[("cols", "10"), ("rows", "5"), ("top", top)]);
+    let plain = common::rows_of("circle.png", &[("cols", "10"), ("rows", "5")]);
+    let short = rows_with("HI");
+    let long = rows_with("HELLO THERE GENERAL KENOBI");
+    assert!(long.len() > short.len(), "{} {}", long.len(), short.len());
+    // the image is still all there, under the caption
+    assert_eq!(long[long.len() - plain.len()..], plain[..]);
+    assert!(long.iter().all(|row| row.chars().count() == 10));
+}
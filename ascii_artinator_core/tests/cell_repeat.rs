@@ -0,0 +1,37 @@
+//! `cell_repeat=N` repeats every glyph N times, and leaves the color
+//! escapes where they were.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::rows::{repeat_cells, visible_chars};
+
+#[test]
+fn two_doubles_the_rows() {
+    let plain = common::rows_of("circle.png", &[]);
+    let doubled = common::rows_of("circle.png", &[("cell_repeat", "2")]);
+    assert_eq!(doubled.len(), plain.len());
+    for (row, plain) in doubled.iter().zip(&plain) {
+        assert_eq!(row.chars().count(), plain.chars().count() * 2);
+        let expected: String = plain.chars().flat_map(|c| [c, c]).collect();
+        assert_eq!(*row, expected);
+    }
+}
+
+#[test]
+fn keeps_color_runs() {
+    let rows = vec!["\x1b[31m⣿⠁\x1b[0m⠃".to_owned()];
+    assert_eq!(repeat_cells(rows, 3), vec!["\x1b[31m⣿⣿⣿⠁⠁⠁\x1b[0m⠃⠃⠃".to_owned()]);
+}
+
+#[test]
+fn colored_conversion_widens() {
+    let query = [("mode", "rgbsplit"), ("color", "always")];
+    let plain = common::rows_of("redblue.png", &query);
+    let doubled = common::rows_of("redblue.png", &[query[0], query[1], ("cell_repeat", "2")]);
+    for (row, plain) in doubled.iter().zip(&plain) {
+        assert_eq!(visible_chars(row).count(), visible_chars(plain).count() * 2);
+        assert_eq!(row.matches('\x1b').count(), plain.matches('\x1b').count());
+    }
+}
@@ -0,0 +1,30 @@
+//! `col_gap=true` puts a space between every two cells, which the border
+//! counts in.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::rows::add_col_gaps;
+
+#[test]
+fn lines_are_cols_times_two_minus_one() {
+    let rows = common::rows_of("circle.png", &[("cols", "12"), ("rows", "4"), ("col_gap", "true")]);
+    assert_eq!(rows.len(), 4);
+    for row in &rows {
+        assert_eq!(row.chars().count(), 12 * 2 - 1, "{}", row);
+        assert!(row.chars().skip(1).step_by(2).all(|c| c == ' '), "{}", row);
+    }
+}
+
+#[test]
+fn keeps_the_colors_out_of_it() {
+    let rows = vec!["\x1b[31m⣿⠁\x1b[0m⠃".to_owned()];
+    assert_eq!(add_col_gaps(rows), vec!["\x1b[31m⣿ ⠁\x1b[0m ⠃".to_owned()]);
+}
+
+#[test]
+fn border_fits_the_gaps() {
+    let rows = common::rows_of("circle.png", &[("cols", "12"), ("rows", "4"), ("col_gap", "true"), ("border", "true")]);
+    assert!(rows.iter().all(|row| row.chars().count() == 12 * 2 - 1 + 2));
+}
@@ -0,0 +1,48 @@
+//! `collapse_blanks=N` shrinks runs of N or more blank rows to one, and
+//! leaves shorter runs alone.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, GrayImage, Luma};
+use ascii_artinator_core::rows::collapse_blank_rows;
+
+const FULL: &str = "⣿⣿";
+const BLANK: &str = "⠀⠀";
+
+fn rows(pattern: &str) -> Vec<String> {
+    return pattern.chars().map(|c| if c == '#' { FULL } else { BLANK }.to_owned()).collect();
+}
+
+#[test]
+fn five_blank_rows_become_one() {
+    assert_eq!(collapse_blank_rows(rows("#.....#"), 3), rows("#.#"));
+    assert_eq!(collapse_blank_rows(rows("#.....#"), 5), rows("#.#"));
+    assert_eq!(collapse_blank_rows(rows("#.....#"), 6), rows("#.....#"));
+}
+
+#[test]
+fn a_single_blank_row_stays() {
+    assert_eq!(collapse_blank_rows(rows("#.#..#"), 3), rows("#.#..#"));
+    assert_eq!(collapse_blank_rows(rows("#.#"), 1), rows("#.#"));
+}
+
+#[test]
+fn runs_at_the_ends_collapse_too() {
+    assert_eq!(collapse_blank_rows(rows("...#...."), 3), rows(".#."));
+    assert_eq!(collapse_blank_rows(rows("....."), 2), rows("."));
+}
+
+#[test]
+fn collapses_the_conversion() {
+    // lit stripes at the top and bottom, with 20 dark pixels between
+    let img = GrayImage::from_fn(8, 28, |_, y| Luma([if !(4..24).contains(&y) { 255 } else { 0 }]));
+    let opts = common::opts(&[("blank_mode", "braille"), ("collapse_blanks", "3")]);
+    let converted = ascii_artinator_core::convert_to_rows(DynamicImage::ImageLuma8(img.clone()), &opts, None).unwrap();
+    let plain = common::opts(&[("blank_mode", "braille")]);
+    let uncollapsed = ascii_artinator_core::convert_to_rows(DynamicImage::ImageLuma8(img), &plain, None).unwrap();
+    assert!(uncollapsed.len() >= converted.len() + 2, "{:?}", converted);
+    assert_eq!(converted.first(), uncollapsed.first());
+    assert_eq!(converted.last(), uncollapsed.last());
+}
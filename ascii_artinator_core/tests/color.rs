@@ -0,0 +1,50 @@
+//! Colored output has to end on a reset, and `color=never` has to leave the
+//! dots alone while dropping the colors.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::color::RESET;
+
+fn rows_with(query: &[(&str, &str)]) -> Vec<String> {
+    return common::rows_of("redblue.png", query);
+}
+
+#[test]
+fn colored_output_ends_with_reset() {
+    for extra in [&[][..], &[("border", "true")], &[("wrap", "7")], &[("layout", "vertical")]] {
+        let query: Vec<_> = [("mode", "rgbsplit")].into_iter().chain(extra.iter().copied()).collect();
+        let rows = rows_with(&query);
+        assert!(rows.iter().any(|row| row.contains('\x1b')), "{:?}", query);
+        assert!(rows.last().unwrap().ends_with(RESET), "{:?}", query);
+    }
+}
+
+#[test]
+fn never_drops_only_the_colors() {
+    let colored = rows_with(&[("mode", "rgbsplit"), ("color", "always")]);
+    let plain = rows_with(&[("mode", "rgbsplit"), ("color", "never")]);
+    assert!(plain.iter().all(|row| !row.contains('\x1b')));
+    let visible = |rows: &[String]| -> Vec<String> {
+        return rows.iter().map(|row| ascii_artinator_core::rows::visible_chars(row).collect()).collect();
+    };
+    assert_eq!(visible(&colored), visible(&plain));
+}
+
+#[test]
+fn resolving() {
+    let resolved = |color: Option<&str>, no_color: bool, terminal: bool| -> bool {
+        let query: Vec<_> = color.map(|color| ("color", color)).into_iter().collect();
+        let mut opts = common::opts(&query);
+        opts.resolve_color(no_color, terminal);
+        return opts.colored();
+    };
+    assert!(resolved(None, false, false));
+    assert!(!resolved(None, true, true));
+    assert!(resolved(Some("always"), true, false));
+    assert!(!resolved(Some("never"), false, true));
+    assert!(resolved(Some("auto"), false, true));
+    assert!(!resolved(Some("auto"), false, false));
+    assert!(!resolved(Some("auto"), true, true));
+}
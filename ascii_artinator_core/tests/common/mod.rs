@@ -0,0 +1,40 @@
+//! What the core's tests share: the bundled images under `golden/`, and
+//! options parsed the way the server parses a query string.
+
+// every test file gets all of this, whether it uses all of it or not
+#![allow(dead_code, clippy::needless_return)]
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use ascii_artinator_core::{image, ConvertError, ConvertOptions};
+
+/// Where a bundled image is.
+pub fn golden(name: &str) -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name);
+}
+
+/// A bundled image, decoded.
+pub fn open(name: &str) -> image::DynamicImage {
+    return image::open(golden(name)).unwrap();
+}
+
+/// `[("cols", "20")]` as the query string map `parse_options` takes.
+pub fn params(query: &[(&str, &str)]) -> HashMap<String, String> {
+    return query.iter().map(|(key, val)| (key.to_string(), val.to_string())).collect();
+}
+
+/// Options from a query, over the defaults.
+pub fn parse(query: &[(&str, &str)]) -> Result<ConvertOptions, ConvertError> {
+    return ascii_artinator_core::parse_options(&params(query), &ConvertOptions::default());
+}
+
+/// Same as [`parse`], for queries that have to be good.
+pub fn opts(query: &[(&str, &str)]) -> ConvertOptions {
+    return parse(query).unwrap();
+}
+
+/// The rows a bundled image converts to with these options.
+pub fn rows_of(name: &str, query: &[(&str, &str)]) -> Vec<String> {
+    return ascii_artinator_core::convert_to_rows(open(name), &opts(query), None).unwrap();
+}
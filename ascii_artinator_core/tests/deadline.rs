@@ -0,0 +1,81 @@
+//! The deadline is checked inside the slow loops too, so a conversion that
+//! started in time still gives up partway instead of running on to the end.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ascii_artinator_core::image::{codecs::gif::GifEncoder, DynamicImage, Frame, GrayImage, Luma, RgbaImage};
+use ascii_artinator_core::options::Dither;
+use ascii_artinator_core::ConvertError;
+
+fn noise(width: u32, height: u32) -> GrayImage {
+    let mut seed: u32 = 1;
+    return GrayImage::from_fn(width, height, |_, _| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        return Luma([(seed >> 24) as u8]);
+    });
+}
+
+fn gone_by() -> Option<Instant> {
+    return Some(Instant::now() - Duration::from_millis(1));
+}
+
+fn far_off() -> Option<Instant> {
+    return Some(Instant::now() + Duration::from_secs(600));
+}
+
+#[test]
+fn dither_gives_up() {
+    for dither in [Dither::Sierra, Dither::Floyd, Dither::Ordered] {
+        let dithered = ascii_artinator_core::dither_as(noise(64, 64), dither, None, gone_by());
+        assert_eq!(dithered.unwrap_err(), ConvertError::DeadlineExceeded, "{:?}", dither);
+    }
+    // with time to do it, it's the same dither as ever
+    let dithered = ascii_artinator_core::dither_as(noise(64, 64), Dither::Sierra, None, far_off()).unwrap();
+    assert_eq!(dithered, ascii_artinator_core::dither_img(noise(64, 64)));
+}
+
+#[test]
+fn dither_gives_up_partway() {
+    // far too big to get through in a few milliseconds
+    let deadline = Some(Instant::now() + Duration::from_millis(5));
+    let dithered = ascii_artinator_core::dither_as(noise(3000, 3000), Dither::Sierra, None, deadline);
+    assert_eq!(dithered.unwrap_err(), ConvertError::DeadlineExceeded);
+}
+
+#[test]
+fn center_weight_gives_up() {
+    let img = DynamicImage::ImageLuma8(noise(64, 64));
+    let weighted = ascii_artinator_core::preprocess::center_weight(img.clone(), 0.5, 0.5, 1, gone_by());
+    assert_eq!(weighted.unwrap_err(), ConvertError::DeadlineExceeded);
+    assert!(ascii_artinator_core::preprocess::center_weight(img, 0.5, 0.5, 1, far_off()).is_ok());
+}
+
+#[test]
+fn frames_give_up() {
+    let mut gif = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut gif);
+        for _ in 0..3 {
+            encoder.encode_frame(Frame::new(RgbaImage::new(4, 4))).unwrap();
+        }
+    }
+    let max_pixels = ascii_artinator_core::DEFAULT_MAX_PIXELS;
+    let frames = ascii_artinator_core::frames::gif_frames(&gif, 0, 3, max_pixels, gone_by());
+    assert_eq!(frames.unwrap_err(), ConvertError::DeadlineExceeded);
+    let (frames, total) = ascii_artinator_core::frames::gif_frames(&gif, 0, 3, max_pixels, far_off()).unwrap();
+    assert_eq!((frames.len(), total), (3, 3));
+}
+
+#[test]
+fn conversion_gives_up_partway() {
+    // gets past the checkpoint at the start, but not through the resize
+    let img = DynamicImage::ImageLuma8(noise(4000, 4000));
+    let mut opts = common::opts(&[]);
+    opts.deadline = Some(Instant::now() + Duration::from_millis(20));
+    let rows = ascii_artinator_core::convert_to_rows(img, &opts, None);
+    assert_eq!(rows.unwrap_err(), ConvertError::DeadlineExceeded);
+}
@@ -0,0 +1,95 @@
+//! Files of a few bytes that claim to be huge get turned away by their
+//! header, before anything gets allocated for the pixels.
+
+#![allow(clippy::needless_return)]
+
+use ascii_artinator_core::image::ImageFormat;
+use ascii_artinator_core::{decode_with_hints, ConvertError, ConvertOptions, DEFAULT_MAX_PIXELS};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { crc >> 1 ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    return !crc;
+}
+
+fn chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut out = (data.len() as u32).to_be_bytes().to_vec();
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+    return out;
+}
+
+/// The header of a gray PNG of `width` by `height`, and a speck of pixel
+/// data that's nowhere near enough for it.
+fn png_claiming(width: u32, height: u32) -> Vec<u8> {
+    let mut ihdr = width.to_be_bytes().to_vec();
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // 8 bit gray, no interlace
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]);
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png.append(&mut chunk(b"IHDR", &ihdr));
+    png.append(&mut chunk(b"IDAT", &[0x78, 0x9c, 0x63, 0x60, 0, 0, 0, 2, 0, 1]));
+    png.append(&mut chunk(b"IEND", &[]));
+    return png;
+}
+
+/// A GIF whose screen and only frame are `width` by `height`, with a
+/// single pixel's worth of data.
+fn gif_claiming(width: u16, height: u16) -> Vec<u8> {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&width.to_le_bytes());
+    gif.extend_from_slice(&height.to_le_bytes());
+    // a two color table, black and white
+    gif.extend_from_slice(&[0x80, 0, 0, 0, 0, 0, 0xff, 0xff, 0xff]);
+    gif.push(b',');
+    gif.extend_from_slice(&[0, 0, 0, 0]);
+    gif.extend_from_slice(&width.to_le_bytes());
+    gif.extend_from_slice(&height.to_le_bytes());
+    gif.extend_from_slice(&[0, 2, 2, 0x44, 0x01, 0, b';']);
+    return gif;
+}
+
+fn decode(bytes: &[u8], format: ImageFormat) -> Result<(u32, u32), ConvertError> {
+    let img = decode_with_hints(bytes, Some(format), None, DEFAULT_MAX_PIXELS, &ConvertOptions::default())?;
+    return Ok((img.width(), img.height()));
+}
+
+#[test]
+fn huge_png_is_too_large() {
+    let bomb = png_claiming(100_000, 100_000);
+    assert!(bomb.len() < 100);
+    assert!(matches!(decode(&bomb, ImageFormat::Png), Err(ConvertError::TooLarge)));
+    // the sniffed format wins over a wrong hint, and is still caught
+    assert!(matches!(decode(&bomb, ImageFormat::Jpeg), Err(ConvertError::TooLarge)));
+}
+
+#[test]
+fn huge_gif_is_too_large() {
+    let bomb = gif_claiming(u16::MAX, u16::MAX);
+    assert!(bomb.len() < 100);
+    assert!(matches!(decode(&bomb, ImageFormat::Gif), Err(ConvertError::TooLarge)));
+}
+
+#[test]
+fn the_budget_is_the_callers() {
+    let bomb = png_claiming(1000, 1000);
+    let small = decode_with_hints(&bomb, None, None, 999_999, &ConvertOptions::default());
+    assert!(matches!(small, Err(ConvertError::TooLarge)));
+    // within budget it's only broken, which is a different error
+    let big = decode_with_hints(&bomb, None, None, 1_000_000, &ConvertOptions::default());
+    assert!(matches!(big, Err(ConvertError::Decode)), "{:?}", big.map(|img| img.width()));
+}
+
+#[test]
+fn small_ones_still_decode() {
+    assert_eq!(decode(&png_claiming(1, 1), ImageFormat::Png).unwrap(), (1, 1));
+    assert_eq!(decode(&gif_claiming(1, 1), ImageFormat::Gif).unwrap(), (1, 1));
+}
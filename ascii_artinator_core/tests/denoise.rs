@@ -0,0 +1,18 @@
+//! The median filter behind `denoise`.
+
+use ascii_artinator_core::image::{DynamicImage, GrayImage, Luma};
+
+#[test]
+fn lone_speck_is_removed() {
+    let mut gray = GrayImage::from_pixel(9, 9, Luma([40]));
+    gray.put_pixel(4, 4, Luma([255]));
+    let denoised = ascii_artinator_core::preprocess::median_filter(&DynamicImage::ImageLuma8(gray), 1).to_luma8();
+    assert!(denoised.pixels().all(|pix| pix.0[0] == 40));
+}
+
+#[test]
+fn edges_stay_sharp() {
+    let gray = GrayImage::from_fn(8, 8, |x, _| if x < 4 { Luma([0]) } else { Luma([255]) });
+    let denoised = ascii_artinator_core::preprocess::median_filter(&DynamicImage::ImageLuma8(gray.clone()), 1);
+    assert_eq!(denoised.to_luma8(), gray);
+}
@@ -0,0 +1,52 @@
+//! 16-bit images keep their precision until the luminance is worked out, so
+//! subtle gradients still come out as gradients.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, ImageBuffer, ImageOutputFormat, Luma};
+
+// the whole range over 256 pixels, in steps far too fine for 8 bits
+fn gradient16() -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    return ImageBuffer::from_fn(256, 32, |x, y| Luma([(x * 256 + y * 7) as u16]));
+}
+
+fn rounded(val: u16) -> u8 {
+    return ((val as u32 * 255 + 32767) / 65535) as u8;
+}
+
+#[test]
+fn luminance_is_rounded_not_cut() {
+    let gray = ascii_artinator_core::to_gray(&DynamicImage::ImageLuma16(gradient16()));
+    for (x, y, pix) in gradient16().enumerate_pixels() {
+        assert!(gray.get_pixel(x, y).0[0].abs_diff(rounded(pix.0[0])) <= 1, "({}, {})", x, y);
+    }
+    // just under halfway up to the next 8-bit level rounds down, just over it up
+    let img = DynamicImage::ImageLuma16(ImageBuffer::from_fn(2, 1, |x, _| Luma([if x == 0 { 257 * 100 + 120 } else { 257 * 100 + 140 }])));
+    assert_eq!(ascii_artinator_core::to_gray(&img).as_raw(), &[100, 101]);
+}
+
+#[test]
+fn png_decodes_at_16_bits() {
+    let mut png = Vec::new();
+    DynamicImage::ImageLuma16(gradient16())
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageOutputFormat::Png)
+        .unwrap();
+    let opts = common::opts(&[]);
+    let img = ascii_artinator_core::decode_with_hints(&png, None, None, ascii_artinator_core::DEFAULT_MAX_PIXELS, &opts).unwrap();
+    // every one of the 16 bits made it
+    assert_eq!(img.as_luma16(), Some(&gradient16()));
+}
+
+#[test]
+fn gradient_fills_up_left_to_right() {
+    let rows = ascii_artinator_core::convert_to_rows(DynamicImage::ImageLuma16(gradient16()), &common::opts(&[("blank_mode", "braille"), ("terminal_bg", "dark")]), None).unwrap();
+    let grid = ascii_artinator_core::braille::BrailleGrid::from_rows(&rows);
+    let quarter = grid.cols() / 4;
+    let lit_in = |cols: std::ops::Range<usize>| -> u32 {
+        return grid.cells.iter().flat_map(|row| row[cols.clone()].iter()).map(|val| val.count_ones()).sum();
+    };
+    let quarters: Vec<u32> = (0..4).map(|q| lit_in(q * quarter..(q + 1) * quarter)).collect();
+    assert!(quarters.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", quarters);
+}
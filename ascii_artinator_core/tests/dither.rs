@@ -0,0 +1,42 @@
+//! `dither=auto` goes by how wide the output is, and the explicit choices do
+//! what they say.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::Dither;
+
+fn rows_with(query: &[(&str, &str)]) -> Vec<String> {
+    return common::rows_of("grad16.png", query);
+}
+
+#[test]
+fn auto_picks_by_width() {
+    assert_eq!(Dither::Auto.resolve(20), Dither::Ordered);
+    assert_eq!(Dither::Auto.resolve(200), Dither::Floyd);
+    assert_eq!(Dither::Sierra.resolve(20), Dither::Sierra);
+}
+
+#[test]
+fn auto_converts_like_what_it_picks() {
+    let small = [("cols", "20"), ("rows", "6")];
+    let big = [("src_width", "200"), ("src_height", "60")];
+    for (size, picked) in [(small, "ordered"), (big, "floyd")] {
+        let auto = rows_with(&[size[0], size[1], ("dither", "auto")]);
+        assert_eq!(auto, rows_with(&[size[0], size[1], ("dither", picked)]), "{:?}", size);
+        assert_eq!(auto[0].chars().count(), if picked == "ordered" { 20 } else { 100 });
+    }
+}
+
+#[test]
+fn default_is_unchanged() {
+    assert_eq!(rows_with(&[]), rows_with(&[("dither", "sierra")]));
+    assert_ne!(rows_with(&[]), rows_with(&[("dither", "floyd")]));
+    assert_ne!(rows_with(&[]), rows_with(&[("dither", "ordered")]));
+}
+
+#[test]
+fn only_with_dither_mode() {
+    assert!(common::parse(&[("dither", "auto"), ("mode", "edges")]).is_err());
+}
@@ -0,0 +1,43 @@
+//! `edge_blend` lights the edges on top of the usual dots: none of them at
+//! 0, all that `mode=edges` finds at 1.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+fn rows_with(query: &[(&str, &str)]) -> Vec<String> {
+    let mut query = query.to_vec();
+    query.extend([("cols", "30"), ("rows", "12"), ("blank_mode", "braille")]);
+    return common::rows_of("circle.png", &query);
+}
+
+// the dots of each cell, as bits
+fn dots(rows: &[String]) -> Vec<u32> {
+    return rows.iter().flat_map(|row| row.chars()).map(|c| c as u32 - 0x2800).collect();
+}
+
+#[test]
+fn zero_is_the_usual_output() {
+    assert_eq!(rows_with(&[("edge_blend", "0")]), rows_with(&[]));
+}
+
+#[test]
+fn one_has_every_edge_and_every_usual_dot() {
+    let blended = dots(&rows_with(&[("edge_blend", "1")]));
+    let edges = dots(&rows_with(&[("mode", "edges")]));
+    let usual = dots(&rows_with(&[]));
+    assert!(edges.iter().any(|&cell| cell != 0));
+    assert_eq!(blended.len(), edges.len());
+    for ((blended, edges), usual) in blended.iter().zip(&edges).zip(&usual) {
+        assert_eq!(blended & (edges | usual), edges | usual);
+    }
+    // there are edges outside the usual dots, or this shows nothing
+    assert_ne!(blended, usual);
+}
+
+#[test]
+fn only_works_with_dither_between_0_and_1() {
+    for query in [[("edge_blend", "1.5"), ("mode", "dither")], [("edge_blend", "0.5"), ("mode", "edges")]] {
+        assert!(matches!(common::parse(&query), Err(ascii_artinator_core::ConvertError::BadOption(_))), "{:?}", query);
+    }
+}
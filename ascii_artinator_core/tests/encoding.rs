@@ -0,0 +1,43 @@
+//! `encoding=escaped` is plain ascii, and reading its escapes back gives the
+//! same codepoints as the UTF-8 output.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::braille::BRAILLE_CHARS;
+
+// the escapes are the same as JSON's, so a JSON string reads them back
+fn unescape(escaped: &str) -> String {
+    return serde_json::from_str(&format!("\"{}\"", escaped.replace('\n', "\\n"))).unwrap();
+}
+
+#[test]
+fn escaped_is_ascii() {
+    let escaped = ascii_artinator_core::escape_unicode(&BRAILLE_CHARS.iter().collect::<String>());
+    assert!(escaped.is_ascii());
+    assert!(escaped.starts_with("\\u2800\\u2801"));
+    assert!(escaped.ends_with("\\u28ff"));
+}
+
+#[test]
+fn round_trips_every_braille_char() {
+    let all: String = BRAILLE_CHARS.iter().collect();
+    assert_eq!(unescape(&ascii_artinator_core::escape_unicode(&all)), all);
+    // past the BMP it takes a surrogate pair
+    assert_eq!(ascii_artinator_core::escape_unicode("🐱"), "\\ud83d\\udc31");
+    assert_eq!(unescape("\\ud83d\\udc31"), "🐱");
+}
+
+#[test]
+fn round_trips_the_conversion() {
+    let convert = |encoding: &str| {
+        let opts = common::opts(&[("encoding", encoding), ("charset", "braille")]);
+        let text = ascii_artinator_core::convert(common::open("circle.png"), &opts).unwrap();
+        return ascii_artinator_core::encode(text, opts.encoding);
+    };
+    let (utf8, escaped) = (convert("utf8"), convert("escaped"));
+    assert!(escaped.is_ascii());
+    assert!(!utf8.is_ascii());
+    assert_eq!(unescape(&escaped), utf8);
+}
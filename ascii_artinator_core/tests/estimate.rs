@@ -0,0 +1,65 @@
+//! The estimate has to match what converting really gives, for the options
+//! that change the size.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::Format;
+
+fn check(image: &str, query: &[(&str, &str)]) {
+    let bytes = std::fs::read(common::golden(image)).unwrap();
+    let (width, height) = ascii_artinator_core::estimate::image_dimensions(&bytes, None, None).unwrap();
+    let opts = common::opts(query);
+    let estimate = ascii_artinator_core::estimate::estimate_size(width, height, &opts).unwrap();
+    let rows = common::rows_of(image, query);
+    assert_eq!(estimate.rows, rows.len(), "rows for {:?}", query);
+    assert_eq!(estimate.cols, ascii_artinator_core::rows::max_width(&rows), "cols for {:?}", query);
+    let text = ascii_artinator_core::format_rows(&rows, opts.format.unwrap_or(Format::Text));
+    let encoded = ascii_artinator_core::encode(text, opts.encoding);
+    assert_eq!(estimate.bytes, Some(encoded.len()), "bytes for {:?}", query);
+}
+
+#[test]
+fn dimensions_come_from_the_header() {
+    let bytes = std::fs::read(common::golden("circle.png")).unwrap();
+    let img = common::open("circle.png");
+    let dimensions = ascii_artinator_core::estimate::image_dimensions(&bytes, None, None).unwrap();
+    assert_eq!(dimensions, (img.width(), img.height()));
+}
+
+#[test]
+fn not_an_image_fails() {
+    assert!(ascii_artinator_core::estimate::image_dimensions(b"not an image", None, None).is_err());
+}
+
+#[test]
+fn matches_plain_conversion() {
+    check("circle.png", &[]);
+    check("grad16.png", &[]);
+}
+
+#[test]
+fn matches_resizing_options() {
+    check("circle.png", &[("aspect", "2")]);
+    check("circle.png", &[("cols", "12"), ("rows", "5")]);
+    check("grad16.png", &[("src_width", "30"), ("src_height", "17")]);
+    check("circle.png", &[("center_weight", "2")]);
+    check("grad16.png", &[("rotate", "90")]);
+    check("grad16.png", &[("rotate", "30")]);
+}
+
+#[test]
+fn matches_layout_options() {
+    check("circle.png", &[("border", "true")]);
+    check("circle.png", &[("wrap", "10")]);
+    check("circle.png", &[("top", "hi"), ("bottom", "there")]);
+    check("circle.png", &[("format", "jsonl")]);
+}
+
+#[test]
+fn render_png_has_no_byte_estimate() {
+    let opts = common::opts(&[("format", "render.png")]);
+    let estimate = ascii_artinator_core::estimate::estimate_size(64, 64, &opts).unwrap();
+    assert_eq!(estimate.bytes, None);
+}
@@ -0,0 +1,44 @@
+//! The `fit` modes, with a landscape image going into a square.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, Rgba, RgbaImage};
+use ascii_artinator_core::{preprocess, Fit};
+
+fn landscape() -> DynamicImage {
+    return DynamicImage::ImageRgba8(RgbaImage::from_pixel(200, 100, Rgba([255, 255, 255, 255])));
+}
+
+#[test]
+fn contain_letterboxes() {
+    let fitted = preprocess::fit(landscape(), 40, 40, Fit::Contain, None).unwrap().to_rgba8();
+    assert_eq!(fitted.dimensions(), (40, 40));
+    // 20 rows of image in the middle, 10 of blank above and below
+    assert_eq!(fitted.get_pixel(20, 5).0[3], 0);
+    assert_eq!(fitted.get_pixel(20, 20).0, [255, 255, 255, 255]);
+    assert_eq!(fitted.get_pixel(20, 35).0[3], 0);
+}
+
+#[test]
+fn cover_fills() {
+    let fitted = preprocess::fit(landscape(), 40, 40, Fit::Cover, None).unwrap().to_rgba8();
+    assert_eq!(fitted.dimensions(), (40, 40));
+    assert!(fitted.pixels().all(|pix| pix.0 == [255, 255, 255, 255]));
+}
+
+#[test]
+fn stretch_fills() {
+    let fitted = preprocess::fit(landscape(), 40, 40, Fit::Stretch, None).unwrap().to_rgba8();
+    assert_eq!(fitted.dimensions(), (40, 40));
+    assert!(fitted.pixels().all(|pix| pix.0 == [255, 255, 255, 255]));
+}
+
+#[test]
+fn cols_and_rows_set_the_output_size() {
+    let opts = common::opts(&[("cols", "12"), ("rows", "7")]);
+    let rows = ascii_artinator_core::convert_to_rows(landscape(), &opts, None).unwrap();
+    assert_eq!(rows.len(), 7);
+    assert!(rows.iter().all(|row| row.chars().count() == 12));
+}
@@ -0,0 +1,40 @@
+//! `flip` mirrors the image before it's converted, and twice the same way
+//! is no flip at all.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+#[test]
+fn twice_is_the_original() {
+    let img = common::open("grad16.png");
+    let plain = common::rows_of("grad16.png", &[]);
+    for flip in ["h", "v", "both"] {
+        let once = ascii_artinator_core::preprocess::flip(img.clone(), common::opts(&[("flip", flip)]).flip);
+        let again = ascii_artinator_core::convert_to_rows(once, &common::opts(&[("flip", flip)]), None).unwrap();
+        assert_eq!(again, plain, "{}", flip);
+    }
+}
+
+#[test]
+fn h_swaps_the_sides() {
+    // red on the left, blue on the right, so the first color says which side
+    let first_color = |query: &[(&str, &str)]| {
+        let mut query = query.to_vec();
+        query.extend([("mode", "rgbsplit"), ("color", "always")]);
+        let rows = common::rows_of("redblue.png", &query);
+        return rows[rows.len() / 2].split('m').next().unwrap().to_owned();
+    };
+    assert_eq!(first_color(&[]), "\x1b[31");
+    assert_eq!(first_color(&[("flip", "h")]), "\x1b[34");
+    assert_eq!(first_color(&[("flip", "v")]), "\x1b[31");
+}
+
+#[test]
+fn v_and_h_make_both() {
+    let img = common::open("circle.png");
+    let opts = |flip: &str| common::opts(&[("flip", flip)]).flip;
+    let preprocess = ascii_artinator_core::preprocess::flip;
+    let both = preprocess(img.clone(), opts("both"));
+    assert_eq!(preprocess(preprocess(img, opts("h")), opts("v")).to_rgba8(), both.to_rgba8());
+}
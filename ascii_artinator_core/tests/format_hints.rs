@@ -0,0 +1,50 @@
+//! The magic bytes say what an image is when they say anything; otherwise
+//! the link's extension goes before the content type.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, ImageFormat, ImageOutputFormat, RgbaImage};
+use ascii_artinator_core::{decode_with_hints, format_candidates, ConvertError, DEFAULT_MAX_PIXELS};
+
+fn encoded(format: ImageOutputFormat) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgba8(RgbaImage::from_fn(8, 8, |x, _| ascii_artinator_core::image::Rgba([x as u8 * 30, 0, 0, 255])))
+        .write_to(&mut std::io::Cursor::new(&mut bytes), format)
+        .unwrap();
+    return bytes;
+}
+
+fn decode(bytes: &[u8], mime: Option<ImageFormat>, ext: Option<ImageFormat>) -> Result<(u32, u32), ConvertError> {
+    let img = decode_with_hints(bytes, mime, ext, DEFAULT_MAX_PIXELS, &common::opts(&[]))?;
+    return Ok((img.width(), img.height()));
+}
+
+#[test]
+fn mislabeled_gif_decodes() {
+    let gif = encoded(ImageOutputFormat::Gif);
+    // said to be a PNG, linked as a GIF, and the bytes agree with the link
+    assert_eq!(format_candidates(&gif, Some(ImageFormat::Png), Some(ImageFormat::Gif)), [ImageFormat::Gif]);
+    assert_eq!(decode(&gif, Some(ImageFormat::Png), Some(ImageFormat::Gif)).unwrap(), (8, 8));
+    // the magic bytes win even over the extension
+    assert_eq!(decode(&gif, None, Some(ImageFormat::Jpeg)).unwrap(), (8, 8));
+}
+
+#[test]
+fn extension_breaks_the_tie() {
+    // TGA has no magic bytes, so it comes down to the hints
+    let tga = encoded(ImageOutputFormat::Tga);
+    assert_eq!(format_candidates(&tga, Some(ImageFormat::Png), Some(ImageFormat::Tga)), [ImageFormat::Tga, ImageFormat::Png]);
+    assert_eq!(decode(&tga, Some(ImageFormat::Png), Some(ImageFormat::Tga)).unwrap(), (8, 8));
+    assert_eq!(decode(&tga, None, Some(ImageFormat::Tga)).unwrap(), (8, 8));
+    assert!(decode(&tga, Some(ImageFormat::Png), None).is_err());
+}
+
+#[test]
+fn no_hints_no_image() {
+    let tga = encoded(ImageOutputFormat::Tga);
+    assert!(format_candidates(&tga, None, None).is_empty());
+    assert_eq!(decode(&tga, None, None).unwrap_err(), ConvertError::NotAnImage);
+    assert_eq!(format_candidates(&tga, Some(ImageFormat::Tga), Some(ImageFormat::Tga)), [ImageFormat::Tga]);
+}
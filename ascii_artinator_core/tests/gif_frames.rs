@@ -0,0 +1,57 @@
+//! `frame` picks a frame of an animated GIF, and frames past the end say so.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{self, codecs::gif::GifEncoder, Frame, Rgba, RgbaImage};
+use ascii_artinator_core::ConvertError;
+
+// three frames told apart by which third is lit
+fn three_frames() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut bytes);
+        for lit in 0..3 {
+            let frame = RgbaImage::from_fn(12, 4, |x, _| {
+                return if x / 4 == lit { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 255]) };
+            });
+            encoder.encode_frame(Frame::new(frame)).unwrap();
+        }
+    }
+    return bytes;
+}
+
+// which third of the frame is lit
+fn lit_third(img: &image::DynamicImage) -> u32 {
+    let gray = img.to_luma8();
+    return (0..3).find(|third| gray.get_pixel(third * 4 + 1, 1).0[0] > 128).unwrap();
+}
+
+fn decode_frame(bytes: &[u8], frame: &str) -> Result<image::DynamicImage, ConvertError> {
+    let opts = common::opts(&[("frame", frame)]);
+    return ascii_artinator_core::decode_with_hints(bytes, None, None, ascii_artinator_core::DEFAULT_MAX_PIXELS, &opts);
+}
+
+#[test]
+fn first_and_middle_frame() {
+    let bytes = three_frames();
+    assert_eq!(lit_third(&decode_frame(&bytes, "0").unwrap()), 0);
+    assert_eq!(lit_third(&decode_frame(&bytes, "1").unwrap()), 1);
+    assert_eq!(lit_third(&decode_frame(&bytes, "2").unwrap()), 2);
+}
+
+#[test]
+fn past_the_last_frame() {
+    assert_eq!(decode_frame(&three_frames(), "3").unwrap_err(), ConvertError::NoSuchFrame(3));
+}
+
+#[test]
+fn only_frame_zero_of_a_still() {
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgba8(RgbaImage::new(4, 4))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageOutputFormat::Png)
+        .unwrap();
+    assert!(decode_frame(&png, "0").is_ok());
+    assert_eq!(decode_frame(&png, "1").unwrap_err(), ConvertError::NoSuchFrame(1));
+}
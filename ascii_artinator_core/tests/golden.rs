@@ -0,0 +1,77 @@
+//! Converts the images in `tests/golden` with a few option combinations and
+//! compares against the known good output next to them, one row per line, so
+//! changes to the pipeline can't change what comes out without anyone
+//! noticing.
+//!
+//! After an intended change, `AA_BLESS=1 cargo test --test golden` rewrites
+//! the golden files; check their diff before committing them.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+fn check(image: &str, query: &str, golden: &str) {
+    let pairs: Vec<(&str, &str)> = query.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap())
+        .collect();
+    let actual = common::rows_of(image, &pairs);
+    let golden_path = common::golden(golden);
+    if std::env::var_os("AA_BLESS").is_some() {
+        let text: String = actual.iter().map(|row| format!("{}\n", row)).collect();
+        std::fs::write(&golden_path, text).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&golden_path)
+        .unwrap_or_else(|_| panic!("no {}, run with AA_BLESS=1 to make it", golden));
+    let expected: Vec<&str> = expected.lines().collect();
+    if actual == expected {
+        return;
+    }
+    let mut diff = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (expected_row, actual_row) = (expected.get(i).copied(), actual.get(i).map(|row| row.as_str()));
+        if expected_row != actual_row {
+            diff.push_str(&format!(
+                "row {}:\n  expected: {}\n  actual:   {}\n",
+                i, expected_row.unwrap_or("(none)"), actual_row.unwrap_or("(none)")
+            ));
+        }
+    }
+    panic!("{} with \"{}\" doesn't match {}:\n{}", image, query, golden, diff);
+}
+
+#[test]
+fn default_options() {
+    check("circle.png", "", "circle.txt");
+}
+
+#[test]
+fn dithered_gradient() {
+    check("grad16.png", "mode=dither", "grad16.txt");
+}
+
+#[test]
+fn edges() {
+    check("circle.png", "mode=edges", "circle_edges.txt");
+}
+
+#[test]
+fn quadrant() {
+    check("circle.png", "charset=quadrant", "circle_quadrant.txt");
+}
+
+#[test]
+fn ascii_ramp() {
+    check("grad16.png", "charset=ascii", "grad16_ascii.txt");
+}
+
+#[test]
+fn border_and_caption() {
+    check("circle.png", "border=true&top=hi", "circle_border_top.txt");
+}
+
+#[test]
+fn rgbsplit() {
+    check("redblue.png", "mode=rgbsplit", "redblue_rgbsplit.txt");
+}
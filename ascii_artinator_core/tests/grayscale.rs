@@ -0,0 +1,37 @@
+//! A grayscale source has to come out the same as its RGB twin, with the
+//! gray values taken as they are.
+
+#![allow(clippy::needless_return)]
+
+use ascii_artinator_core::{ConvertOptions, image};
+
+// every gray level, a few times over
+fn gray_ramp() -> image::GrayImage {
+    return image::GrayImage::from_fn(256, 40, |x, y| image::Luma([((x + y * 7) % 256) as u8]));
+}
+
+#[test]
+fn gray_levels_are_kept() {
+    let gray = gray_ramp();
+    let rgb = image::DynamicImage::ImageLuma8(gray.clone()).to_rgb8();
+    assert_eq!(ascii_artinator_core::to_gray(&image::DynamicImage::ImageLuma8(gray.clone())), gray);
+    assert_eq!(ascii_artinator_core::to_gray(&image::DynamicImage::ImageRgb8(rgb)), gray);
+}
+
+#[test]
+fn gray_and_rgb_twin_match() {
+    let gray = gray_ramp();
+    let png = |img: image::DynamicImage| -> image::DynamicImage {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageOutputFormat::Png).unwrap();
+        return image::load_from_memory(bytes.get_ref()).unwrap();
+    };
+    let gray_png = png(image::DynamicImage::ImageLuma8(gray.clone()));
+    let rgb_png = png(image::DynamicImage::ImageRgb8(image::DynamicImage::ImageLuma8(gray).to_rgb8()));
+    assert!(matches!(gray_png, image::DynamicImage::ImageLuma8(_)));
+    assert!(matches!(rgb_png, image::DynamicImage::ImageRgb8(_)));
+    let opts = ConvertOptions::default();
+    let from_gray = ascii_artinator_core::convert_to_rows(gray_png, &opts, None).unwrap();
+    let from_rgb = ascii_artinator_core::convert_to_rows(rgb_png, &opts, None).unwrap();
+    assert_eq!(from_gray, from_rgb);
+}
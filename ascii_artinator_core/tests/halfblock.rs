@@ -0,0 +1,28 @@
+//! Each 1x2 block of pixels is the half block char with those halves filled.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::halfblock::{gray_to_halfblock, HALFBLOCK_CHARS};
+use ascii_artinator_core::image::{GrayImage, Luma};
+
+// a 1x2 block, top first
+fn block(on: [bool; 2]) -> GrayImage {
+    return GrayImage::from_fn(1, 2, |_, y| Luma([if on[y as usize] { 255 } else { 0 }]));
+}
+
+#[test]
+fn each_half_is_its_own() {
+    let halves = [(" ", [false, false]), ("▀", [true, false]), ("▄", [false, true]), ("█", [true, true])];
+    for (expected, on) in halves {
+        assert_eq!(gray_to_halfblock(&block(on)), vec![expected.to_owned()], "{:?}", on);
+    }
+}
+
+#[test]
+fn white_converts_to_full_blocks() {
+    let rows = common::rows_of("circle.png", &[("charset", "halfblock")]);
+    assert!(rows.iter().flat_map(|row| row.chars()).any(|c| c == '█'));
+    assert!(rows.iter().flat_map(|row| row.chars()).all(|c| HALFBLOCK_CHARS.contains(&c)));
+}
@@ -0,0 +1,56 @@
+//! `ico_size` has to pick the closest of the sizes in an .ico, and the
+//! biggest without it.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::codecs::ico::{IcoEncoder, IcoFrame};
+use ascii_artinator_core::image::{ColorType, Rgba, RgbaImage};
+use ascii_artinator_core::ConvertError;
+
+// an .ico with a 16, a 64 and a 32 in it, in that order
+fn multi_size_ico() -> Vec<u8> {
+    let sizes = [16, 64, 32];
+    let imgs: Vec<RgbaImage> = sizes.iter()
+        .map(|&size| RgbaImage::from_fn(size, size, |x, _| Rgba(if x < size / 2 { [0, 0, 0, 255] } else { [255; 4] })))
+        .collect();
+    let frames: Vec<IcoFrame> = imgs.iter()
+        .map(|img| IcoFrame::as_png(img.as_raw(), img.width(), img.height(), ColorType::Rgba8).unwrap())
+        .collect();
+    let mut ico = Vec::new();
+    IcoEncoder::new(&mut ico).encode_images(&frames).unwrap();
+    return ico;
+}
+
+fn decode(ico: &[u8], query: &[(&str, &str)]) -> Result<u32, ConvertError> {
+    let opts = common::parse(query)?;
+    // no hints, so it's down to the magic bytes
+    let img = ascii_artinator_core::decode_with_hints(ico, None, None, ascii_artinator_core::DEFAULT_MAX_PIXELS, &opts)?;
+    assert_eq!(img.width(), img.height());
+    return Ok(img.width());
+}
+
+#[test]
+fn picks_the_closest_size() {
+    let ico = multi_size_ico();
+    assert_eq!(decode(&ico, &[("ico_size", "16")]).unwrap(), 16);
+    assert_eq!(decode(&ico, &[("ico_size", "32")]).unwrap(), 32);
+    assert_eq!(decode(&ico, &[("ico_size", "40")]).unwrap(), 32);
+    assert_eq!(decode(&ico, &[("ico_size", "1")]).unwrap(), 16);
+    // halfway goes to the bigger one
+    assert_eq!(decode(&ico, &[("ico_size", "48")]).unwrap(), 64);
+}
+
+#[test]
+fn biggest_by_default() {
+    assert_eq!(decode(&multi_size_ico(), &[]).unwrap(), 64);
+}
+
+#[test]
+fn rejects_sizes_an_ico_cant_have() {
+    let ico = multi_size_ico();
+    for size in ["0", "257"] {
+        assert!(matches!(decode(&ico, &[("ico_size", size)]), Err(ConvertError::BadOption(_))));
+    }
+}
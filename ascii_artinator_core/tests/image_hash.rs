@@ -0,0 +1,69 @@
+//! The average hash barely moves for the same picture cropped, resized or
+//! recompressed, and differs by far more between different pictures.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::hash::{average_hash, hamming_distance, is_near_duplicate, NEAR_DUPLICATE_BITS};
+use ascii_artinator_core::image::{self, DynamicImage, Rgb, RgbImage};
+
+// something like a photo: a sky fading down, a sun, and rolling hills
+fn landscape() -> DynamicImage {
+    return DynamicImage::ImageRgb8(RgbImage::from_fn(320, 240, |x, y| {
+        let (dx, dy) = (x as i32 - 220, y as i32 - 70);
+        if dx * dx + dy * dy < 35 * 35 {
+            return Rgb([250, 220, 90]);
+        }
+        if y as f32 > 160.0 + 30.0 * (x as f32 / 50.0).sin() {
+            return Rgb([40, 120 + (x % 7) as u8, 50]);
+        }
+        return Rgb([120, 160, 255 - (y * 255 / 240 / 2) as u8]);
+    }));
+}
+
+fn jpeg_of(img: &DynamicImage) -> DynamicImage {
+    let mut jpeg = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(40)).unwrap();
+    return image::load_from_memory(&jpeg).unwrap();
+}
+
+#[test]
+fn near_duplicates_are_within_the_threshold() {
+    let original = average_hash(&landscape());
+    let near = [
+        landscape().crop_imm(8, 6, 304, 228),
+        landscape().crop_imm(0, 0, 300, 230),
+        landscape().resize(100, 75, image::imageops::Triangle),
+        landscape().brighten(30),
+        jpeg_of(&landscape()),
+    ];
+    for (i, img) in near.iter().enumerate() {
+        let hash = average_hash(img);
+        assert!(is_near_duplicate(original, hash), "{}: {} bits", i, hamming_distance(original, hash));
+    }
+}
+
+#[test]
+fn different_images_are_not() {
+    let hashes = [
+        average_hash(&landscape()),
+        average_hash(&landscape().flipv()),
+        average_hash(&common::open("circle.png")),
+        average_hash(&common::open("grad16.png")),
+        average_hash(&common::open("redblue.png")),
+    ];
+    for (i, &a) in hashes.iter().enumerate() {
+        for &b in &hashes[i + 1..] {
+            // not just past it, well past it
+            assert!(hamming_distance(a, b) > NEAR_DUPLICATE_BITS * 2, "{:016x} {:016x}", a, b);
+        }
+    }
+}
+
+#[test]
+fn distance_counts_bits() {
+    assert_eq!(hamming_distance(0, 0), 0);
+    assert_eq!(hamming_distance(0b1011, 0b0010), 2);
+    assert_eq!(hamming_distance(0, u64::MAX), 64);
+}
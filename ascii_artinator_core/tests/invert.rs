@@ -0,0 +1,46 @@
+//! `invert=auto` has to flip a dark subject on a bright background, so the
+//! subject gets the dots, and leave a bright one on a dark background be.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, GrayImage, Luma};
+use ascii_artinator_core::{ConvertOptions, Invert};
+
+// a disc of `subject` in the middle of `background`
+fn disc(subject: u8, background: u8) -> DynamicImage {
+    let img = GrayImage::from_fn(80, 80, |x, y| {
+        let (dx, dy) = (x as i32 - 40, y as i32 - 40);
+        return Luma([if dx * dx + dy * dy < 25 * 25 { subject } else { background }]);
+    });
+    return DynamicImage::ImageLuma8(img);
+}
+
+fn convert(img: DynamicImage, invert: &str) -> (Vec<String>, Invert) {
+    let opts = common::opts(&[("invert", invert)]);
+    let (text, applied) = ascii_artinator_core::convert_with_applied(img, &opts).unwrap();
+    return (text.split(' ').map(str::to_owned).collect(), applied.invert);
+}
+
+#[test]
+fn flips_a_dark_subject_on_bright() {
+    let (auto, applied) = convert(disc(0, 255), "auto");
+    assert_eq!(applied, Invert::On);
+    assert_eq!(auto, convert(disc(0, 255), "true").0);
+    assert_ne!(auto, convert(disc(0, 255), "false").0);
+}
+
+#[test]
+fn leaves_a_bright_subject_on_dark() {
+    let (auto, applied) = convert(disc(255, 0), "auto");
+    assert_eq!(applied, Invert::Off);
+    assert_eq!(auto, convert(disc(255, 0), "off").0);
+}
+
+#[test]
+fn off_unless_asked() {
+    assert_eq!(ConvertOptions::default().invert, Invert::Off);
+    // a flat image has nothing to go by
+    assert_eq!(Invert::Auto.resolve(&disc(128, 128)), Invert::Off);
+}
@@ -0,0 +1,37 @@
+//! `format=jsonl` has to give one parseable object per row, in order.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+fn convert(image: &str, query: &[(&str, &str)]) -> (Vec<String>, String) {
+    let opts = common::opts(query);
+    let img = common::open(image);
+    let rows = ascii_artinator_core::convert_to_rows(img.clone(), &opts, None).unwrap();
+    let mut jsonl_opts = opts.clone();
+    jsonl_opts.format = Some(ascii_artinator_core::options::Format::Jsonl);
+    return (rows, ascii_artinator_core::convert(img, &jsonl_opts).unwrap());
+}
+
+fn check(rows: &[String], jsonl: &str) {
+    let lines: Vec<&str> = jsonl.lines().collect();
+    assert_eq!(lines.len(), rows.len());
+    for (i, (line, row)) in lines.iter().zip(rows).enumerate() {
+        let parsed: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(parsed["row"], i);
+        assert_eq!(parsed["cells"], row.as_str());
+    }
+}
+
+#[test]
+fn one_line_per_row() {
+    let (rows, jsonl) = convert("circle.png", &[]);
+    check(&rows, &jsonl);
+}
+
+#[test]
+fn ansi_escapes_stay_valid_json() {
+    let (rows, jsonl) = convert("redblue.png", &[("mode", "rgbsplit")]);
+    assert!(rows.iter().any(|row| row.contains('\x1b')));
+    check(&rows, &jsonl);
+}
@@ -0,0 +1,73 @@
+//! Checks that `format=rust` and `format=c` give literals that read back as
+//! exactly the rows they came from.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::rows_of;
+
+// reads a literal back the way the compiler would, for the escapes the
+// converters emit
+fn unescape(literal: &str, c_style: bool) -> String {
+    let inner = literal.strip_prefix('"').and_then(|l| l.strip_suffix('"')).expect("not quoted");
+    let mut chars = inner.chars().peekable();
+    let mut out = String::new();
+    let take_hex = |chars: &mut std::iter::Peekable<std::str::Chars>, n: usize| -> u32 {
+        let hex: String = (0..n).map(|_| chars.next().unwrap()).collect();
+        return u32::from_str_radix(&hex, 16).unwrap();
+    };
+    while let Some(c) = chars.next() {
+        assert!(c != '"' && c != '\n', "unescaped {:?} in {}", c, literal);
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let code = match chars.next().unwrap() {
+            'n' => '\n' as u32,
+            '"' => '"' as u32,
+            '\\' => '\\' as u32,
+            '?' if c_style => '?' as u32,
+            'x' if !c_style => take_hex(&mut chars, 2),
+            'u' if !c_style => {
+                assert_eq!(chars.next(), Some('{'));
+                let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                u32::from_str_radix(&hex, 16).unwrap()
+            },
+            'u' if c_style => take_hex(&mut chars, 4),
+            'U' if c_style => take_hex(&mut chars, 8),
+            digit @ '0'..='7' if c_style => {
+                let rest: String = (0..2).map(|_| chars.next().unwrap()).collect();
+                u32::from_str_radix(&format!("{}{}", digit, rest), 8).unwrap()
+            },
+            other => panic!("unexpected escape \\{} in {}", other, literal),
+        };
+        out.push(char::from_u32(code).unwrap());
+    }
+    return out;
+}
+
+fn check_round_trip(rows: &[String]) {
+    let original = rows.join("\n");
+    let rust = ascii_artinator_core::to_rust_literal(rows);
+    assert!(rust.is_ascii());
+    assert_eq!(unescape(&rust, false), original);
+    let c = ascii_artinator_core::to_c_literal(rows);
+    assert!(c.is_ascii());
+    assert_eq!(unescape(&c, true), original);
+}
+
+#[test]
+fn braille_round_trips() {
+    check_round_trip(&rows_of("circle.png", &[]));
+}
+
+#[test]
+fn ansi_colors_round_trip() {
+    check_round_trip(&rows_of("redblue.png", &[("mode", "rgbsplit")]));
+}
+
+#[test]
+fn quotes_and_backslashes_round_trip() {
+    check_round_trip(&["say \"hi\" \\ ??".to_owned(), "⣿⠁".to_owned()]);
+}
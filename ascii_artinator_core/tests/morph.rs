@@ -0,0 +1,49 @@
+//! `dilate` grows the dots into their neighbors, and `erode` takes away
+//! whatever is thinner than it.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{GrayImage, Luma};
+use ascii_artinator_core::morph::{dilate, erode};
+
+fn lit(dots: &GrayImage) -> Vec<(u32, u32)> {
+    return dots.enumerate_pixels().filter(|(_, _, pix)| pix.0[0] > 80).map(|(x, y, _)| (x, y)).collect();
+}
+
+fn one_dot() -> GrayImage {
+    let mut dots = GrayImage::new(7, 7);
+    dots.put_pixel(3, 3, Luma([255]));
+    return dots;
+}
+
+#[test]
+fn a_lone_dot_lights_its_neighbors() {
+    let grown = lit(&dilate(&one_dot(), 1));
+    let expected: Vec<(u32, u32)> = (2..=4).flat_map(|y| (2..=4).map(move |x| (x, y))).collect();
+    assert_eq!(grown, expected);
+    assert_eq!(lit(&dilate(&one_dot(), 2)).len(), 25);
+}
+
+#[test]
+fn erode_takes_it_back() {
+    assert!(lit(&erode(&one_dot(), 1)).is_empty());
+    assert_eq!(lit(&erode(&dilate(&one_dot(), 1), 1)), vec![(3, 3)]);
+}
+
+#[test]
+fn edges_dont_count_as_off() {
+    let full = GrayImage::from_pixel(4, 4, Luma([255]));
+    assert_eq!(erode(&full, 1), full);
+}
+
+#[test]
+fn thickens_the_conversion() {
+    let fill = |query: &[(&str, &str)]| {
+        return ascii_artinator_core::braille::BrailleGrid::from_rows(&common::rows_of("circle.png", query)).stats().fill;
+    };
+    let plain = fill(&[("mode", "edges")]);
+    assert!(fill(&[("mode", "edges"), ("dilate", "1")]) > plain);
+    assert!(fill(&[("mode", "edges"), ("erode", "1")]) < plain);
+}
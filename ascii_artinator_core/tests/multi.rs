@@ -0,0 +1,60 @@
+//! `format=multi` has to give the braille and an ascii fallback of the very
+//! same dots, as one JSON object.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::ConvertError;
+use common::{open, parse};
+
+fn split(text: &str) -> Vec<String> {
+    return text.lines().map(str::to_owned).collect();
+}
+
+#[test]
+fn both_have_the_same_shape() {
+    let opts = parse(&[("format", "multi"), ("mode", "edges")]).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&ascii_artinator_core::convert(open("circle.png"), &opts).unwrap()).unwrap();
+    let braille = split(parsed["braille"].as_str().unwrap());
+    let ascii = split(parsed["ascii"].as_str().unwrap());
+    assert!(!braille.is_empty());
+    assert_eq!(braille.len(), ascii.len());
+    for (braille_row, ascii_row) in braille.iter().zip(&ascii) {
+        assert_eq!(braille_row.chars().count(), ascii_row.chars().count());
+        assert!(ascii_row.is_ascii());
+    }
+}
+
+#[test]
+fn same_as_converting_each_alone() {
+    let opts = parse(&[("format", "multi")]).unwrap();
+    let (braille, ascii) = ascii_artinator_core::convert_to_multi(open("grad16.png"), &opts, None).unwrap();
+    let alone = |charset: &str| {
+        let opts = parse(&[("charset", charset)]).unwrap();
+        return ascii_artinator_core::convert_to_rows(open("grad16.png"), &opts, None).unwrap();
+    };
+    assert_eq!(braille, alone("braille"));
+    assert_eq!(ascii, alone("ascii"));
+}
+
+#[test]
+fn escaped_stays_valid_json() {
+    let opts = parse(&[("format", "multi"), ("encoding", "escaped")]).unwrap();
+    let mut text = String::new();
+    ascii_artinator_core::write_output(&mut text, open("circle.png"), &opts, None).unwrap();
+    assert!(text.is_ascii());
+    let parsed: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert!(parsed["braille"].as_str().unwrap().chars().any(|c| ('\u{2800}'..='\u{28ff}').contains(&c)));
+}
+
+#[test]
+fn rejects_what_it_cant_pair() {
+    for query in [
+        [("format", "multi"), ("charset", "ascii")],
+        [("format", "multi"), ("mode", "rgbsplit")],
+        [("format", "multi"), ("rtl", "true")],
+    ] {
+        assert!(matches!(parse(&query), Err(ConvertError::BadOption(_))), "{:?}", query);
+    }
+}
@@ -0,0 +1,67 @@
+//! Every bad query gets a 400 saying which option it was, and only one kind
+//! of message for each way of getting it wrong.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::ConvertError;
+use common::parse;
+
+// the query, and what it should be told
+const BAD: &[(&[(&str, &str)], &str)] = &[
+    // unknown and unparseable
+    (&[("colz", "20")], "unknown option: colz"),
+    (&[("cols", "abc"), ("rows", "5")], "bad value for cols: abc"),
+    (&[("cols", "-1"), ("rows", "5")], "bad value for cols: -1"),
+    (&[("mode", "sparkly")], "bad value for mode: sparkly"),
+    (&[("dither", "atkinson")], "bad value for dither: atkinson"),
+    (&[("border", "yes")], "bad value for border: yes"),
+    (&[("profile", "nope")], "bad value for profile: nope"),
+    // out of range
+    (&[("cols", "0"), ("rows", "5")], "cols and rows have to be between 1 and 100"),
+    (&[("cols", "20"), ("rows", "101")], "cols and rows have to be between 1 and 100"),
+    (&[("aspect", "5")], "aspect has to be between 0.25 and 4"),
+    (&[("denoise", "4")], "denoise goes up to 3"),
+    (&[("dilate", "5")], "dilate and erode go up to 4"),
+    (&[("cell_repeat", "0")], "cell_repeat has to be between 1 and 8"),
+    (&[("wrap", "0")], "wrap has to be at least 1"),
+    (&[("preview_rows", "0")], "preview_rows has to be at least 1"),
+    (&[("target_density", "1.5")], "target_density has to be between 0 and 1"),
+    // only together, or never together
+    (&[("cols", "20")], "cols and rows go together"),
+    (&[("src_width", "20")], "src_width and src_height go together"),
+    (&[("cols", "20"), ("rows", "5"), ("center_weight", "2")], "center_weight doesn't work with cols and rows"),
+    (&[("edge_low", "200"), ("edge_high", "100")], "edge_low can't be above edge_high"),
+    (&[("mode", "rgbsplit"), ("charset", "ascii")], "rgbsplit only works with braille"),
+    (&[("mode", "rgbsplit"), ("stencil", "true")], "stencil doesn't work with rgbsplit"),
+    (&[("mode", "edges"), ("dither", "floyd")], "dither only works with mode=dither"),
+    (&[("mode", "edges"), ("alpha_dither", "true")], "alpha_dither only works with dither"),
+    (&[("rtl_mirror", "true")], "rtl_mirror goes with rtl"),
+    (&[("summary", "true"), ("format", "html")], "summary only goes on text"),
+];
+
+#[test]
+fn bad_queries_say_why() {
+    for (query, expected) in BAD {
+        match parse(query) {
+            Err(ConvertError::BadOption(msg)) => assert_eq!(msg, *expected, "{:?}", query),
+            other => panic!("{:?} gave {:?}", query, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn good_queries_pass() {
+    let good: &[&[(&str, &str)]] = &[
+        &[],
+        &[("cols", "1"), ("rows", "100")],
+        &[("aspect", "0.25")],
+        &[("mode", "dither"), ("dither", "floyd"), ("target_density", "1")],
+        &[("rtl", "true"), ("rtl_mirror", "true")],
+        &[("summary", "true"), ("format", "text")],
+    ];
+    for query in good {
+        assert!(parse(query).is_ok(), "{:?}", query);
+    }
+}
@@ -0,0 +1,29 @@
+//! Otsu's threshold has to land between the two levels of a two-level image,
+//! so stenciling at it splits them apart.
+
+#![allow(clippy::needless_return)]
+
+use ascii_artinator_core::image::{GrayImage, Luma};
+use ascii_artinator_core::preprocess;
+
+fn two_levels(dark: u8, bright: u8) -> GrayImage {
+    return GrayImage::from_fn(32, 32, |x, _| Luma([if x < 12 { dark } else { bright }]));
+}
+
+#[test]
+fn splits_two_levels() {
+    for (dark, bright) in [(40, 200), (0, 255), (100, 110)] {
+        let img = two_levels(dark, bright);
+        let threshold = preprocess::otsu_threshold(&img);
+        assert!((dark..bright).contains(&threshold), "{} for {} and {}", threshold, dark, bright);
+        let stenciled = preprocess::stencil(img.clone(), threshold);
+        for (pix, orig) in stenciled.pixels().zip(img.pixels()) {
+            assert_eq!(pix.0[0] == 255, orig.0[0] == bright);
+        }
+    }
+}
+
+#[test]
+fn flat_image_gets_zero() {
+    assert_eq!(preprocess::otsu_threshold(&two_levels(90, 90)), 0);
+}
@@ -0,0 +1,59 @@
+//! Writing into a sink has to come out exactly the same as building the
+//! string, for every format and encoding.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image;
+use common::opts as opts_with;
+
+fn circle() -> image::DynamicImage {
+    return common::open("circle.png");
+}
+
+#[test]
+fn matches_the_string_output() {
+    for format in ["text", "html", "rust", "c", "jsonl"] {
+        for encoding in ["utf8", "escaped"] {
+            let opts = opts_with(&[("format", format), ("encoding", encoding), ("border", "true")]);
+            let expected = ascii_artinator_core::encode(ascii_artinator_core::convert(circle(), &opts).unwrap(), opts.encoding);
+            let mut written = String::new();
+            ascii_artinator_core::write_output(&mut written, circle(), &opts, None).unwrap();
+            assert_eq!(written, expected, "{} {}", format, encoding);
+        }
+    }
+}
+
+#[test]
+fn writes_into_io() {
+    let opts = opts_with(&[]);
+    let mut sink = ascii_artinator_core::output::IoSink::new(Vec::new());
+    ascii_artinator_core::write_output(&mut sink, circle(), &opts, None).unwrap();
+    assert_eq!(String::from_utf8(sink.inner).unwrap(), ascii_artinator_core::convert(circle(), &opts).unwrap());
+}
+
+// a sink that takes so many bytes and then fails, like a closed pipe
+struct Full(usize);
+
+impl std::io::Write for Full {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.len() > self.0 {
+            return Err(std::io::ErrorKind::BrokenPipe.into());
+        }
+        self.0 -= buf.len();
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return Ok(());
+    }
+}
+
+#[test]
+fn failing_sink_is_an_error() {
+    let mut sink = ascii_artinator_core::output::IoSink::new(Full(10));
+    let written = ascii_artinator_core::write_output(&mut sink, circle(), &opts_with(&[]), None);
+    assert!(matches!(written, Err(ascii_artinator_core::ConvertError::Write)));
+    assert_eq!(sink.error.unwrap().kind(), std::io::ErrorKind::BrokenPipe);
+}
@@ -0,0 +1,58 @@
+//! `preview_rows` has to crop the full conversion, and only add its `…` row
+//! when something actually got cut.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::ConvertError;
+use common::parse;
+
+fn convert(query: &[(&str, &str)]) -> Vec<String> {
+    let mut query = query.to_vec();
+    query.push(("blank_mode", "braille"));
+    return common::rows_of("circle.png", &query);
+}
+
+fn is_marker(row: &str) -> bool {
+    return row.contains('⠄') && row.chars().all(|c| c == '⠄' || c == '\u{2800}');
+}
+
+#[test]
+fn cuts_with_a_marker() {
+    let full = convert(&[]);
+    let preview = convert(&[("preview_rows", "3")]);
+    assert_eq!(preview.len(), 4);
+    assert_eq!(preview[..3], full[..3]);
+    assert!(is_marker(&preview[3]), "{}", preview[3]);
+    assert_eq!(preview[3].chars().count(), full[0].chars().count());
+}
+
+#[test]
+fn no_marker_when_nothing_was_cut() {
+    let full = convert(&[]);
+    for rows in [full.len(), full.len() + 5] {
+        let preview = convert(&[("preview_rows", &rows.to_string())]);
+        assert_eq!(preview, full);
+        assert!(!preview.iter().any(|row| is_marker(row)));
+    }
+}
+
+#[test]
+fn counts_the_captions_too() {
+    let full = convert(&[("top", "hi")]);
+    let preview = convert(&[("top", "hi"), ("preview_rows", "2")]);
+    assert_eq!(preview[..2], full[..2]);
+    assert!(is_marker(&preview[2]));
+}
+
+#[test]
+fn only_for_plain_braille() {
+    for query in [
+        [("preview_rows", "0"), ("charset", "braille")],
+        [("preview_rows", "3"), ("charset", "ascii")],
+        [("preview_rows", "3"), ("mode", "rgbsplit")],
+    ] {
+        assert!(matches!(parse(&query), Err(ConvertError::BadOption(_))), "{:?}", query);
+    }
+}
@@ -0,0 +1,41 @@
+//! `profile=aalib` has to come to exactly the options it says it does, and
+//! still let any of them be changed.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::{Charset, ConvertOptions};
+use common::parse as opts_with;
+
+#[test]
+fn aalib_sets_its_options() {
+    let expected = ConvertOptions {
+        charset: Charset::Aalib,
+        autocontrast: true,
+        autocontrast_clip: 2.0,
+        ..ConvertOptions::default()
+    };
+    assert_eq!(opts_with(&[("profile", "aalib")]).unwrap(), expected);
+}
+
+#[test]
+fn options_override_the_profile() {
+    let opts = opts_with(&[("profile", "aalib"), ("autocontrast_clip", "5"), ("charset", "ascii")]).unwrap();
+    assert_eq!(opts.charset, Charset::Ascii);
+    assert_eq!(opts.autocontrast_clip, 5.0);
+    assert!(opts.autocontrast);
+}
+
+#[test]
+fn unknown_profile_fails() {
+    assert!(opts_with(&[("profile", "libcaca")]).is_err());
+}
+
+#[test]
+fn aalib_converts_to_its_ramp() {
+    let rows = common::rows_of("grad16.png", &[("profile", "aalib")]);
+    let chars: String = rows.concat();
+    assert!(chars.chars().all(|c| ascii_artinator_core::mapper::AALIB_RAMP.contains(c)), "{}", chars);
+    assert!(chars.chars().any(|c| c != ' '));
+}
@@ -0,0 +1,40 @@
+//! Each 2x2 block of pixels is the quadrant char with those quarters filled.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{GrayImage, Luma};
+use ascii_artinator_core::quadrant::gray_to_quadrant;
+
+// a 2x2 block, top left first
+fn block(on: [bool; 4]) -> GrayImage {
+    return GrayImage::from_fn(2, 2, |x, y| Luma([if on[(y * 2 + x) as usize] { 255 } else { 0 }]));
+}
+
+#[test]
+fn all_on_is_the_full_block() {
+    assert_eq!(gray_to_quadrant(&block([true; 4])), vec!["█".to_owned()]);
+}
+
+#[test]
+fn all_off_is_a_space() {
+    assert_eq!(gray_to_quadrant(&block([false; 4])), vec![" ".to_owned()]);
+}
+
+#[test]
+fn each_quarter_is_its_own() {
+    let quarters = [("▘", [true, false, false, false]), ("▝", [false, true, false, false]),
+        ("▖", [false, false, true, false]), ("▗", [false, false, false, true]),
+        ("▀", [true, true, false, false]), ("▌", [true, false, true, false]), ("▚", [true, false, false, true])];
+    for (expected, on) in quarters {
+        assert_eq!(gray_to_quadrant(&block(on)), vec![expected.to_owned()], "{:?}", on);
+    }
+}
+
+#[test]
+fn white_converts_to_full_blocks() {
+    let rows = common::rows_of("circle.png", &[("charset", "quadrant")]);
+    assert!(rows.iter().flat_map(|row| row.chars()).any(|c| c == '█'));
+    assert!(rows.iter().flat_map(|row| row.chars()).all(|c| ascii_artinator_core::quadrant::QUADRANT_CHARS.contains(&c)));
+}
@@ -0,0 +1,48 @@
+//! The font `render.png` gets built in is monospace and has every braille
+//! pattern, each drawn within its own cell.
+
+#![cfg(feature = "render")]
+#![allow(clippy::needless_return)]
+
+use ab_glyph::{Font, FontRef, ScaleFont};
+use ascii_artinator_core::render::{render_png, DEFAULT_FONT};
+
+fn font() -> FontRef<'static> {
+    return FontRef::try_from_slice(DEFAULT_FONT).unwrap();
+}
+
+#[test]
+fn every_pattern_in_the_same_cell() {
+    let font = font();
+    let advance = font.h_advance_unscaled(font.glyph_id('M'));
+    let braille = (0x2800..=0x28ff).map(|code| char::from_u32(code).unwrap());
+    for c in braille.chain(' '..='~') {
+        let glyph = font.glyph_id(c);
+        assert_ne!(glyph.0, 0, "{:?}", c);
+        assert_eq!(font.h_advance_unscaled(glyph), advance, "{:?}", c);
+    }
+}
+
+#[test]
+fn dots_stay_inside_the_cell() {
+    let font = font().into_scaled(32.0);
+    let glyph = font.scaled_glyph('⣿');
+    let bounds = font.outline_glyph(glyph).unwrap().px_bounds();
+    assert!(bounds.min.x >= 0.0 && bounds.max.x <= font.h_advance(font.glyph_id('⣿')), "{:?}", bounds);
+    assert!(bounds.min.y >= -font.ascent() && bounds.max.y <= -font.descent(), "{:?}", bounds);
+}
+
+#[test]
+fn draws_the_dots_where_they_go() {
+    let png = render_png("⣿⠀\n⠀⠁", DEFAULT_FONT, 16.0, [0, 0, 0], [255, 255, 255]).unwrap();
+    let img = ascii_artinator_core::image::load_from_memory(&png).unwrap().to_luma8();
+    let (width, height) = (img.width(), img.height());
+    let ink = |x0: u32, y0: u32, x1: u32, y1: u32| -> usize {
+        return (y0..y1).flat_map(|y| (x0..x1).map(move |x| (x, y))).filter(|&(x, y)| img.get_pixel(x, y).0[0] < 128).count();
+    };
+    // the full cell top left, nothing top right, and one dot bottom right
+    assert!(ink(0, 0, width / 2, height / 2) > ink(width / 2, height / 2, width, height));
+    assert_eq!(ink(width / 2, 0, width, height / 2), 0);
+    assert!(ink(width / 2, height / 2, width, height) > 0);
+    assert_eq!(ink(0, height / 2, width / 2, height), 0);
+}
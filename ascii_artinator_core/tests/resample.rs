@@ -0,0 +1,62 @@
+//! The deadline-checked resize comes out the same as `image`'s own, to the
+//! byte, for every kind of pixel and going either way in size.
+
+#![allow(clippy::needless_return)]
+
+use std::time::{Duration, Instant};
+
+use ascii_artinator_core::image::{imageops::Triangle, DynamicImage, Rgba, RgbaImage};
+use ascii_artinator_core::resample;
+use ascii_artinator_core::ConvertError;
+
+fn noise(width: u32, height: u32) -> DynamicImage {
+    let mut seed: u32 = 7;
+    return DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |_, _| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        let [r, g, b, a] = seed.to_le_bytes();
+        return Rgba([r, g, b, a]);
+    }));
+}
+
+fn kinds() -> Vec<DynamicImage> {
+    let img = noise(53, 37);
+    return vec![
+        DynamicImage::ImageLuma8(img.to_luma8()),
+        DynamicImage::ImageLumaA8(img.to_luma_alpha8()),
+        DynamicImage::ImageRgb8(img.to_rgb8()),
+        img.clone(),
+        DynamicImage::ImageLuma16(img.to_luma16()),
+        DynamicImage::ImageRgba16(img.to_rgba16()),
+        DynamicImage::ImageRgb32F(img.to_rgb32f()),
+    ];
+}
+
+#[test]
+fn same_as_image_resize_exact() {
+    for img in kinds() {
+        for (width, height) in [(20, 11), (53, 37), (120, 80), (7, 60), (1, 1)] {
+            let ours = resample::resize_exact(&img, width, height, None).unwrap();
+            assert_eq!(ours, img.resize_exact(width, height, Triangle), "{:?} to {}x{}", img.color(), width, height);
+        }
+    }
+}
+
+#[test]
+fn same_as_image_resize_and_fill() {
+    for img in kinds() {
+        for (width, height) in [(20, 20), (58, 10), (10, 58), (200, 90)] {
+            let ours = resample::resize(&img, width, height, None).unwrap();
+            assert_eq!(ours, img.resize(width, height, Triangle), "{:?} in {}x{}", img.color(), width, height);
+            let ours = resample::resize_to_fill(&img, width, height, None).unwrap();
+            assert_eq!(ours, img.resize_to_fill(width, height, Triangle), "{:?} over {}x{}", img.color(), width, height);
+        }
+    }
+}
+
+#[test]
+fn gives_up_partway() {
+    let img = noise(3000, 3000);
+    let deadline = Some(Instant::now() + Duration::from_millis(5));
+    let resized = resample::resize_exact(&img, 58, 40, deadline);
+    assert_eq!(resized.unwrap_err(), ConvertError::DeadlineExceeded);
+}
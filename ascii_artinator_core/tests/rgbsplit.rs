@@ -0,0 +1,53 @@
+//! `mode=rgbsplit` dithers each channel on its own, so a pure color only
+//! lights its own channel's dots, in its own color.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::color::{rgb_split, RESET};
+use ascii_artinator_core::image::{DynamicImage, Rgba, RgbaImage};
+
+fn solid(color: [u8; 3]) -> DynamicImage {
+    let [r, g, b] = color;
+    return DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([r, g, b, 255])));
+}
+
+// the color escapes the rows use, besides resets
+fn escapes(rows: &[String]) -> Vec<String> {
+    let mut found: Vec<String> = rows.iter()
+        .flat_map(|row| row.split('\x1b').skip(1).map(|esc| format!("\x1b{}", &esc[..esc.find('m').unwrap() + 1])))
+        .filter(|esc| esc != RESET)
+        .collect();
+    found.sort();
+    found.dedup();
+    return found;
+}
+
+#[test]
+fn pure_red_is_only_red() {
+    let rows = rgb_split(&solid([255, 0, 0]), '⠀', true);
+    assert_eq!(escapes(&rows), vec!["\x1b[31m"]);
+    // the red channel on its own lights everything, and nothing else adds to it
+    assert!(rows.iter().all(|row| row.chars().filter(|c| !c.is_ascii()).all(|c| c == '⣿')));
+}
+
+#[test]
+fn each_channel_has_its_color() {
+    for (color, expected) in [([0, 255, 0], "\x1b[32m"), ([0, 0, 255], "\x1b[34m"), ([255, 0, 255], "\x1b[35m"), ([255, 255, 255], "\x1b[37m")] {
+        assert_eq!(escapes(&rgb_split(&solid(color), '⠀', true)), vec![expected], "{:?}", color);
+    }
+}
+
+#[test]
+fn black_lights_nothing() {
+    let rows = rgb_split(&solid([0, 0, 0]), '⠀', true);
+    assert!(rows.iter().all(|row| row.chars().all(|c| c == '⠀')), "{:?}", rows);
+}
+
+#[test]
+fn red_half_and_blue_half() {
+    let rows = common::rows_of("redblue.png", &[("mode", "rgbsplit"), ("color", "always")]);
+    // magenta where a cell has some of both
+    assert_eq!(escapes(&rows), vec!["\x1b[31m", "\x1b[34m", "\x1b[35m"]);
+}
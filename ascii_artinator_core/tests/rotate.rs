@@ -0,0 +1,62 @@
+//! `rotate` turns the image by any angle first: quarter turns exactly, the
+//! rest sampled, both landing on the same pixels.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, GenericImageView, Rgba};
+use ascii_artinator_core::preprocess::{rotate, rotated_size};
+
+const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+fn max_diff(a: &DynamicImage, b: &DynamicImage) -> u8 {
+    return a.to_rgba8().pixels().zip(b.to_rgba8().pixels())
+        .flat_map(|(a, b)| a.0.iter().zip(b.0.iter()).map(|(a, b)| a.abs_diff(*b)).collect::<Vec<_>>())
+        .max().unwrap();
+}
+
+#[test]
+fn ninety_is_the_exact_turn() {
+    let img = common::open("grad16.png");
+    assert_eq!(rotate(img.clone(), 90.0, CLEAR).to_rgba8(), img.rotate90().to_rgba8());
+    assert_eq!(rotate(img.clone(), -90.0, CLEAR).to_rgba8(), img.rotate270().to_rgba8());
+    assert_eq!(rotate(img.clone(), 450.0, CLEAR).to_rgba8(), img.rotate90().to_rgba8());
+}
+
+#[test]
+fn sampling_lands_on_the_exact_turn() {
+    // just off a quarter turn, so it takes the sampled path, to the same place
+    let img = common::open("grad16.png");
+    let sampled = rotate(img.clone(), 90.001, CLEAR);
+    let exact = img.rotate90();
+    assert_eq!(sampled.dimensions(), exact.dimensions());
+    assert!(max_diff(&sampled, &exact) <= 2, "{}", max_diff(&sampled, &exact));
+}
+
+#[test]
+fn the_canvas_grows() {
+    assert_eq!(rotated_size(40, 20, 90.0), (20, 40));
+    assert_eq!(rotated_size(40, 20, 180.0), (40, 20));
+    // 40 * cos 45 + 20 * sin 45
+    assert_eq!(rotated_size(40, 20, 45.0), (42, 42));
+    let rows = common::rows_of("grad16.png", &[("rotate", "45")]);
+    let plain = common::rows_of("grad16.png", &[]);
+    assert_ne!(rows, plain);
+}
+
+#[test]
+fn corners_get_the_fill() {
+    let white = DynamicImage::ImageRgba8(ascii_artinator_core::image::RgbaImage::from_pixel(20, 20, Rgba([255; 4])));
+    let red = Rgba([255, 0, 0, 255]);
+    let rotated = rotate(white, 45.0, red).to_rgba8();
+    assert_eq!(*rotated.get_pixel(0, 0), red);
+    assert_eq!(*rotated.get_pixel(rotated.width() / 2, rotated.height() / 2), Rgba([255; 4]));
+}
+
+#[test]
+fn conversion_goes_by_the_turned_size() {
+    let turned = common::open("grad16.png").rotate90();
+    let expected = ascii_artinator_core::convert_to_rows(turned, &common::opts(&[]), None).unwrap();
+    assert_eq!(common::rows_of("grad16.png", &[("rotate", "90")]), expected);
+}
@@ -0,0 +1,40 @@
+//! `rtl` reverses the cells of each row, and doing it twice undoes it.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::braille::BrailleGrid;
+
+fn circle_grid() -> BrailleGrid {
+    let img = ascii_artinator_core::resize_img(common::open("circle.png"), 1.0);
+    return BrailleGrid::from_dots(&ascii_artinator_core::dither_img(ascii_artinator_core::to_gray(&img)));
+}
+
+#[test]
+fn twice_is_the_original() {
+    let grid = circle_grid();
+    for mirror_dots in [false, true] {
+        assert_ne!(grid.reverse_cells(mirror_dots), grid);
+        assert_eq!(grid.reverse_cells(mirror_dots).reverse_cells(mirror_dots), grid);
+    }
+}
+
+#[test]
+fn mirrored_dots_are_a_flip() {
+    let grid = circle_grid();
+    let mirrored = grid.reverse_cells(true);
+    let width = grid.cols() as u32 * 2;
+    for y in 0..grid.rows() as u32 * 4 {
+        for x in 0..width {
+            assert_eq!(mirrored.get_dot(width - 1 - x, y), grid.get_dot(x, y), "({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn reverses_the_output_rows() {
+    let rows_with = |query: &[(&str, &str)]| common::rows_of("circle.png", query);
+    let reversed: Vec<String> = rows_with(&[]).iter().map(|row| row.chars().rev().collect()).collect();
+    assert_eq!(rows_with(&[("rtl", "true")]), reversed);
+}
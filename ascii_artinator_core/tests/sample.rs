@@ -0,0 +1,51 @@
+//! Each `sample` has to bring a block with one dark pixel down to the right
+//! brightness.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{GrayImage, Luma};
+use ascii_artinator_core::{preprocess, Sample};
+
+// a single block, all light but for one dark pixel
+fn one_dark_pixel() -> GrayImage {
+    let block = preprocess::SAMPLE_BLOCK;
+    return GrayImage::from_fn(block, block, |x, y| Luma([if (x, y) == (1, 2) { 0 } else { 200 }]));
+}
+
+#[test]
+fn each_reduction() {
+    let img = one_dark_pixel();
+    let sampled = |sample| preprocess::sample_blocks(&img, preprocess::SAMPLE_BLOCK, sample).get_pixel(0, 0).0[0];
+    assert_eq!(sampled(Sample::Avg), 188);
+    assert_eq!(sampled(Sample::Min), 0);
+    assert_eq!(sampled(Sample::Max), 200);
+    assert_eq!(sampled(Sample::Median), 200);
+}
+
+#[test]
+fn blocks_stay_apart() {
+    let img = GrayImage::from_fn(8, 4, |x, _| Luma([if x < 4 { 10 } else { 250 }]));
+    let sampled = preprocess::sample_blocks(&img, 4, Sample::Min);
+    assert_eq!(sampled.dimensions(), (2, 1));
+    assert_eq!(sampled.get_pixel(0, 0).0[0], 10);
+    assert_eq!(sampled.get_pixel(1, 0).0[0], 250);
+}
+
+fn rows_with(query: &[(&str, &str)]) -> Vec<String> {
+    return common::rows_of("circle.png", query);
+}
+
+// however the image is sized, sampling differently mustn't change the size
+#[test]
+fn same_size_as_avg() {
+    let shape = |rows: Vec<String>| rows.iter().map(|row| row.chars().count()).collect::<Vec<_>>();
+    for sizing in [&[][..], &[("center_weight", "2")], &[("cols", "10"), ("rows", "5")]] {
+        let avg = shape(rows_with(sizing));
+        for sample in ["min", "max", "median"] {
+            let query: Vec<_> = sizing.iter().copied().chain([("sample", sample)]).collect();
+            assert_eq!(shape(rows_with(&query)), avg, "{:?}", query);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! `src_width` and `src_height` resize to exactly that many dots, whatever
+//! the shape of the image.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, RgbaImage};
+use ascii_artinator_core::ConvertError;
+
+fn convert(query: &[(&str, &str)]) -> Result<Vec<String>, ConvertError> {
+    let opts = common::parse(query)?;
+    // wide and bright, nothing like the sizes asked for
+    let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(300, 40, [255, 255, 255, 255].into()));
+    return ascii_artinator_core::convert_to_rows(img, &opts, None);
+}
+
+#[test]
+fn grid_matches_the_dots() {
+    // braille cells are 2 by 4 dots, and partial cells still count
+    for (width, height, cols, rows) in [(20, 40, 10, 10), (7, 9, 4, 3), (1, 1, 1, 1), (200, 400, 100, 100)] {
+        let out = convert(&[("src_width", &width.to_string()), ("src_height", &height.to_string())]).unwrap();
+        assert_eq!(out.len(), rows, "{}x{}", width, height);
+        assert!(out.iter().all(|row| row.chars().count() == cols), "{}x{}", width, height);
+    }
+}
+
+#[test]
+fn bad_sizes() {
+    for query in [
+        &[("src_width", "10")][..],
+        &[("src_width", "0"), ("src_height", "10")],
+        &[("src_width", "201"), ("src_height", "10")],
+        &[("src_width", "10"), ("src_height", "10"), ("cols", "5"), ("rows", "5")],
+    ] {
+        assert!(matches!(convert(query), Err(ConvertError::BadOption(_))), "{:?}", query);
+    }
+}
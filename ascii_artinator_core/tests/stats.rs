@@ -0,0 +1,39 @@
+//! The codepoint histogram and dot fill of a braille grid.
+
+#![allow(clippy::needless_return)]
+
+use ascii_artinator_core::braille::{BRAILLE_CHARS, BrailleGrid};
+
+#[test]
+fn all_on_is_full() {
+    let grid = BrailleGrid { cells: vec![vec![0xff; 5]; 3] };
+    let stats = grid.stats();
+    assert_eq!(stats.fill, 100.0);
+    assert_eq!(stats.counts.len(), 1);
+    assert_eq!(stats.counts[&'⣿'], 15);
+}
+
+#[test]
+fn all_off_is_empty() {
+    let stats = BrailleGrid::new(5, 3).stats();
+    assert_eq!(stats.fill, 0.0);
+    assert_eq!(stats.counts[&BRAILLE_CHARS[0]], 15);
+}
+
+#[test]
+fn empty_grid_has_no_fill() {
+    assert_eq!(BrailleGrid::new(0, 0).stats().fill, 0.0);
+}
+
+#[test]
+fn counts_each_char() {
+    let rows = vec!["⠁⠁⣿".to_owned(), "\u{1b}[31m⠃\u{1b}[0m".to_owned()];
+    let grid = BrailleGrid::from_rows(&rows);
+    // the short row is padded with blanks, and the escapes are left out
+    assert_eq!(grid.cells, vec![vec![1, 1, 0xff], vec![3, 0, 0]]);
+    let stats = grid.stats();
+    assert_eq!(stats.counts[&'⠁'], 2);
+    assert_eq!(stats.counts[&'⠃'], 1);
+    assert_eq!(stats.counts[&BRAILLE_CHARS[0]], 2);
+    assert_eq!(stats.fill, 12.0 * 100.0 / 48.0);
+}
@@ -0,0 +1,51 @@
+//! `stencil=true` lights everything brighter than `stencil_threshold` and
+//! leaves the rest blank, so a dark subject comes out as a cut-out.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::braille::BrailleGrid;
+use ascii_artinator_core::image::{DynamicImage, GrayImage, Luma};
+
+// a black circle on white, 64 pixels across
+fn black_circle() -> DynamicImage {
+    return DynamicImage::ImageLuma8(GrayImage::from_fn(64, 64, |x, y| {
+        let (dx, dy) = (x as i32 - 32, y as i32 - 32);
+        return Luma([if dx * dx + dy * dy < 20 * 20 { 0 } else { 255 }]);
+    }));
+}
+
+#[test]
+fn circle_is_blank_and_its_surroundings_lit() {
+    let opts = common::opts(&[
+        ("stencil", "true"), ("blank_mode", "braille"), ("cols", "16"), ("rows", "8"), ("terminal_bg", "dark")
+    ]);
+    let rows = ascii_artinator_core::convert_to_rows(black_circle(), &opts, None).unwrap();
+    let grid = BrailleGrid::from_rows(&rows);
+    let (cols, rows) = (grid.cols(), grid.rows());
+    // the middle is the circle, the corners the background
+    assert_eq!(grid.cells[rows / 2][cols / 2], 0);
+    for (x, y) in [(0, 0), (cols - 1, 0), (0, rows - 1), (cols - 1, rows - 1)] {
+        assert_eq!(grid.cells[y][x], 0xff, "({}, {})", x, y);
+    }
+}
+
+#[test]
+fn only_two_levels() {
+    let gray = GrayImage::from_fn(256, 1, |x, _| Luma([x as u8]));
+    let cut = ascii_artinator_core::preprocess::stencil(gray, 100);
+    for (x, _, pix) in cut.enumerate_pixels() {
+        assert_eq!(pix.0[0], if x > 100 { 255 } else { 0 });
+    }
+}
+
+#[test]
+fn threshold_moves_the_edge() {
+    let fill = |threshold: &str| {
+        let query = [("stencil", "true"), ("stencil_threshold", threshold)];
+        return BrailleGrid::from_rows(&common::rows_of("grad16.png", &query)).stats().fill;
+    };
+    assert!(fill("64") > fill("128"));
+    assert!(fill("128") > fill("192"));
+}
@@ -0,0 +1,64 @@
+//! `summary=true` ends the text with a line on what was actually applied,
+//! and stays off the formats meant for machines.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::ConvertOptions;
+use common::parse as opts_with;
+
+fn convert_with(query: &[(&str, &str)]) -> String {
+    let mut opts = opts_with(query).unwrap();
+    opts.source = Some("https://example.com/img.png".to_owned());
+    return ascii_artinator_core::convert(common::open("circle.png"), &opts).unwrap();
+}
+
+#[test]
+fn says_what_was_applied() {
+    let plain = convert_with(&[("cols", "20"), ("rows", "8"), ("dither", "auto")]);
+    let summed = convert_with(&[("cols", "20"), ("rows", "8"), ("dither", "auto"), ("summary", "true")]);
+    let summary = summed.strip_prefix(&plain).unwrap();
+    // the auto dither comes out as what it picked for 20 columns
+    assert_eq!(summary, " # 20x8 braille from example.com/img.png (mode=dither, dither=ordered)");
+}
+
+#[test]
+fn mentions_the_stencil_threshold() {
+    let summed = convert_with(&[("stencil", "true"), ("stencil_threshold", "90"), ("summary", "true")]);
+    assert!(summed.ends_with("(mode=dither, dither=sierra, threshold=90)"), "{}", summed);
+}
+
+#[test]
+fn otsu_comes_out_as_a_number() {
+    let summed = convert_with(&[("stencil", "true"), ("stencil_threshold", "auto"), ("summary", "true")]);
+    let threshold = summed.rsplit_once("threshold=").unwrap().1.strip_suffix(')').unwrap();
+    let threshold: u8 = threshold.parse().unwrap_or_else(|_| panic!("{}", summed));
+    // the same as asking for that number outright
+    let explicit = convert_with(&[("stencil", "true"), ("stencil_threshold", &threshold.to_string()), ("summary", "true")]);
+    assert_eq!(summed, explicit);
+    assert_ne!(threshold, 128);
+}
+
+#[test]
+fn mentions_both_edge_thresholds() {
+    let summed = convert_with(&[("mode", "edges"), ("edge_low", "40"), ("edge_high", "90"), ("summary", "true")]);
+    assert!(summed.ends_with("(mode=edges, edge_low=40, edge_high=90)"), "{}", summed);
+}
+
+#[test]
+fn not_on_machine_formats() {
+    assert!(opts_with(&[("summary", "true"), ("format", "jsonl")]).is_err());
+    assert!(opts_with(&[("summary", "true"), ("format", "html")]).is_err());
+    assert!(opts_with(&[("summary", "true"), ("format", "text")]).is_ok());
+}
+
+#[test]
+fn counted_in_the_estimate() {
+    let opts = opts_with(&[("summary", "true")]).unwrap();
+    let estimate = ascii_artinator_core::estimate::estimate_size(200, 200, &opts).unwrap();
+    let plain = ascii_artinator_core::estimate::estimate_size(200, 200, &ConvertOptions::default()).unwrap();
+    assert_eq!(estimate.rows, plain.rows + 1);
+    let body = ascii_artinator_core::convert(common::open("circle.png"), &opts).unwrap();
+    assert_eq!(estimate.bytes, Some(body.len()));
+}
@@ -0,0 +1,56 @@
+//! `target_density` has to light about the fraction of dots asked for, and
+//! say what threshold it took to get there.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{GrayImage, Luma};
+use ascii_artinator_core::{preprocess, ConvertError};
+use common::parse;
+
+// lit dots over all the dots there are room for
+fn lit_fraction(rows: &[String]) -> f64 {
+    let cells: Vec<u32> = rows.iter().flat_map(|row| row.chars()).map(|c| c as u32 - 0x2800).collect();
+    let lit: u32 = cells.iter().map(|cell| cell.count_ones()).sum();
+    return lit as f64 / (cells.len() * 8) as f64;
+}
+
+#[test]
+fn lands_near_the_density_on_a_gradient() {
+    for density in [0.25, 0.5, 0.75] {
+        // whole cells, so there's no padding counted as unlit
+        let rows = common::rows_of("grad16.png", &[
+            ("target_density", &density.to_string()), ("blank_mode", "braille"), ("src_width", "60"), ("src_height", "32")
+        ]);
+        let lit = lit_fraction(&rows);
+        assert!((lit - density).abs() < 0.05, "{} lit for {}", lit, density);
+    }
+}
+
+#[test]
+fn threshold_search_is_close_on_a_smooth_ramp() {
+    let ramp = GrayImage::from_fn(256, 4, |x, _| Luma([x as u8]));
+    for density in [0.0, 0.1, 0.5, 0.9, 1.0] {
+        let threshold = preprocess::density_threshold(&ramp, density);
+        let lit = ramp.pixels().filter(|pix| pix.0[0] > threshold).count() as f32 / ramp.pixels().len() as f32;
+        assert!((lit - density).abs() <= 1.0 / 256.0 + f32::EPSILON, "{} lit for {}", lit, density);
+    }
+}
+
+#[test]
+fn reports_the_threshold_it_picked() {
+    let opts = parse(&[("target_density", "0.5")]).unwrap();
+    let (_, applied) = ascii_artinator_core::convert_with_applied(common::open("grad16.png"), &opts).unwrap();
+    assert!(applied.stencil);
+    let expected = preprocess::density_threshold(&ascii_artinator_core::to_gray(&common::open("grad16.png")), 0.5);
+    assert!(applied.stencil_threshold.abs_diff(expected) <= 16, "{} against {}", applied.stencil_threshold, expected);
+}
+
+#[test]
+fn rejects_bad_densities() {
+    for query in [[("target_density", "1.5")], [("target_density", "-0.1")]] {
+        assert!(matches!(parse(&query), Err(ConvertError::BadOption(_))));
+    }
+    assert!(matches!(parse(&[("target_density", "0.5"), ("mode", "edges")]), Err(ConvertError::BadOption(_))));
+}
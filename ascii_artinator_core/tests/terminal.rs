@@ -0,0 +1,27 @@
+//! The `terminal` presets and how they combine with `aspect`.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::Terminal;
+use common::opts as parse;
+
+#[test]
+fn preset_sets_the_aspect() {
+    let opts = parse(&[("terminal", "kitty")]);
+    assert_eq!(opts.terminal, Terminal::Kitty);
+    assert_eq!(opts.aspect(), Terminal::Kitty.aspect());
+    assert_eq!(parse(&[("terminal", "iterm")]).aspect(), 0.9);
+}
+
+#[test]
+fn manual_aspect_goes_on_top() {
+    let opts = parse(&[("terminal", "iterm"), ("aspect", "2")]);
+    assert_eq!(opts.aspect(), 1.8);
+}
+
+#[test]
+fn no_preset_is_no_stretch() {
+    assert_eq!(parse(&[]).aspect(), 1.0);
+}
@@ -0,0 +1,51 @@
+//! `terminal_bg=light` puts the dots where `dark` leaves them out, and
+//! transparent parts stay blank on both. Left out, it's light.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{DynamicImage, Rgba, RgbaImage};
+use ascii_artinator_core::{ConvertOptions, TerminalBg};
+
+// black on the left, white on the right, see-through along the bottom
+fn halves() -> DynamicImage {
+    return DynamicImage::ImageRgba8(RgbaImage::from_fn(116, 64, |x, y| match (x < 58, y < 48) {
+        (_, false) => Rgba([255, 255, 255, 0]),
+        (true, true) => Rgba([0, 0, 0, 255]),
+        (false, true) => Rgba([255, 255, 255, 255]),
+    }));
+}
+
+fn rows_with(terminal_bg: &str) -> Vec<Vec<char>> {
+    let opts = common::opts(&[("terminal_bg", terminal_bg)]);
+    let rows = ascii_artinator_core::convert_to_rows(halves(), &opts, None).unwrap();
+    return rows.iter().map(|row| row.chars().collect()).collect();
+}
+
+#[test]
+fn light_flips_dark() {
+    let (dark, light) = (rows_with("dark"), rows_with("light"));
+    assert_eq!(dark.len(), light.len());
+    let (full, blank) = ('⣿', '⠁');
+    // well inside each half, away from where the resize blends them
+    let (left, right) = (5, 24);
+    let opaque = dark.len() * 3 / 4 - 1;
+    for row in 0..opaque {
+        assert_eq!((dark[row][left], dark[row][right]), (blank, full), "row {}", row);
+        assert_eq!((light[row][left], light[row][right]), (full, blank), "row {}", row);
+    }
+    for row in opaque + 1..dark.len() {
+        assert!(dark[row].iter().chain(&light[row]).all(|&c| c == blank), "row {}", row);
+    }
+}
+
+#[test]
+fn light_is_the_default() {
+    let plain = ascii_artinator_core::convert_to_rows(halves(), &ConvertOptions::default(), None).unwrap();
+    let light: Vec<String> = rows_with("light").into_iter().map(|row| row.into_iter().collect()).collect();
+    assert_eq!(plain, light);
+    assert_eq!(ConvertOptions::default().resolved_terminal_bg(), Some(TerminalBg::Light));
+    // rgbsplit has colors of its own and isn't flipped either way
+    assert_eq!(common::opts(&[("mode", "rgbsplit")]).resolved_terminal_bg(), None);
+}
@@ -0,0 +1,32 @@
+//! Text drawn straight to braille has to keep coming out the same.
+
+#![allow(clippy::needless_return)]
+
+use ascii_artinator_core::{text_to_braille, BlankMode, ConvertError, TextFont, MAX_TEXT_CHARS};
+
+#[test]
+fn single_letter_is_stable() {
+    assert_eq!(text_to_braille("A", TextFont::Small, BlankMode::Braille).unwrap(), ["⡮⡆", "⠁⠁"]);
+    assert_eq!(text_to_braille("A", TextFont::Big, BlankMode::Braille).unwrap(), ["⣤⠛⣤", "⣿⠛⣿", "⠛⠀⠛"]);
+    // lowercase is drawn as uppercase
+    assert_eq!(
+        text_to_braille("a", TextFont::Big, BlankMode::Braille),
+        text_to_braille("A", TextFont::Big, BlankMode::Braille)
+    );
+}
+
+#[test]
+fn lines_stay_lines() {
+    let one = text_to_braille("HI", TextFont::Big, BlankMode::Dot).unwrap();
+    let two = text_to_braille("HI\nHI", TextFont::Big, BlankMode::Dot).unwrap();
+    assert_eq!(two.len(), one.len() * 2);
+    assert_eq!(two[..one.len()], one[..]);
+}
+
+#[test]
+fn text_is_bounded() {
+    assert!(matches!(text_to_braille(" ", TextFont::Big, BlankMode::Dot), Err(ConvertError::BadOption(_))));
+    let long = "A".repeat(MAX_TEXT_CHARS + 1);
+    assert!(matches!(text_to_braille(&long, TextFont::Big, BlankMode::Dot), Err(ConvertError::BadOption(_))));
+    assert!(text_to_braille(&long[1..], TextFont::Small, BlankMode::Dot).is_ok());
+}
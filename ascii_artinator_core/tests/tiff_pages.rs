@@ -0,0 +1,88 @@
+//! `page` picks a page of a multi-page TIFF, and pages past the end say so;
+//! `frames=all` converts every page instead.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::image::{self, GrayImage, Luma};
+use ascii_artinator_core::ConvertError;
+
+// two pages told apart by which half is lit
+fn two_pages() -> (Vec<u8>, [GrayImage; 2]) {
+    let pages = [
+        GrayImage::from_fn(16, 8, |x, _| Luma([if x < 8 { 255 } else { 0 }])),
+        GrayImage::from_fn(16, 8, |x, _| Luma([if x < 8 { 0 } else { 255 }])),
+    ];
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    let mut encoder = tiff::encoder::TiffEncoder::new(&mut bytes).unwrap();
+    for page in &pages {
+        encoder.write_image::<tiff::encoder::colortype::Gray8>(page.width(), page.height(), page.as_raw()).unwrap();
+    }
+    return (bytes.into_inner(), pages);
+}
+
+fn decode_page(bytes: &[u8], page: &str) -> Result<image::DynamicImage, ConvertError> {
+    let opts = common::opts(&[("page", page)]);
+    // no hints, so it has to go by the magic bytes
+    return ascii_artinator_core::decode_with_hints(bytes, None, None, ascii_artinator_core::DEFAULT_MAX_PIXELS, &opts);
+}
+
+#[test]
+fn first_and_last_page() {
+    let (bytes, pages) = two_pages();
+    assert_eq!(image::guess_format(&bytes).unwrap(), image::ImageFormat::Tiff);
+    assert_eq!(decode_page(&bytes, "0").unwrap().to_luma8(), pages[0]);
+    assert_eq!(decode_page(&bytes, "1").unwrap().to_luma8(), pages[1]);
+}
+
+#[test]
+fn past_the_last_page() {
+    let (bytes, _) = two_pages();
+    assert_eq!(decode_page(&bytes, "2").unwrap_err(), ConvertError::NoSuchFrame(2));
+}
+
+#[test]
+fn all_pages() {
+    let (bytes, pages) = two_pages();
+    let max_pixels = ascii_artinator_core::DEFAULT_MAX_PIXELS;
+    let (decoded, total) = ascii_artinator_core::frames::tiff_pages(&bytes, 0, 10, max_pixels, None).unwrap();
+    assert_eq!(total, 2);
+    let decoded: Vec<GrayImage> = decoded.iter().map(|page| page.to_luma8()).collect();
+    assert_eq!(decoded, pages);
+    let (rest, total) = ascii_artinator_core::frames::tiff_pages(&bytes, 1, 10, max_pixels, None).unwrap();
+    assert_eq!((rest.len(), total), (1, 2));
+}
+
+#[test]
+fn frames_all_converts_every_page() {
+    let (bytes, pages) = two_pages();
+    let opts = common::opts(&[("frames", "all"), ("cols", "8"), ("rows", "2")]);
+    let max_pixels = ascii_artinator_core::DEFAULT_MAX_PIXELS;
+    let decoded = ascii_artinator_core::frames::decode_all_with_hints(&bytes, None, None, max_pixels, &opts).unwrap();
+    assert_eq!(decoded.iter().map(|page| page.to_luma8()).collect::<Vec<_>>(), pages);
+    let mut all = String::new();
+    ascii_artinator_core::write_frames(&mut all, decoded, &opts).unwrap();
+    // each page the way it converts on its own, one under the other
+    let one_by_one: Vec<String> = ["0", "1"].iter().map(|page| {
+        let opts = common::opts(&[("page", page), ("cols", "8"), ("rows", "2")]);
+        return ascii_artinator_core::convert(decode_page(&bytes, page).unwrap(), &opts).unwrap();
+    }).collect();
+    assert_eq!(all, one_by_one.join("\n"));
+    assert_ne!(one_by_one[0], one_by_one[1]);
+}
+
+#[test]
+fn one_page_or_all_of_them() {
+    let err = |query: &[(&str, &str)]| common::parse(query).unwrap_err();
+    assert_eq!(err(&[("frame", "0"), ("page", "1")]), ConvertError::BadOption(
+        "frame and page are the same thing, so only one of them".to_owned()
+    ));
+    assert_eq!(err(&[("frames", "all"), ("page", "1")]), ConvertError::BadOption(
+        "frames=all and frame or page don't go together".to_owned()
+    ));
+    assert_eq!(err(&[("frames", "all"), ("format", "html")]), ConvertError::BadOption(
+        "frames=all only comes as text".to_owned()
+    ));
+    assert_eq!(err(&[("frames", "some")]), ConvertError::BadOption("bad value for frames: some".to_owned()));
+}
@@ -0,0 +1,54 @@
+//! `trim_bg=auto` blanks out the color the corners share, within
+//! `trim_tolerance`, and with `trim_crop` cuts it off.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::braille::BrailleGrid;
+use ascii_artinator_core::image::{DynamicImage, Rgb, RgbImage};
+
+// light gray, a bit noisy, with a long black bar in the middle
+fn product_shot() -> DynamicImage {
+    return DynamicImage::ImageRgb8(RgbImage::from_fn(80, 80, |x, y| {
+        if (10..70).contains(&x) && (35..45).contains(&y) {
+            return Rgb([0, 0, 0]);
+        }
+        let noise = ((x * 7 + y * 13) % 11) as u8;
+        return Rgb([195 + noise, 195 + noise, 195 + noise]);
+    }));
+}
+
+// how full the dots are, and the output's size in chars
+fn fill(query: &[(&str, &str)]) -> (f64, (usize, usize)) {
+    let mut query = query.to_vec();
+    query.push(("blank_mode", "braille"));
+    // dots where it's bright, so the black bar is blank
+    query.push(("terminal_bg", "dark"));
+    let rows = ascii_artinator_core::convert_to_rows(product_shot(), &common::opts(&query), None).unwrap();
+    return (BrailleGrid::from_rows(&rows).stats().fill, (rows[0].chars().count(), rows.len()));
+}
+
+#[test]
+fn gray_background_goes_blank() {
+    let (untrimmed, _) = fill(&[]);
+    assert!(untrimmed > 50.0, "{}", untrimmed);
+    // the bar is black anyway, so nothing's left
+    assert_eq!(fill(&[("trim_bg", "auto")]).0, 0.0);
+}
+
+#[test]
+fn tolerance_is_how_close_counts() {
+    // the noise is more than nothing, so only part of it goes
+    let (strict, _) = fill(&[("trim_bg", "auto"), ("trim_tolerance", "0")]);
+    assert!(strict > 0.0);
+    assert!(strict < fill(&[]).0);
+}
+
+#[test]
+fn crop_cuts_it_off() {
+    let (_, (cols, rows)) = fill(&[("trim_bg", "auto")]);
+    // down to the bar, which is much wider than it's tall
+    let (_, (cropped_cols, cropped_rows)) = fill(&[("trim_bg", "auto"), ("trim_crop", "true")]);
+    assert!(cropped_cols * rows > cols * cropped_rows * 2, "{}x{} {}x{}", cols, rows, cropped_cols, cropped_rows);
+}
@@ -0,0 +1,51 @@
+//! JPEGs cut off partway: a clean error from the normal decode, and from
+//! the tolerant one whatever arrived, unless too little did.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::{decode_truncated_jpeg, decode_with_hints, ConvertError, ConvertOptions, DEFAULT_MAX_PIXELS};
+
+fn circle_jpeg() -> Vec<u8> {
+    let mut jpeg = Vec::new();
+    common::open("circle.png")
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), ascii_artinator_core::image::ImageOutputFormat::Jpeg(90))
+        .unwrap();
+    return jpeg;
+}
+
+fn decode(bytes: &[u8]) -> Result<ascii_artinator_core::image::DynamicImage, ConvertError> {
+    return decode_with_hints(bytes, None, None, DEFAULT_MAX_PIXELS, &ConvertOptions::default());
+}
+
+#[test]
+fn half_a_jpeg_is_a_clean_error() {
+    let jpeg = circle_jpeg();
+    let half = &jpeg[..jpeg.len() / 2];
+    assert!(matches!(decode(half), Err(ConvertError::Decode)));
+}
+
+#[test]
+fn half_a_jpeg_decodes_tolerantly() {
+    let jpeg = circle_jpeg();
+    let full = decode(&jpeg).unwrap();
+    let half = decode_truncated_jpeg(&jpeg[..jpeg.len() / 2], DEFAULT_MAX_PIXELS, &ConvertOptions::default()).unwrap();
+    assert_eq!((half.width(), half.height()), (full.width(), full.height()));
+    // the top made it, the bottom didn't
+    let (full, half) = (full.to_luma8(), half.to_luma8());
+    assert_eq!(half.get_pixel(0, 0), full.get_pixel(0, 0));
+    let last = full.height() - 1;
+    assert!((0..full.width()).any(|x| half.get_pixel(x, last) != full.get_pixel(x, last)));
+}
+
+#[test]
+fn too_little_is_still_an_error() {
+    let jpeg = circle_jpeg();
+    let opts = ConvertOptions::default();
+    for len in [0, 2, 20] {
+        assert!(decode_truncated_jpeg(&jpeg[..len], DEFAULT_MAX_PIXELS, &opts).is_err(), "{}", len);
+    }
+    // and a whole one isn't the tolerant path's business
+    assert!(matches!(decode_truncated_jpeg(&jpeg, DEFAULT_MAX_PIXELS, &opts), Err(ConvertError::Decode)));
+}
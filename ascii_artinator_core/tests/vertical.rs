@@ -0,0 +1,29 @@
+//! `layout=vertical` puts every char on a line of its own, row by row, left
+//! to right.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::rows::to_vertical;
+
+#[test]
+fn n_by_m_is_n_times_m_lines() {
+    let rows = vec!["⠁⠂⠃".to_owned(), "⠄⠅⠆".to_owned()];
+    assert_eq!(to_vertical(&rows), ["⠁", "⠂", "⠃", "⠄", "⠅", "⠆"]);
+}
+
+#[test]
+fn colors_stay_with_their_char() {
+    let rows = vec!["\x1b[31m⠁⠂\x1b[0m".to_owned()];
+    assert_eq!(to_vertical(&rows), ["\x1b[31m⠁", "⠂\x1b[0m"]);
+}
+
+#[test]
+fn the_conversion_in_order() {
+    let plain = common::rows_of("circle.png", &[("cols", "6"), ("rows", "3")]);
+    let vertical = common::rows_of("circle.png", &[("cols", "6"), ("rows", "3"), ("layout", "vertical")]);
+    assert_eq!(vertical.len(), 6 * 3);
+    let flat: Vec<String> = plain.iter().flat_map(|row| row.chars().map(String::from)).collect();
+    assert_eq!(vertical, flat);
+}
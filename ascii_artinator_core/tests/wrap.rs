@@ -0,0 +1,54 @@
+//! `wrap=N` reflows wide output into stacked blocks of at most N columns,
+//! with a blank line between them.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use ascii_artinator_core::rows::wrap_columns;
+
+// 160 columns, each row told apart by its char, each half by its color
+fn wide(rows: usize) -> Vec<String> {
+    return (0..rows).map(|row| {
+        let c = char::from_u32(0x2801 + row as u32).unwrap();
+        return format!("\x1b[31m{}\x1b[34m{}\x1b[0m", c.to_string().repeat(80), c.to_string().repeat(80));
+    }).collect();
+}
+
+#[test]
+fn one_sixty_is_two_blocks_of_eighty() {
+    let wrapped = wrap_columns(&wide(3), 80);
+    // 3 rows, a blank line, 3 more
+    assert_eq!(wrapped.len(), 7);
+    assert_eq!(wrapped[3], "");
+    for (i, row) in wrapped.iter().enumerate().filter(|(i, _)| *i != 3) {
+        assert_eq!(ascii_artinator_core::rows::visible_chars(row).count(), 80, "{}", i);
+    }
+    // the left halves first, in order, then the right ones
+    assert!(wrapped[0].starts_with("\x1b[31m⠁"));
+    assert!(wrapped[2].contains('⠃'));
+    assert!(wrapped[4].contains("\x1b[34m⠁"));
+}
+
+#[test]
+fn narrow_enough_is_untouched() {
+    let rows = wide(2);
+    assert_eq!(wrap_columns(&rows, 160), rows);
+    assert_eq!(wrap_columns(&rows, 200), rows);
+}
+
+#[test]
+fn uneven_last_block() {
+    let rows = vec!["⠁⠂⠃⠄⠅".to_owned()];
+    assert_eq!(wrap_columns(&rows, 2), ["⠁⠂", "", "⠃⠄", "", "⠅"]);
+}
+
+#[test]
+fn wraps_the_conversion() {
+    let query = [("cols", "20"), ("rows", "5")];
+    let plain = common::rows_of("circle.png", &query);
+    let wrapped = common::rows_of("circle.png", &[query[0], query[1], ("wrap", "10")]);
+    assert_eq!(wrapped.len(), 5 * 2 + 1);
+    let left: Vec<String> = plain.iter().map(|row| row.chars().take(10).collect()).collect();
+    assert_eq!(wrapped[..5], left[..]);
+}
@@ -1,8 +1,12 @@
 //! This is a simple implementation of a web interface for the API.
 
+#![allow(clippy::needless_return)]
+
+use wasm_bindgen::JsCast;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use gloo_net::http::Request;
+use futures::future::{select, Either};
 
 /// This will return the API endpoint, which can be set via an environment
 /// variable, defaulting to same host, same port, "/braille".
@@ -10,6 +14,59 @@ fn get_endpoint() -> &'static str {
     return option_env!("AA_ENDPOINT").unwrap_or("/braille");
 }
 
+/// How long to wait for the API before giving up, in milliseconds. Can be set
+/// via an environment variable, defaulting to 30 seconds.
+fn get_timeout_ms() -> u32 {
+    return option_env!("AA_TIMEOUT_MS")
+        .and_then(|ms| ms.parse().ok())
+        .unwrap_or(30_000);
+}
+
+/// How long the page may sit untouched before it clears itself, in
+/// milliseconds, for kiosks. Can be set via an environment variable; unset or
+/// 0, it never does.
+fn get_idle_ms() -> Option<u32> {
+    return option_env!("AA_IDLE_MS")
+        .and_then(|ms| ms.parse().ok())
+        .filter(|&ms| ms > 0);
+}
+
+/// The charsets in the dropdown, by their name in the query and then the
+/// one shown.
+const CHARSETS: [(&str, &str); 4] = [
+    ("braille", "Braille"), ("ascii", "ASCII"), ("halfblock", "Half blocks"), ("quadrant", "Quadrants")
+];
+
+/// Which of [`CHARSETS`] is picked until someone picks another. Can be set
+/// via the AA_DEFAULT_MODE environment variable at build time (or its older
+/// name, AA_DEFAULT_CHARSET), defaulting to braille.
+fn get_default_charset() -> &'static str {
+    return option_env!("AA_DEFAULT_MODE")
+        .or(option_env!("AA_DEFAULT_CHARSET"))
+        .filter(|charset| CHARSETS.iter().any(|(name, _)| name == charset))
+        .unwrap_or("braille");
+}
+
+/// Where the last charset picked is kept, so it's still picked next time.
+const CHARSET_KEY: &str = "aa_charset";
+
+fn local_storage() -> Option<web_sys::Storage> {
+    return web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+}
+
+/// The charset picked last time, if it's one there still is.
+fn stored_charset() -> Option<&'static str> {
+    let stored = local_storage()?.get_item(CHARSET_KEY).ok().flatten()?;
+    return CHARSETS.iter().map(|(name, _)| *name).find(|name| *name == stored);
+}
+
+/// Known-good images for the "Try a sample" button, cycled on every click.
+const SAMPLE_URLS: [&str; 3] = [
+    "https://www.rust-lang.org/logos/rust-logo-512x512.png",
+    "https://upload.wikimedia.org/wikipedia/commons/4/47/PNG_transparency_demonstration_1.png",
+    "https://upload.wikimedia.org/wikipedia/en/a/a9/Example.jpg",
+];
+
 /// This enum entails the states the Braille display can be in.
 #[derive(PartialEq, Eq, Clone)]
 enum BrailleState {
@@ -20,7 +77,9 @@ enum BrailleState {
     /// Showing a braille string!
     Showing(AttrValue),
     /// Got an error.
-    Error(AttrValue)
+    Error(AttrValue),
+    /// Asked too often, and told to wait this many more seconds.
+    RateLimited(u32)
 }
 
 /// This component displays the API result.
@@ -30,14 +89,84 @@ struct BrailleProps {
 }
 
 /// This displays the current braille string, or some error.
-struct BrailleDisplay {}
+struct BrailleDisplay {
+    /// The last result shown before the current one, to highlight what
+    /// changed since.
+    previous: Option<AttrValue>
+}
+
+/// The rows of a result, which come joined with a space before each one,
+/// or one per line for the charsets that have spaces of their own.
+fn result_rows(s: &str) -> Vec<Vec<char>> {
+    if is_lines(s) {
+        return s.lines().map(|row| row.chars().collect()).collect();
+    }
+    return s.split(' ').filter(|row| !row.is_empty()).map(|row| row.chars().collect()).collect();
+}
+
+fn is_lines(s: &str) -> bool {
+    return s.contains('\n');
+}
+
+/// The rows out of a `format=html` answer, one per line, as far as it's
+/// come in.
+fn html_rows(s: &str) -> String {
+    let Some(start) = s.find("<pre").and_then(|pre| s[pre..].find(">\n").map(|end| pre + end + 2)) else {
+        return String::new();
+    };
+    let rows = &s[start..];
+    let rows = rows.split("</pre>").next().unwrap_or(rows).trim_end_matches('\n');
+    return rows.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+}
+
+/// The result with the cells that differ from `previous` in spans of their
+/// own, going row by row over the part both have.
+fn diff_cells(s: &str, previous: &str) -> Html {
+    let old_rows = result_rows(previous);
+    let mut nodes = vec![];
+    for (y, row) in result_rows(s).into_iter().enumerate() {
+        let old_row = old_rows.get(y);
+        let changed = |x: usize| old_row.and_then(|old| old.get(x)).is_some_and(|&old| old != row[x]);
+        if !is_lines(s) {
+            nodes.push(html! { { " " } });
+        } else if y > 0 {
+            nodes.push(html! { { "\n" } });
+        }
+        // runs of cells that did or didn't change, so it's not a node each
+        let mut start = 0;
+        while start < row.len() {
+            let run_changed = changed(start);
+            let end = (start..row.len()).find(|&x| changed(x) != run_changed).unwrap_or(row.len());
+            let run: String = row[start..end].iter().collect();
+            nodes.push(if run_changed {
+                html! { <span class="changed">{ run }</span> }
+            } else {
+                html! { { run } }
+            });
+            start = end;
+        }
+    }
+    return nodes.into_iter().collect();
+}
 
 impl Component for BrailleDisplay {
     type Message = ();
     type Properties = BrailleProps;
 
     fn create(_ctx: &Context<Self>) -> Self {
-        return Self {};
+        return Self { previous: None };
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        match (&old_props.state, &ctx.props().state) {
+            // streaming in, still the same result
+            (BrailleState::Showing(_), BrailleState::Showing(_)) => {},
+            // a new one is on its way, so this one's the one to compare to
+            (BrailleState::Showing(old), _) => self.previous = Some(old.clone()),
+            (_, BrailleState::Waiting) => self.previous = None,
+            _ => {},
+        }
+        return true;
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
@@ -48,11 +177,26 @@ impl Component for BrailleDisplay {
             BrailleState::Requesting => html! {
                 <div class="waiting">{ "Loading..." }</div>
             },
-            BrailleState::Showing(ref s) => html! {
-                <div class="braille">{ s }</div>
+            BrailleState::Showing(ref s) => {
+                let class = classes!("braille", is_lines(s).then_some("lines"));
+                match self.previous {
+                    Some(ref previous) => html! {
+                        <div {class}>{ diff_cells(s, previous) }</div>
+                    },
+                    None => html! {
+                        <div {class}>{ s }</div>
+                    },
+                }
             },
+            // a pre, so multi-line errors keep their line breaks
             BrailleState::Error(ref s) => html! {
-                <div class="error">{ s }</div>
+                <pre class="error">{ s }</pre>
+            },
+            BrailleState::RateLimited(0) => html! {
+                <div class="waiting">{ "You can try again now." }</div>
+            },
+            BrailleState::RateLimited(seconds) => html! {
+                <div class="waiting">{ format!("Too many requests, try again in {}s.", seconds) }</div>
             },
         }
     }
@@ -63,7 +207,27 @@ struct App {
     /// The URL currently in the form.
     url: String,
     /// The state for the Braille component.
-    state: BrailleState
+    state: BrailleState,
+    /// Index of the next sample to show.
+    next_sample: usize,
+    /// Aborts the request still going, if there is one.
+    request: Option<web_sys::AbortController>,
+    /// Bumped with every request, so a replaced one can't show up late.
+    request_id: usize,
+    /// Whether to ask for a stencil, cut at `threshold`.
+    stencil: bool,
+    threshold: u8,
+    /// The threshold the server suggests for the last image, if it said.
+    suggested_threshold: Option<u8>,
+    /// One of the names in [`CHARSETS`].
+    charset: &'static str,
+    /// Whether to go again by itself once a rate limit is over.
+    auto_retry: bool,
+    /// Counts the rate limit down while there is one.
+    countdown: Option<gloo_timers::callback::Interval>,
+    /// Clears the page when it goes off; replaced on every interaction,
+    /// which cancels the one before.
+    idle_timer: Option<gloo_timers::callback::Timeout>
 }
 
 /// This entails the messages the app can send to itself.
@@ -72,48 +236,314 @@ enum AppMsg {
     UrlChange(String),
     /// Generate button hit.
     GenBraille,
-    /// Set the BrailleDisplay state.
-    SetBrailleState(BrailleState)
+    /// Sample button hit, load the next sample and generate right away.
+    TrySample,
+    /// Set the BrailleDisplay state, if the request it's from is still the
+    /// latest one.
+    RequestState(usize, BrailleState),
+    /// The threshold the server suggests, also only from the latest request.
+    SuggestedThreshold(usize, Option<u8>),
+    /// Stencil checkbox toggled.
+    StencilToggle(bool),
+    /// Threshold slider moved.
+    ThresholdChange(u8),
+    /// Another charset picked in the dropdown.
+    CharsetChange(String),
+    /// Retry checkbox toggled.
+    AutoRetryToggle(bool),
+    /// A second of the rate limit went by.
+    CountdownTick,
+    /// Suggested threshold clicked, use it and generate again.
+    ApplySuggestion,
+    /// Nobody touched anything for a while, back to the start.
+    Idle,
+    /// A local file was picked, to be converted right here.
+    #[cfg(feature = "local")]
+    FileChosen(web_sys::File)
 }
 
-/// This is a function that returns a Future for an AppMsg. This way, we can
-/// pass it to send_future and change the state of the component asynchronously
-/// (namely, when the request finishes).
-async fn do_request(img_url: String) -> AppMsg {
-    let params = [
-        ("img_url", &img_url)
-    ];
+/// This does the actual request, reading the body piece by piece and
+/// passing what has arrived so far to `on_state` every time more does, so
+/// long answers show up as they stream in instead of all at the end. The
+/// suggested threshold goes to `on_hint` as soon as the headers are in. With
+/// `html`, the body is a `format=html` page, and only its rows are passed on.
+/// A 429 with a `Retry-After` it can make sense of is passed on as the wait.
+async fn stream_braille(
+    params: Vec<(&'static str, String)>,
+    signal: web_sys::AbortSignal,
+    on_state: Callback<BrailleState>,
+    on_hint: Callback<Option<u8>>
+) {
+    let html = params.iter().any(|(key, val)| *key == "format" && val == "html");
+    let shown = |s: String| if html { html_rows(&s) } else { s };
     let req = Request::get(get_endpoint())
         .query(params)
+        .abort_signal(Some(&signal))
         .send()
         .await;
-    let bs: BrailleState = match req {
-        Ok(resp) => {
-            // request sent
-            if resp.ok() {
-                // response is 200
-                match resp.text().await {
-                    // body could be decoded as text (braille!)
-                    Ok(s) => BrailleState::Showing(s.into()),
-                    // body could not be decoded as text (what?)
-                    Err(e) => BrailleState::Error(e.to_string().into())
+    let resp = match req {
+        Ok(resp) => resp,
+        // request failed to send
+        Err(err) => return on_state.emit(BrailleState::Error(err.to_string().into())),
+    };
+    if resp.status() == 429 {
+        let wait = resp.headers().get("retry-after").and_then(|val| retry_after_seconds(&val));
+        if let Some(seconds) = wait {
+            return on_state.emit(BrailleState::RateLimited(seconds));
+        }
+    }
+    if !resp.ok() {
+        // response is not 200 (i.e. error), no use showing half of it
+        let bs = match resp.text().await {
+            // error body could be decoded as text (okay)
+            Ok(s) => BrailleState::Error(s.into()),
+            // error body could *not* be decoded as text (what?!)
+            Err(e) => BrailleState::Error(e.to_string().into())
+        };
+        return on_state.emit(bs);
+    }
+    // older servers don't send it, and then there's just no hint
+    on_hint.emit(resp.headers().get("x-suggested-threshold").and_then(|t| t.parse().ok()));
+    let reader = resp.body()
+        .and_then(|body| body.get_reader().dyn_into::<web_sys::ReadableStreamDefaultReader>().ok());
+    let Some(reader) = reader else {
+        // no body at all
+        return on_state.emit(BrailleState::Showing("".into()));
+    };
+    let mut bytes = Vec::new();
+    loop {
+        let chunk = match wasm_bindgen_futures::JsFuture::from(reader.read()).await {
+            Ok(chunk) => chunk,
+            // aborted, or the connection dropped halfway
+            Err(_) => return on_state.emit(BrailleState::Error("The answer got cut off.".into())),
+        };
+        let done = js_sys::Reflect::get(&chunk, &"done".into())
+            .ok()
+            .and_then(|done| done.as_bool())
+            .unwrap_or(true);
+        if done {
+            break;
+        }
+        if let Ok(value) = js_sys::Reflect::get(&chunk, &"value".into()) {
+            bytes.extend(js_sys::Uint8Array::new(&value).to_vec());
+        }
+        // a chunk can end halfway through a char, the rest of it comes next
+        let valid_up_to = match std::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            Err(e) => e.valid_up_to(),
+        };
+        let so_far = String::from_utf8_lossy(&bytes[..valid_up_to]).into_owned();
+        on_state.emit(BrailleState::Showing(shown(so_far).into()));
+    }
+    on_state.emit(BrailleState::Showing(shown(String::from_utf8_lossy(&bytes).into_owned()).into()));
+}
+
+/// How many seconds a `Retry-After` says to wait, from either the number of
+/// them or the date to wait until.
+fn retry_after_seconds(val: &str) -> Option<u32> {
+    let val = val.trim();
+    if let Ok(seconds) = val.parse() {
+        return Some(seconds);
+    }
+    let until = js_sys::Date::parse(val);
+    if until.is_nan() {
+        return None;
+    }
+    // a date already gone by is no wait at all
+    return Some(((until - js_sys::Date::now()) / 1000.0).ceil().max(0.0) as u32);
+}
+
+/// Starts a request in the background. Every state it goes through is passed
+/// on to `on_state`, until it's done, takes too long, or gets aborted through
+/// `controller`.
+fn do_request(
+    params: Vec<(&'static str, String)>,
+    controller: web_sys::AbortController,
+    on_state: Callback<BrailleState>,
+    on_hint: Callback<Option<u8>>
+) {
+    yew::platform::spawn_local(async move {
+        let timeout = gloo_timers::future::TimeoutFuture::new(get_timeout_ms());
+        let streaming = stream_braille(params, controller.signal(), on_state.clone(), on_hint);
+        // timeout finished first
+        if let Either::Right(_) = select(Box::pin(streaming), timeout).await {
+            // dropping the future doesn't stop the fetch itself, this does
+            controller.abort();
+            on_state.emit(BrailleState::Error("The server took too long to answer.".into()));
+        }
+    });
+}
+
+/// Like do_request, but converts a local file in the browser itself, so it
+/// never goes over the network. Remote URLs still go through the server,
+/// since fetching them from here would run into CORS. `id` is the request
+/// it's for, so a slow one can't show up over a newer one.
+#[cfg(feature = "local")]
+async fn convert_file(id: usize, file: web_sys::File, stencil: Option<u8>, charset: &'static str) -> AppMsg {
+    let file = gloo_file::File::from(file);
+    let bs: BrailleState = match gloo_file::futures::read_as_bytes(&file).await {
+        Ok(bytes) => {
+            // no content-type to go by here, so guess from the magic bytes
+            let format = ascii_artinator_core::image::guess_format(&bytes).ok();
+            let query = [("charset".to_owned(), charset.to_owned())].into_iter().collect();
+            let converted = ascii_artinator_core::parse_options(&query, &Default::default()).and_then(|mut opts| {
+                if let Some(threshold) = stencil {
+                    opts.stencil = true;
+                    opts.stencil_threshold = threshold;
                 }
-            } else {
-                // response is not 200 (i.e. error)
-                match resp.text().await {
-                    // error body could be decoded as text (okay)
-                    Ok(s) => BrailleState::Error(s.into()),
-                    // error body could *not* be decoded as text (what?!)
-                    Err(e) => BrailleState::Error(e.to_string().into())
+                // the same rows one per line the server's html would have
+                if charset != "braille" {
+                    let img = ascii_artinator_core::decode(&bytes, format, ascii_artinator_core::DEFAULT_MAX_PIXELS, &opts)?;
+                    return Ok(ascii_artinator_core::convert_to_rows(img, &opts, None)?.join("\n"));
                 }
+                return ascii_artinator_core::convert_bytes(&bytes, format, &opts);
+            });
+            match converted {
+                Ok(s) => BrailleState::Showing(s.into()),
+                Err(e) => BrailleState::Error(e.to_string().into())
             }
         },
-        // request failed to send
-        Err(err) => BrailleState::Error(err.to_string().into()),
+        // file could not be read
+        Err(e) => BrailleState::Error(e.to_string().into())
     };
-    // this message tells the App component to change the state property of
-    // its BrailleDisplay component, thus triggering a redraw
-    return AppMsg::SetBrailleState(bs);
+    return AppMsg::RequestState(id, bs);
+}
+
+impl App {
+    /// Aborts the request still going, if any, and makes sure nothing it
+    /// sends from here on is shown.
+    fn cancel_request(&mut self) {
+        if let Some(controller) = self.request.take() {
+            controller.abort();
+        }
+        self.request_id += 1;
+        self.countdown = None;
+    }
+
+    /// Starts the idle timer over, if there is one.
+    fn reset_idle_timer(&mut self, ctx: &Context<Self>) {
+        self.idle_timer = get_idle_ms().map(|ms| {
+            let link = ctx.link().clone();
+            return gloo_timers::callback::Timeout::new(ms, move || link.send_message(AppMsg::Idle));
+        });
+    }
+
+    /// The stencil threshold to ask for, if the stencil is on.
+    fn stencil(&self) -> Option<u8> {
+        return self.stencil.then_some(self.threshold);
+    }
+
+    /// The query for the server, with the options picked in the form.
+    fn query_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("img_url", self.url.clone()), ("charset", self.charset.to_owned())];
+        // rows one per line, since other charsets have spaces in them
+        if self.charset != "braille" {
+            params.push(("format", "html".to_owned()));
+        }
+        if let Some(threshold) = self.stencil() {
+            params.push(("stencil", "true".to_owned()));
+            params.push(("stencil_threshold", threshold.to_string()));
+        }
+        return params;
+    }
+
+    /// The stencil checkbox and slider, and the suggested threshold next to
+    /// them once there is one.
+    fn view_threshold(&self, ctx: &Context<Self>) -> Html {
+        let toggle_cb = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            return AppMsg::StencilToggle(input.checked());
+        });
+        let slider_cb = ctx.link().batch_callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            return input.value().parse().ok().map(AppMsg::ThresholdChange);
+        });
+        let hint = match self.suggested_threshold {
+            Some(threshold) => {
+                let apply_cb = ctx.link().callback(|_e: MouseEvent| AppMsg::ApplySuggestion);
+                html! {
+                    <button class="hint" onclick={apply_cb}>{ format!("Try {}", threshold) }</button>
+                }
+            },
+            None => html! {},
+        };
+        return html! {
+            <>
+                <br />
+                <label>
+                    <input onchange={toggle_cb} type="checkbox" checked={self.stencil} />
+                    { " Stencil at " }
+                </label>
+                <input
+                    oninput={slider_cb}
+                    type="range"
+                    min="0"
+                    max="255"
+                    value={self.threshold.to_string()}
+                    disabled={!self.stencil}
+                />
+                { format!(" {} ", self.threshold) }
+                { hint }
+            </>
+        }
+    }
+
+    /// The checkbox for going again by itself after a rate limit.
+    fn view_retry(&self, ctx: &Context<Self>) -> Html {
+        let retry_cb = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            return AppMsg::AutoRetryToggle(input.checked());
+        });
+        return html! {
+            <>
+                <br />
+                <label>
+                    <input onchange={retry_cb} type="checkbox" checked={self.auto_retry} />
+                    { " Try again when allowed" }
+                </label>
+            </>
+        }
+    }
+
+    /// The dropdown for the charset.
+    fn view_charset(&self, ctx: &Context<Self>) -> Html {
+        let charset_cb = ctx.link().callback(|e: Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            return AppMsg::CharsetChange(select.value());
+        });
+        return html! {
+            <>
+                <br />
+                <select onchange={charset_cb}>
+                    { for CHARSETS.iter().map(|(name, label)| html! {
+                        <option value={*name} selected={*name == self.charset}>{ *label }</option>
+                    }) }
+                </select>
+            </>
+        }
+    }
+
+    /// The file picker for local conversion, if it's compiled in.
+    #[cfg(feature = "local")]
+    fn view_file_input(ctx: &Context<Self>) -> Html {
+        let file_cb = ctx.link().batch_callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            return input.files()
+                .and_then(|files| files.get(0))
+                .map(AppMsg::FileChosen);
+        });
+        return html! {
+            <>
+                <br />
+                <input onchange={file_cb} type="file" accept="image/*" />
+            </>
+        }
+    }
+
+    #[cfg(not(feature = "local"))]
+    fn view_file_input(_ctx: &Context<Self>) -> Html {
+        return html! {};
+    }
 }
 
 impl Component for App {
@@ -123,18 +553,126 @@ impl Component for App {
     fn create(_ctx: &Context<Self>) -> Self {
         return Self {
             url: "".to_owned(),
-            state: BrailleState::Waiting
+            state: BrailleState::Waiting,
+            next_sample: 0,
+            request: None,
+            request_id: 0,
+            stencil: false,
+            threshold: 128,
+            suggested_threshold: None,
+            charset: stored_charset().unwrap_or_else(get_default_charset),
+            auto_retry: false,
+            countdown: None,
+            idle_timer: None
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        // requests coming back and the timer itself don't count as anyone
+        // being there
+        let from_user = !matches!(
+            msg,
+            AppMsg::RequestState(..)
+                | AppMsg::SuggestedThreshold(..)
+                | AppMsg::CountdownTick
+                | AppMsg::Idle
+        );
+        if from_user {
+            self.reset_idle_timer(ctx);
+        }
         match msg {
             AppMsg::UrlChange(s) => self.url = s,
             AppMsg::GenBraille => {
+                self.cancel_request();
+                self.suggested_threshold = None;
+                let id = self.request_id;
+                match web_sys::AbortController::new() {
+                    Ok(controller) => {
+                        self.state = BrailleState::Requesting;
+                        self.request = Some(controller.clone());
+                        let on_state = ctx.link().callback(move |bs| AppMsg::RequestState(id, bs));
+                        let on_hint = ctx.link().callback(move |t| AppMsg::SuggestedThreshold(id, t));
+                        do_request(self.query_params(), controller, on_state, on_hint);
+                    },
+                    Err(_) => self.state = BrailleState::Error("This browser can't make requests.".into()),
+                }
+            },
+            AppMsg::TrySample => {
+                self.url = SAMPLE_URLS[self.next_sample].to_owned();
+                self.next_sample = (self.next_sample + 1) % SAMPLE_URLS.len();
+                ctx.link().send_message(AppMsg::GenBraille);
+            },
+            AppMsg::RequestState(id, bs) => {
+                // left over from a request that was replaced, ignore it
+                if id != self.request_id {
+                    return false;
+                }
+                if let BrailleState::RateLimited(seconds) = bs {
+                    self.request = None;
+                    if seconds > 0 {
+                        let link = ctx.link().clone();
+                        let tick = move || link.send_message(AppMsg::CountdownTick);
+                        self.countdown = Some(gloo_timers::callback::Interval::new(1000, tick));
+                    } else if self.auto_retry {
+                        ctx.link().send_message(AppMsg::GenBraille);
+                    }
+                }
+                self.state = bs;
+            },
+            AppMsg::CountdownTick => {
+                let BrailleState::RateLimited(seconds) = self.state else {
+                    return false;
+                };
+                let seconds = seconds.saturating_sub(1);
+                self.state = BrailleState::RateLimited(seconds);
+                if seconds == 0 {
+                    self.countdown = None;
+                    if self.auto_retry {
+                        ctx.link().send_message(AppMsg::GenBraille);
+                    }
+                }
+            },
+            AppMsg::SuggestedThreshold(id, threshold) => {
+                if id != self.request_id {
+                    return false;
+                }
+                self.suggested_threshold = threshold;
+            },
+            AppMsg::StencilToggle(on) => self.stencil = on,
+            AppMsg::ThresholdChange(threshold) => self.threshold = threshold,
+            AppMsg::AutoRetryToggle(on) => self.auto_retry = on,
+            AppMsg::CharsetChange(charset) => {
+                let Some(&(name, _)) = CHARSETS.iter().find(|(name, _)| *name == charset) else {
+                    return false;
+                };
+                self.charset = name;
+                // not being able to keep it only means picking it again
+                if let Some(storage) = local_storage() {
+                    let _ = storage.set_item(CHARSET_KEY, name);
+                }
+            },
+            AppMsg::Idle => {
+                self.cancel_request();
+                self.url.clear();
+                self.state = BrailleState::Waiting;
+                self.suggested_threshold = None;
+                self.idle_timer = None;
+            },
+            AppMsg::ApplySuggestion => {
+                if let Some(threshold) = self.suggested_threshold {
+                    self.stencil = true;
+                    self.threshold = threshold;
+                    ctx.link().send_message(AppMsg::GenBraille);
+                }
+            },
+            #[cfg(feature = "local")]
+            AppMsg::FileChosen(file) => {
+                self.cancel_request();
+                // that's only worked out by the server
+                self.suggested_threshold = None;
                 self.state = BrailleState::Requesting;
-                ctx.link().send_future(do_request(self.url.clone()))
+                ctx.link().send_future(convert_file(self.request_id, file, self.stencil(), self.charset))
             },
-            AppMsg::SetBrailleState(bs) => self.state = bs,
         }
         return true;
     }
@@ -147,14 +685,23 @@ impl Component for App {
         let btn_cb = ctx.link().callback(|_e: MouseEvent| {
             return Self::Message::GenBraille;
         });
+        let sample_cb = ctx.link().callback(|_e: MouseEvent| {
+            return Self::Message::TrySample;
+        });
         return html! {
             <>
                 <h3>{ "Image to Braille" }</h3>
                 <br />
                 <br />
-                <input oninput={url_cb} type="text" />
+                <input oninput={url_cb} type="text" value={self.url.clone()} />
                 <br />
                 <button onclick={btn_cb}>{ "Go" }</button>
+                { " " }
+                <button onclick={sample_cb}>{ "Try a sample" }</button>
+                { Self::view_file_input(ctx) }
+                { self.view_charset(ctx) }
+                { self.view_threshold(ctx) }
+                { self.view_retry(ctx) }
                 <br />
                 <br />
                 <BrailleDisplay state={self.state.clone()} />